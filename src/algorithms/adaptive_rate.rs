@@ -0,0 +1,159 @@
+/// 按运动状态自适应的更新速率
+///
+/// 大量标签长时间静止的部署中，对静止标签按运动时的速率求解和推送既浪费 CPU 也占用推送带宽。
+/// `AdaptiveRate` 复用 [`SettledPosition`] 的停留判定：标签稳定后按较长间隔输出，
+/// 一旦离开稳定位置立即恢复较短间隔。通过 `PositioningEngine::with_adaptive_rate`
+/// 接入引擎时，状态变化会直接调整引擎的求解时机
+
+use crate::algorithms::postprocess::stage_time;
+use crate::algorithms::{Clock, EpochStrategy, LocationResult, PostProcessor, SettledPosition};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 运动状态
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MotionState {
+    /// 运动中（或尚未稳定）
+    Moving,
+    /// 静止
+    Stationary,
+}
+
+/// 自适应输出速率阶段
+#[derive(Clone, Debug)]
+pub struct AdaptiveRate {
+    /// 运动检测
+    detector: SettledPosition,
+    /// 停留半径
+    radius: f64,
+    /// 运动时的输出间隔
+    moving_interval: Duration,
+    /// 静止时的输出间隔
+    stationary_interval: Duration,
+    /// 当前状态
+    state: MotionState,
+    /// 上次输出的时间
+    last_output: Option<DateTime<Utc>>,
+    /// 计时用的时钟，None 时按结果的时间戳
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl AdaptiveRate {
+    /// 创建自适应速率阶段
+    ///
+    /// # 参数
+    /// - `radius`: 停留半径（坐标单位），位置在半径内停留 `settle_after` 视为静止
+    /// - `settle_after`: 判定静止所需的停留时间
+    /// - `moving_interval`: 运动时的输出间隔
+    /// - `stationary_interval`: 静止时的输出间隔
+    pub fn new(radius: f64, settle_after: Duration, moving_interval: Duration, stationary_interval: Duration) -> Self {
+        AdaptiveRate {
+            detector: SettledPosition::new(radius, settle_after),
+            radius: radius.abs(),
+            moving_interval,
+            stationary_interval,
+            state: MotionState::Moving,
+            last_output: None,
+            clock: None,
+        }
+    }
+
+    /// 按指定时钟计算停留时间和输出间隔
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.set_clock(clock);
+        self
+    }
+
+    /// 当前运动状态
+    pub fn state(&self) -> MotionState {
+        self.state
+    }
+
+    /// 当前建议的更新间隔
+    pub fn interval(&self) -> Duration {
+        match self.state {
+            MotionState::Moving => self.moving_interval,
+            MotionState::Stationary => self.stationary_interval,
+        }
+    }
+
+    /// 当前建议的求解时机策略
+    pub fn epoch_strategy(&self) -> EpochStrategy {
+        EpochStrategy::Timer { interval: self.interval() }
+    }
+
+    /// 输入一个结果并更新运动状态
+    ///
+    /// # 返回
+    /// - 状态是否发生变化
+    pub fn observe(&mut self, result: &LocationResult) -> bool {
+        self.detector.update(result);
+        let state = match self.detector.settled() {
+            Some(settled) if settled.distance_2d_to(result) <= self.radius => MotionState::Stationary,
+            _ => MotionState::Moving,
+        };
+        let changed = state != self.state;
+        self.state = state;
+        changed
+    }
+}
+
+impl PostProcessor for AdaptiveRate {
+    fn name(&self) -> &str {
+        "adaptive_rate"
+    }
+
+    fn process(&mut self, result: LocationResult) -> Option<LocationResult> {
+        // 刚开始运动时立即输出，不等静止间隔结束
+        let started_moving = self.observe(&result) && self.state == MotionState::Moving;
+        let now = stage_time(self.clock.as_ref(), &result);
+        if !started_moving
+            && let Some(last) = self.last_output
+            && (now - last).to_std().unwrap_or_default() < self.interval()
+        {
+            return None;
+        }
+        self.last_output = Some(now);
+        Some(result)
+    }
+
+    fn reset(&mut self) {
+        self.detector.reset();
+        self.state = MotionState::Moving;
+        self.last_output = None;
+    }
+
+    fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.detector.set_clock(clock.clone());
+        self.clock = Some(clock);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f64, second: i64) -> LocationResult {
+        let mut result = LocationResult::new(x, 0.0, 0.0, 0.9, 10.0, "m".to_string(), 3);
+        result.timestamp = DateTime::from_timestamp(1_700_000_000 + second, 0).unwrap();
+        result
+    }
+
+    #[test]
+    fn test_rate_drops_when_stationary_and_recovers_on_motion() {
+        let mut stage = AdaptiveRate::new(30.0, Duration::from_secs(5), Duration::from_secs(1), Duration::from_secs(30));
+
+        let still: usize = (0..60).filter_map(|s| stage.process(at(100.0, s))).count();
+        assert_eq!(stage.state(), MotionState::Stationary);
+        assert_eq!(stage.epoch_strategy(), EpochStrategy::Timer { interval: Duration::from_secs(30) });
+        // 前 5 秒按运动速率输出，之后每 30 秒一次
+        assert!((6..=9).contains(&still));
+
+        // 开始移动立即输出并恢复 1 秒间隔
+        assert!(stage.process(at(500.0, 60)).is_some());
+        assert_eq!(stage.state(), MotionState::Moving);
+        assert!(stage.process(at(600.0, 61)).is_some());
+        assert_eq!(stage.interval(), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,291 @@
+/// 告警规则
+///
+/// 简单的运维告警直接在定位流程内判定，无需外部流处理：
+/// - 设备在区域内停留超过指定时长
+/// - 信标超过指定时长未收到
+/// - 设备移动速度超过上限
+///
+/// 规则可从 JSON 加载，每次满足条件只告警一次，条件解除后重新计数
+
+use crate::algorithms::{BlunavEvent, EventBus, LocationResult, ZoneMap};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// 告警规则
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertRule {
+    /// 设备在区域内连续停留超过 `min_duration_s` 秒
+    DwellInZone {
+        /// 区域名称
+        zone: String,
+        /// 最短停留时长（秒）
+        min_duration_s: f64,
+    },
+    /// 信标超过 `max_silence_s` 秒未收到
+    BeaconOffline {
+        /// 信标 ID
+        beacon_id: String,
+        /// 最长静默时长（秒）
+        max_silence_s: f64,
+    },
+    /// 设备速度超过 `max_speed`（坐标单位/秒）
+    SpeedLimit {
+        /// 速度上限
+        max_speed: f64,
+    },
+}
+
+/// 告警
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alert {
+    /// 触发的规则在规则列表中的序号
+    pub rule_index: usize,
+    /// 相关设备（信标离线告警为信标 ID）
+    pub subject: String,
+    /// 告警说明
+    pub message: String,
+    /// 触发时间
+    pub timestamp: DateTime<Utc>,
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.timestamp.format("%H:%M:%S"), self.subject, self.message)
+    }
+}
+
+/// 告警引擎
+#[derive(Clone, Debug)]
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    zones: ZoneMap,
+    /// (规则序号, 设备) -> 进入区域的时间
+    dwell_since: HashMap<(usize, String), DateTime<Utc>>,
+    /// 信标 -> 最近一次收到的时间
+    beacon_seen: HashMap<String, DateTime<Utc>>,
+    /// 开始监测的时间，从未收到的信标从此刻起计算静默时长
+    started: Option<DateTime<Utc>>,
+    /// 设备 -> 上一次定位结果
+    last_fix: HashMap<String, LocationResult>,
+    /// 已告警且条件尚未解除的 (规则序号, 对象)
+    active: HashSet<(usize, String)>,
+    /// 事件总线
+    events: Option<EventBus>,
+}
+
+impl AlertEngine {
+    /// 创建告警引擎
+    pub fn new(rules: Vec<AlertRule>, zones: ZoneMap) -> Self {
+        AlertEngine {
+            rules,
+            zones,
+            dwell_since: HashMap::new(),
+            beacon_seen: HashMap::new(),
+            started: None,
+            last_fix: HashMap::new(),
+            active: HashSet::new(),
+            events: None,
+        }
+    }
+
+    /// 从 JSON 规则列表创建
+    ///
+    /// 格式如 `[{"type": "speed_limit", "max_speed": 300.0}]`
+    pub fn from_json(json: &str, zones: ZoneMap) -> Result<Self, String> {
+        let rules: Vec<AlertRule> =
+            serde_json::from_str(json).map_err(|e| format!("告警规则解析失败: {}", e))?;
+        for rule in &rules {
+            if let AlertRule::DwellInZone { zone, .. } = rule
+                && zones.get(zone).is_none()
+            {
+                return Err(format!("告警规则引用了不存在的区域: {}", zone));
+            }
+        }
+        Ok(Self::new(rules, zones))
+    }
+
+    /// 设置开始监测的时间（默认为第一次调用 [`AlertEngine::check_offline`] 的时间）
+    pub fn with_start_time(mut self, start: DateTime<Utc>) -> Self {
+        self.started = Some(start);
+        self
+    }
+
+    /// 同时以 `BlunavEvent::Alert` 发送到事件总线
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// 规则列表
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    /// 处理一个设备的定位结果，返回新触发的告警
+    pub fn on_fix(&mut self, device_id: &str, result: &LocationResult) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        let now = result.timestamp;
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            let key = (index, device_id.to_string());
+            match rule {
+                AlertRule::DwellInZone { zone, min_duration_s } => {
+                    let inside = self.zones.get(zone).is_some_and(|z| z.contains(result));
+                    if !inside {
+                        self.dwell_since.remove(&key);
+                        self.active.remove(&key);
+                        continue;
+                    }
+                    let since = *self.dwell_since.entry(key.clone()).or_insert(now);
+                    let dwell = (now - since).num_milliseconds() as f64 / 1000.0;
+                    if dwell >= *min_duration_s && self.active.insert(key) {
+                        alerts.push(Alert {
+                            rule_index: index,
+                            subject: device_id.to_string(),
+                            message: format!("在区域 {} 停留 {:.0} 秒", zone, dwell),
+                            timestamp: now,
+                        });
+                    }
+                }
+                AlertRule::SpeedLimit { max_speed } => {
+                    let Some(previous) = self.last_fix.get(device_id) else {
+                        continue;
+                    };
+                    let seconds = (now - previous.timestamp).num_milliseconds() as f64 / 1000.0;
+                    if seconds <= 0.0 {
+                        continue;
+                    }
+                    let speed = previous.distance_2d_to(result) / seconds;
+                    if speed <= *max_speed {
+                        self.active.remove(&key);
+                    } else if self.active.insert(key) {
+                        alerts.push(Alert {
+                            rule_index: index,
+                            subject: device_id.to_string(),
+                            message: format!("速度 {:.1} 超过上限 {:.1}", speed, max_speed),
+                            timestamp: now,
+                        });
+                    }
+                }
+                AlertRule::BeaconOffline { .. } => {}
+            }
+        }
+
+        self.last_fix.insert(device_id.to_string(), result.clone());
+        self.publish(&alerts);
+        alerts
+    }
+
+    /// 记录收到信标信号
+    pub fn on_beacon_seen(&mut self, beacon_id: &str, timestamp: DateTime<Utc>) {
+        self.beacon_seen.insert(beacon_id.to_string(), timestamp);
+        for (index, rule) in self.rules.iter().enumerate() {
+            if matches!(rule, AlertRule::BeaconOffline { beacon_id: id, .. } if id == beacon_id) {
+                self.active.remove(&(index, beacon_id.to_string()));
+            }
+        }
+    }
+
+    /// 检查信标离线规则，返回新触发的告警
+    ///
+    /// 从未收到过的信标从开始监测的时间起计算静默时长，启动后一直收不到同样会告警。
+    /// 应周期性调用
+    pub fn check_offline(&mut self, now: DateTime<Utc>) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        let started = *self.started.get_or_insert(now);
+        for (index, rule) in self.rules.iter().enumerate() {
+            let AlertRule::BeaconOffline { beacon_id, max_silence_s } = rule else {
+                continue;
+            };
+            let seen = self.beacon_seen.get(beacon_id).copied().unwrap_or(started);
+            let silence = (now - seen).num_milliseconds() as f64 / 1000.0;
+            if silence > *max_silence_s && self.active.insert((index, beacon_id.clone())) {
+                alerts.push(Alert {
+                    rule_index: index,
+                    subject: beacon_id.clone(),
+                    message: format!("信标 {:.0} 秒未收到", silence),
+                    timestamp: now,
+                });
+            }
+        }
+        self.publish(&alerts);
+        alerts
+    }
+
+    fn publish(&self, alerts: &[Alert]) {
+        if let Some(events) = &self.events {
+            for alert in alerts {
+                events.emit(BlunavEvent::Alert(alert.clone()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::Zone;
+    use chrono::TimeZone;
+
+    fn fix_at(x: f64, second: i64) -> LocationResult {
+        let timestamp = Utc.timestamp_opt(1_700_000_000 + second, 0).unwrap();
+        LocationResult::with_timestamp(x, 50.0, 0.0, 0.8, 10.0, "m".to_string(), 3, timestamp)
+    }
+
+    #[test]
+    fn test_dwell_and_speed_rules_fire_once() {
+        let zones = ZoneMap::from_vec(vec![Zone::rectangle("dock", 0.0, 0.0, 100.0, 100.0)]);
+        let json = r#"[
+            {"type": "dwell_in_zone", "zone": "dock", "min_duration_s": 300},
+            {"type": "speed_limit", "max_speed": 200.0}
+        ]"#;
+        let mut engine = AlertEngine::from_json(json, zones.clone()).unwrap();
+
+        assert!(engine.on_fix("forklift", &fix_at(50.0, 0)).is_empty());
+        assert!(engine.on_fix("forklift", &fix_at(55.0, 200)).is_empty());
+        let alerts = engine.on_fix("forklift", &fix_at(50.0, 310));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].rule_index, 0);
+        assert!(engine.on_fix("forklift", &fix_at(50.0, 320)).is_empty());
+
+        // 1 秒移动 500，超速且离开区域
+        let alerts = engine.on_fix("forklift", &fix_at(550.0, 321));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].rule_index, 1);
+
+        assert!(AlertEngine::from_json(r#"[{"type": "dwell_in_zone", "zone": "x", "min_duration_s": 1}]"#, zones).is_err());
+    }
+
+    #[test]
+    fn test_beacon_offline() {
+        let rules = vec![AlertRule::BeaconOffline {
+            beacon_id: "B1".to_string(),
+            max_silence_s: 600.0,
+        }];
+        let events = EventBus::default();
+        let mut received = events.subscribe();
+        let mut engine = AlertEngine::new(rules, ZoneMap::new()).with_events(events);
+
+        let t0 = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        assert!(engine.check_offline(t0).is_empty());
+        engine.on_beacon_seen("B1", t0);
+        assert!(engine.check_offline(t0 + chrono::Duration::seconds(300)).is_empty());
+        assert_eq!(engine.check_offline(t0 + chrono::Duration::seconds(601)).len(), 1);
+        assert!(engine.check_offline(t0 + chrono::Duration::seconds(700)).is_empty());
+        assert_eq!(received.try_recv().unwrap().kind(), "alert");
+
+        // 启动后从未收到的信标同样告警，静默时长从开始监测算起
+        let rules = vec![AlertRule::BeaconOffline {
+            beacon_id: "B2".to_string(),
+            max_silence_s: 600.0,
+        }];
+        let mut engine = AlertEngine::new(rules, ZoneMap::new()).with_start_time(t0);
+        assert!(engine.check_offline(t0 + chrono::Duration::seconds(300)).is_empty());
+        let alerts = engine.check_offline(t0 + chrono::Duration::seconds(601));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].subject, "B2");
+    }
+}
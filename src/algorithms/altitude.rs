@@ -0,0 +1,204 @@
+/// 气压高度与楼层估计
+///
+/// 相对气压变化可以可靠地区分楼层（约 0.12 hPa/米），而仅靠蓝牙的 z 估计误差常超过一层楼高。
+/// `FloorEstimator` 以已知楼层的气压为基准，将气压高度与蓝牙 z 按方差加权融合后判定楼层
+
+use crate::algorithms::{Observation, ObservationKind};
+
+/// 由气压计算相对高度（米）
+///
+/// 国际标准大气公式 h = 44330 · (1 - (p / p_ref)^(1/5.255))，
+/// 结果为相对于 `reference_hpa` 所在高度的高差，气压低于基准时为正
+pub fn pressure_to_relative_altitude(pressure_hpa: f64, reference_hpa: f64) -> f64 {
+    44330.0 * (1.0 - (pressure_hpa / reference_hpa).powf(1.0 / 5.255))
+}
+
+/// 楼层估计结果
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FloorEstimate {
+    /// 楼层序号（`floor_heights` 的下标）
+    pub floor: usize,
+    /// 融合后的高度（坐标单位）
+    pub z: f64,
+    /// 是否使用了气压数据
+    pub used_barometer: bool,
+}
+
+/// 楼层估计器 - 融合气压高度与蓝牙 z
+#[derive(Clone, Debug)]
+pub struct FloorEstimator {
+    /// 各楼层的参考高度（坐标单位），按楼层序号排列
+    floor_heights: Vec<f64>,
+    /// 每米对应的坐标单位（厘米坐标为 100）
+    units_per_meter: f64,
+    /// 基准气压 (hPa) 及其所在楼层
+    reference: Option<(f64, usize)>,
+    /// 最近一次气压 (hPa)
+    pressure: Option<f64>,
+    /// 气压高度标准差（米）
+    baro_std_m: f64,
+    /// 蓝牙 z 标准差（米）
+    ble_std_m: f64,
+    /// 换层滞回（米）：需越过两层中点该距离才切换
+    hysteresis_m: f64,
+    /// 当前楼层
+    current: Option<usize>,
+}
+
+impl FloorEstimator {
+    /// 创建楼层估计器
+    ///
+    /// # 参数
+    /// - `floor_heights`: 各楼层的参考高度（坐标单位）
+    /// - `units_per_meter`: 每米对应的坐标单位
+    pub fn new(floor_heights: Vec<f64>, units_per_meter: f64) -> Self {
+        FloorEstimator {
+            floor_heights,
+            units_per_meter,
+            reference: None,
+            pressure: None,
+            baro_std_m: 0.5,
+            ble_std_m: 2.0,
+            hysteresis_m: 0.5,
+            current: None,
+        }
+    }
+
+    /// 设置气压高度与蓝牙 z 的标准差（米）
+    pub fn with_noise(mut self, baro_std_m: f64, ble_std_m: f64) -> Self {
+        self.baro_std_m = baro_std_m.abs().max(1e-3);
+        self.ble_std_m = ble_std_m.abs().max(1e-3);
+        self
+    }
+
+    /// 设置换层滞回（米）
+    pub fn with_hysteresis(mut self, hysteresis_m: f64) -> Self {
+        self.hysteresis_m = hysteresis_m.max(0.0);
+        self
+    }
+
+    /// 以当前气压作为指定楼层的基准（如进入大楼时在已知楼层校准）
+    pub fn calibrate(&mut self, pressure_hpa: f64, floor: usize) -> Result<(), String> {
+        if floor >= self.floor_heights.len() {
+            return Err(format!("楼层 {} 超出范围 (共 {} 层)", floor, self.floor_heights.len()));
+        }
+        if pressure_hpa <= 0.0 {
+            return Err(format!("气压必须为正数: {}", pressure_hpa));
+        }
+        self.reference = Some((pressure_hpa, floor));
+        self.pressure = Some(pressure_hpa);
+        self.current = Some(floor);
+        Ok(())
+    }
+
+    /// 记录一次气压读数 (hPa)
+    pub fn update_pressure(&mut self, pressure_hpa: f64) {
+        if pressure_hpa > 0.0 {
+            self.pressure = Some(pressure_hpa);
+        }
+    }
+
+    /// 从观测中提取气压读数，返回使用的条数
+    pub fn ingest(&mut self, observations: &[Observation]) -> usize {
+        let mut count = 0;
+        for obs in observations.iter().filter(|o| o.kind == ObservationKind::Pressure) {
+            self.update_pressure(obs.value);
+            count += 1;
+        }
+        count
+    }
+
+    /// 气压推算的高度（坐标单位），未校准时为 None
+    pub fn barometric_z(&self) -> Option<f64> {
+        let (reference_hpa, reference_floor) = self.reference?;
+        let pressure = self.pressure?;
+        let dz = pressure_to_relative_altitude(pressure, reference_hpa) * self.units_per_meter;
+        Some(self.floor_heights[reference_floor] + dz)
+    }
+
+    /// 融合估计高度与楼层
+    ///
+    /// # 参数
+    /// - `ble_z`: 蓝牙定位得到的 z（坐标单位），没有时只用气压
+    ///
+    /// # 返回
+    /// - 楼层估计，或 None 如果两种数据都没有或未配置楼层
+    pub fn estimate(&mut self, ble_z: Option<f64>) -> Option<FloorEstimate> {
+        if self.floor_heights.is_empty() {
+            return None;
+        }
+        let baro_z = self.barometric_z();
+        let z = match (baro_z, ble_z) {
+            (Some(baro), Some(ble)) => {
+                let wb = 1.0 / self.baro_std_m.powi(2);
+                let wl = 1.0 / self.ble_std_m.powi(2);
+                (baro * wb + ble * wl) / (wb + wl)
+            }
+            (Some(baro), None) => baro,
+            (None, Some(ble)) => ble,
+            (None, None) => return None,
+        };
+
+        let nearest = self
+            .floor_heights
+            .iter()
+            .enumerate()
+            .min_by(|a, b| (a.1 - z).abs().total_cmp(&(b.1 - z).abs()))
+            .map(|(i, _)| i)?;
+
+        // 仅当明显靠近另一层时才切换
+        let floor = match self.current {
+            Some(current) if current != nearest => {
+                let margin = (self.floor_heights[current] - z).abs() - (self.floor_heights[nearest] - z).abs();
+                if margin > 2.0 * self.hysteresis_m * self.units_per_meter {
+                    nearest
+                } else {
+                    current
+                }
+            }
+            _ => nearest,
+        };
+        self.current = Some(floor);
+
+        Some(FloorEstimate {
+            floor,
+            z,
+            used_barometer: baro_z.is_some(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::ObservationSource;
+
+    #[test]
+    fn test_relative_altitude() {
+        // 海平面附近每升高约 8.3 米气压下降 1 hPa
+        let h = pressure_to_relative_altitude(1012.25, 1013.25);
+        assert!((h - 8.3).abs() < 0.2);
+        assert_eq!(pressure_to_relative_altitude(1000.0, 1000.0), 0.0);
+    }
+
+    #[test]
+    fn test_barometer_overrides_noisy_ble_z() {
+        // 层高 4 米，厘米坐标
+        let mut estimator = FloorEstimator::new(vec![0.0, 400.0, 800.0], 100.0);
+        estimator.calibrate(1013.25, 0).unwrap();
+
+        // 上升约 8 米（0.96 hPa），蓝牙 z 误判在一层
+        let observations = vec![Observation::new(
+            ObservationSource::Simulator,
+            "baro",
+            ObservationKind::Pressure,
+            1013.25 - 0.96,
+            Some(0),
+        )];
+        assert_eq!(estimator.ingest(&observations), 1);
+        let estimate = estimator.estimate(Some(450.0)).unwrap();
+        assert_eq!(estimate.floor, 2);
+        assert!(estimate.used_barometer);
+        assert!(estimator.calibrate(1013.25, 5).is_err());
+    }
+}
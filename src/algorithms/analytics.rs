@@ -0,0 +1,283 @@
+/// 轨迹分析
+///
+/// 对已记录的定位轨迹做离线分析：
+/// - 停留/移动分段（停留点 + 时长，移动段 + 路径与平均速度）
+/// - 轨迹压缩（考虑时间的 Douglas–Peucker）
+
+use crate::algorithms::{LocationResult, LocationSequence};
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// 停留片段
+#[derive(Clone, Debug, PartialEq)]
+pub struct StopEpisode {
+    /// 停留位置 X（片段内平均值）
+    pub x: f64,
+    /// 停留位置 Y（片段内平均值）
+    pub y: f64,
+    /// 开始时间
+    pub start: DateTime<Utc>,
+    /// 结束时间
+    pub end: DateTime<Utc>,
+    /// 片段内的定位点数
+    pub sample_count: usize,
+}
+
+impl StopEpisode {
+    /// 停留时长
+    pub fn duration(&self) -> Duration {
+        (self.end - self.start).to_std().unwrap_or_default()
+    }
+}
+
+/// 移动片段
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveLeg {
+    /// 路径点 (x, y)，包含前后停留点的衔接位置
+    pub path: Vec<(f64, f64)>,
+    /// 开始时间
+    pub start: DateTime<Utc>,
+    /// 结束时间
+    pub end: DateTime<Utc>,
+    /// 路径长度
+    pub length: f64,
+    /// 平均速度（坐标单位/秒）
+    pub average_speed: f64,
+}
+
+/// 轨迹片段
+#[derive(Clone, Debug, PartialEq)]
+pub enum TrackSegment {
+    /// 停留
+    Stop(StopEpisode),
+    /// 移动
+    Move(MoveLeg),
+}
+
+/// 将轨迹分割为停留与移动片段
+///
+/// 从每个点出发，向后连续落在 `stop_radius` 内的点若持续时间不少于
+/// `min_stop_duration`，则构成一个停留片段；其余点归入相邻停留之间的移动片段
+///
+/// # 参数
+/// - `track`: 按时间排序的定位结果
+/// - `stop_radius`: 停留判定半径（坐标单位）
+/// - `min_stop_duration`: 最短停留时长
+pub fn segment_track(
+    track: &[LocationResult],
+    stop_radius: f64,
+    min_stop_duration: Duration,
+) -> Vec<TrackSegment> {
+    let mut segments = Vec::new();
+    let mut moving: Vec<&LocationResult> = Vec::new();
+    let mut anchor: Option<&LocationResult> = None;
+
+    let mut i = 0;
+    while i < track.len() {
+        let mut j = i + 1;
+        while j < track.len() && track[i].distance_2d_to(&track[j]) <= stop_radius {
+            j += 1;
+        }
+
+        let span = (track[j - 1].timestamp - track[i].timestamp)
+            .to_std()
+            .unwrap_or_default();
+        if j - i >= 2 && span >= min_stop_duration {
+            let stop = &track[i..j];
+            if !moving.is_empty() {
+                segments.push(TrackSegment::Move(build_leg(anchor, &moving, Some(&stop[0]))));
+                moving.clear();
+            }
+
+            let count = stop.len() as f64;
+            segments.push(TrackSegment::Stop(StopEpisode {
+                x: stop.iter().map(|r| r.x).sum::<f64>() / count,
+                y: stop.iter().map(|r| r.y).sum::<f64>() / count,
+                start: stop[0].timestamp,
+                end: stop[stop.len() - 1].timestamp,
+                sample_count: stop.len(),
+            }));
+            anchor = Some(&stop[stop.len() - 1]);
+            i = j;
+        } else {
+            moving.push(&track[i]);
+            i += 1;
+        }
+    }
+
+    if !moving.is_empty() {
+        segments.push(TrackSegment::Move(build_leg(anchor, &moving, None)));
+    }
+    segments
+}
+
+fn build_leg(
+    from: Option<&LocationResult>,
+    moving: &[&LocationResult],
+    to: Option<&LocationResult>,
+) -> MoveLeg {
+    let points: Vec<&LocationResult> = from
+        .into_iter()
+        .chain(moving.iter().copied())
+        .chain(to)
+        .collect();
+
+    let length = points
+        .windows(2)
+        .map(|w| w[0].distance_2d_to(w[1]))
+        .sum::<f64>();
+    let start = points[0].timestamp;
+    let end = points[points.len() - 1].timestamp;
+    let seconds = (end - start).num_milliseconds() as f64 / 1000.0;
+
+    MoveLeg {
+        path: points.iter().map(|r| r.xy()).collect(),
+        start,
+        end,
+        length,
+        average_speed: if seconds > 0.0 { length / seconds } else { 0.0 },
+    }
+}
+
+/// 压缩轨迹（考虑时间的 Douglas–Peucker）
+///
+/// 误差采用同步欧氏距离：点与线段上“同一时刻”插值位置之间的平面距离，
+/// 因此在同一直线上停留或变速的点也会被保留，压缩后的轨迹按时间插值仍与原轨迹一致。
+/// 首尾点始终保留
+///
+/// # 参数
+/// - `track`: 按时间排序的定位结果
+/// - `tolerance`: 允许的最大偏差（坐标单位）
+pub fn simplify_track(track: &[LocationResult], tolerance: f64) -> Vec<LocationResult> {
+    if track.len() <= 2 {
+        return track.to_vec();
+    }
+
+    let mut keep = vec![false; track.len()];
+    keep[0] = true;
+    keep[track.len() - 1] = true;
+
+    let mut stack = vec![(0, track.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let (start, end) = (&track[first], &track[last]);
+        let span = (end.timestamp - start.timestamp).num_milliseconds() as f64;
+
+        let mut worst = (0.0, 0);
+        for (i, point) in track.iter().enumerate().take(last).skip(first + 1) {
+            let t = if span > 0.0 {
+                (point.timestamp - start.timestamp).num_milliseconds() as f64 / span
+            } else {
+                0.0
+            };
+            let x = start.x + (end.x - start.x) * t;
+            let y = start.y + (end.y - start.y) * t;
+            let deviation = ((point.x - x).powi(2) + (point.y - y).powi(2)).sqrt();
+            if deviation > worst.0 {
+                worst = (deviation, i);
+            }
+        }
+
+        if worst.0 > tolerance {
+            keep[worst.1] = true;
+            stack.push((first, worst.1));
+            stack.push((worst.1, last));
+        }
+    }
+
+    track
+        .iter()
+        .zip(keep)
+        .filter(|(_, k)| *k)
+        .map(|(r, _)| r.clone())
+        .collect()
+}
+
+impl LocationSequence {
+    /// 将序列分割为停留与移动片段，参见 [`segment_track`]
+    pub fn segments(&self, stop_radius: f64, min_stop_duration: Duration) -> Vec<TrackSegment> {
+        segment_track(self.all(), stop_radius, min_stop_duration)
+    }
+
+    /// 压缩后的序列，参见 [`simplify_track`]
+    pub fn simplify(&self, tolerance: f64) -> LocationSequence {
+        let mut simplified = LocationSequence::new();
+        for result in simplify_track(self.all(), tolerance) {
+            simplified.push(result);
+        }
+        simplified
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn fix_at(x: f64, y: f64, second: i64) -> LocationResult {
+        let timestamp = Utc.timestamp_opt(1_700_000_000 + second, 0).unwrap();
+        LocationResult::with_timestamp(x, y, 0.0, 0.8, 10.0, "m".to_string(), 3, timestamp)
+    }
+
+    #[test]
+    fn test_segment_stop_move_stop() {
+        let mut sequence = LocationSequence::new();
+        // 在 (0, 0) 附近停留 10 秒
+        for s in 0..=10 {
+            sequence.push(fix_at((s % 2) as f64 * 5.0, 0.0, s));
+        }
+        // 以 100 单位/秒移动
+        for s in 11..=14 {
+            sequence.push(fix_at((s - 10) as f64 * 100.0, 0.0, s));
+        }
+        // 在 (500, 0) 附近停留 10 秒
+        for s in 15..=25 {
+            sequence.push(fix_at(500.0, (s % 2) as f64 * 5.0, s));
+        }
+
+        let segments = sequence.segments(20.0, Duration::from_secs(5));
+        assert_eq!(segments.len(), 3);
+
+        let TrackSegment::Stop(first) = &segments[0] else {
+            panic!("应为停留片段");
+        };
+        assert_eq!(first.duration(), Duration::from_secs(10));
+
+        let TrackSegment::Move(leg) = &segments[1] else {
+            panic!("应为移动片段");
+        };
+        assert!((leg.length - 500.0).abs() < 10.0);
+        assert!((leg.average_speed - 100.0).abs() < 5.0);
+
+        assert!(matches!(segments[2], TrackSegment::Stop(_)));
+    }
+
+    #[test]
+    fn test_simplify_keeps_pauses_on_straight_line() {
+        let mut sequence = LocationSequence::new();
+        // 匀速直线：0..=10 秒从 0 走到 1000
+        for s in 0..=10 {
+            sequence.push(fix_at(s as f64 * 100.0, 0.0, s));
+        }
+        let simplified = sequence.simplify(1.0);
+        assert_eq!(simplified.len(), 2);
+
+        // 同一直线上，在 500 处停留 5 秒：空间上共线，但时间上偏离
+        let mut paused = LocationSequence::new();
+        for s in 0..=5 {
+            paused.push(fix_at(s as f64 * 100.0, 0.0, s));
+        }
+        for s in 6..=10 {
+            paused.push(fix_at(500.0, 0.0, s));
+        }
+        for s in 11..=15 {
+            paused.push(fix_at(500.0 + (s - 10) as f64 * 100.0, 0.0, s));
+        }
+        let simplified = paused.simplify(1.0);
+        let kept: Vec<(f64, i64)> = simplified
+            .all()
+            .iter()
+            .map(|r| (r.x, r.timestamp.timestamp() - 1_700_000_000))
+            .collect();
+        assert_eq!(kept, vec![(0.0, 0), (500.0, 5), (500.0, 10), (1000.0, 15)]);
+    }
+}
@@ -0,0 +1,222 @@
+/// 接收端天线方向图补偿
+///
+/// 网关模式下接收端固定安装，常使用定向天线：标签位于天线主瓣方向时 RSSI 偏高，
+/// 位于旁瓣或背面时偏低，直接换算距离会产生系统性偏差。
+/// 这里为每个接收端配置水平方向图（方位角 -> 增益 dB），按接收端指向标签估计位置的方位角扣除增益。
+///
+/// 不同型号的接收端（蓝牙适配器）报告的 RSSI 还有整体偏差，可为每个接收端设置标定偏移 (dB)，
+/// 或由所有接收端都能听到的参考信标估计，在融合之前一并扣除
+
+use crate::algorithms::{BeaconSet, DistanceEstimator, SignalReadings};
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+
+/// 水平天线方向图
+#[derive(Clone, Debug, PartialEq)]
+pub struct AntennaPattern {
+    /// 主瓣朝向（弧度，与 x 轴夹角）
+    pub boresight: f64,
+    /// (相对主瓣的方位角（弧度）, 增益 dB)，按角度升序排列
+    gains: Vec<(f64, f64)>,
+}
+
+impl AntennaPattern {
+    /// 全向天线（增益恒为 0）
+    pub fn omni() -> Self {
+        AntennaPattern {
+            boresight: 0.0,
+            gains: vec![(0.0, 0.0)],
+        }
+    }
+
+    /// 由增益表创建
+    ///
+    /// # 参数
+    /// - `boresight`: 主瓣朝向（弧度）
+    /// - `gains`: (相对主瓣的方位角（度）, 增益 dB)，角度之间线性插值，首尾环绕
+    pub fn from_table(boresight: f64, gains: &[(f64, f64)]) -> Result<Self, String> {
+        if gains.is_empty() {
+            return Err("天线方向图至少需要一个增益点".to_string());
+        }
+        if gains.iter().any(|(angle, gain)| !angle.is_finite() || !gain.is_finite()) {
+            return Err("天线方向图包含无效数值".to_string());
+        }
+        let mut gains: Vec<(f64, f64)> = gains
+            .iter()
+            .map(|(angle, gain)| (angle.to_radians().rem_euclid(TAU), *gain))
+            .collect();
+        gains.sort_by(|a, b| a.0.total_cmp(&b.0));
+        gains.dedup_by(|a, b| (a.0 - b.0).abs() < 1e-12);
+        Ok(AntennaPattern { boresight, gains })
+    }
+
+    /// 指定方位角上的增益 (dB)
+    ///
+    /// # 参数
+    /// - `bearing`: 接收端指向标签的方位角（弧度，与 x 轴夹角）
+    pub fn gain(&self, bearing: f64) -> f64 {
+        let angle = (bearing - self.boresight).rem_euclid(TAU);
+        let n = self.gains.len();
+        if n == 1 {
+            return self.gains[0].1;
+        }
+        // 找到 angle 所在的区间，最后一个点与第一个点之间环绕
+        let upper = self.gains.iter().position(|(a, _)| *a >= angle).unwrap_or(n);
+        let (a0, g0) = if upper == 0 { (self.gains[n - 1].0 - TAU, self.gains[n - 1].1) } else { self.gains[upper - 1] };
+        let (a1, g1) = if upper == n { (self.gains[0].0 + TAU, self.gains[0].1) } else { self.gains[upper] };
+        if a1 - a0 < 1e-12 {
+            return g1;
+        }
+        g0 + (g1 - g0) * (angle - a0) / (a1 - a0)
+    }
+}
+
+/// 各接收端的天线方向图
+#[derive(Clone, Debug, Default)]
+pub struct AntennaCorrection {
+    /// 接收端 ID -> 方向图
+    patterns: HashMap<String, AntennaPattern>,
+    /// 接收端 ID -> RSSI 标定偏移 (dB)，正值表示该接收端报告偏高
+    offsets: HashMap<String, f64>,
+}
+
+impl AntennaCorrection {
+    /// 创建空配置（所有接收端视为全向）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 为接收端设置方向图
+    pub fn with_pattern(mut self, receiver_id: impl Into<String>, pattern: AntennaPattern) -> Self {
+        self.patterns.insert(receiver_id.into(), pattern);
+        self
+    }
+
+    /// 为接收端设置 RSSI 标定偏移 (dB)，正值表示该接收端报告偏高
+    pub fn with_offset(mut self, receiver_id: impl Into<String>, offset_db: f64) -> Self {
+        self.offsets.insert(receiver_id.into(), offset_db);
+        self
+    }
+
+    /// 接收端的方向图
+    pub fn pattern(&self, receiver_id: &str) -> Option<&AntennaPattern> {
+        self.patterns.get(receiver_id)
+    }
+
+    /// 接收端的 RSSI 标定偏移 (dB)，未设置时为 0
+    pub fn offset(&self, receiver_id: &str) -> f64 {
+        self.offsets.get(receiver_id).copied().unwrap_or(0.0)
+    }
+
+    /// 由参考信标估计各接收端的标定偏移
+    ///
+    /// 偏移为接收端报告的 RSSI 与按模型、距离和方向图增益推算的 RSSI 之差，
+    /// 已设置的偏移被覆盖；模型对接收端不给出 [`DistanceEstimator::expected_rssi`] 时跳过该接收端
+    ///
+    /// # 参数
+    /// - `reference`: 各接收端听到参考信标的 RSSI（接收端 ID -> RSSI，建议取一段时间的均值）
+    /// - `reference_position`: 参考信标位置 (x, y, z)
+    /// - `receivers`: 接收端位置（以信标集合表示）
+    /// - `model`: 测距模型
+    pub fn with_reference_offsets(
+        mut self,
+        reference: &SignalReadings,
+        reference_position: (f64, f64, f64),
+        receivers: &BeaconSet,
+        model: &(impl DistanceEstimator + ?Sized),
+    ) -> Self {
+        let (x, y, z) = reference_position;
+        for (receiver_id, rssi) in reference.all() {
+            let Some(receiver) = receivers.get(receiver_id) else {
+                continue;
+            };
+            let distance = ((x - receiver.x).powi(2) + (y - receiver.y).powi(2) + (z - receiver.z).powi(2)).sqrt();
+            let gain = self.patterns.get(receiver_id).map_or(0.0, |p| p.gain((y - receiver.y).atan2(x - receiver.x)));
+            if let Some(expected) = model.expected_rssi(receiver_id, distance) {
+                self.offsets.insert(receiver_id.clone(), *rssi as f64 - expected - gain);
+            }
+        }
+        self
+    }
+
+    /// 补偿一组读数（扣除方向图增益与标定偏移）
+    ///
+    /// # 参数
+    /// - `readings`: 接收端 ID -> RSSI
+    /// - `receivers`: 接收端位置（以信标集合表示）
+    /// - `tag_position`: 标签的估计位置（如上一次定位结果）
+    pub fn correct_readings(
+        &self,
+        readings: &SignalReadings,
+        receivers: &BeaconSet,
+        tag_position: (f64, f64),
+    ) -> SignalReadings {
+        let mut corrected = SignalReadings::new();
+        for (receiver_id, rssi) in readings.all() {
+            // 没有方向图或位置未知的接收端不补偿
+            let gain = match (self.patterns.get(receiver_id), receivers.get(receiver_id)) {
+                (Some(pattern), Some(receiver)) => {
+                    pattern.gain((tag_position.1 - receiver.y).atan2(tag_position.0 - receiver.x))
+                }
+                _ => 0.0,
+            };
+            let corrected_rssi = *rssi as f64 - gain - self.offset(receiver_id);
+            corrected.add(receiver_id.clone(), corrected_rssi.round() as i16);
+        }
+        corrected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{Beacon, RSSIModel};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_pattern_interpolation_wraps() {
+        let pattern = AntennaPattern::from_table(PI / 2.0, &[(0.0, 6.0), (90.0, 0.0), (180.0, -10.0), (270.0, 0.0)]).unwrap();
+        assert!((pattern.gain(PI / 2.0) - 6.0).abs() < 1e-9);
+        assert!((pattern.gain(-PI / 2.0) + 10.0).abs() < 1e-9);
+        assert!((pattern.gain(PI / 2.0 + PI / 4.0) - 3.0).abs() < 1e-9);
+        assert!((pattern.gain(PI / 2.0 - PI / 4.0) - 3.0).abs() < 1e-9);
+        assert_eq!(AntennaPattern::omni().gain(1.0), 0.0);
+        assert!(AntennaPattern::from_table(0.0, &[]).is_err());
+    }
+
+    #[test]
+    fn test_correct_readings_by_bearing() {
+        let receivers = BeaconSet::from_vec(vec![
+            Beacon::new("GW1".to_string(), String::new(), 0.0, 0.0, 0.0),
+            Beacon::new("GW2".to_string(), String::new(), 1000.0, 0.0, 0.0),
+        ]);
+        // GW1 朝向 +x，标签位于其主瓣方向
+        let correction = AntennaCorrection::new()
+            .with_pattern("GW1", AntennaPattern::from_table(0.0, &[(0.0, 5.0), (180.0, -5.0)]).unwrap());
+        let readings = SignalReadings::from_pairs(vec![("GW1", -60), ("GW2", -70)]);
+
+        let corrected = correction.correct_readings(&readings, &receivers, (500.0, 0.0));
+        assert_eq!(corrected.get("GW1"), Some(-65));
+        assert_eq!(corrected.get("GW2"), Some(-70));
+    }
+
+    #[test]
+    fn test_receiver_offsets_from_reference_beacon() {
+        let receivers = BeaconSet::from_vec(vec![
+            Beacon::new("GW1".to_string(), String::new(), 0.0, 0.0, 0.0),
+            Beacon::new("GW2".to_string(), String::new(), 1000.0, 0.0, 0.0),
+        ]);
+        let model = RSSIModel::default();
+        // 参考信标位于两者中点，GW2 的适配器整体偏高 4 dB
+        let expected = model.distance_to_rssi(500.0).round() as i16;
+        let reference = SignalReadings::from_pairs(vec![("GW1", expected), ("GW2", expected + 4), ("GW9", -50)]);
+        let correction = AntennaCorrection::new().with_reference_offsets(&reference, (500.0, 0.0, 0.0), &receivers, &model);
+        assert!(correction.offset("GW1").abs() < 0.5);
+        assert!((correction.offset("GW2") - 4.0).abs() < 0.5);
+        assert_eq!(correction.offset("GW9"), 0.0);
+
+        let readings = SignalReadings::from_pairs(vec![("GW1", -70), ("GW2", -66)]);
+        let corrected = correction.with_offset("GW2", 4.0).correct_readings(&readings, &receivers, (500.0, 0.0));
+        assert_eq!(corrected.get("GW1"), corrected.get("GW2"));
+    }
+}
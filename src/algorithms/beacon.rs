@@ -1,5 +1,6 @@
 /// 蓝牙信标定义和相关数据结构
 
+use regex::Regex;
 use std::collections::HashMap;
 
 /// 单个蓝牙信标定义
@@ -15,12 +16,67 @@ pub struct Beacon {
     pub y: f64,
     /// Z 坐标 - 高度（单位可配置，默认厘米）
     pub z: f64,
+    /// 其他平台上的标识（如 macOS 上的 UUID），参见 `DeviceId`
+    pub aliases: Vec<String>,
+    /// 按广播名称匹配（地址随平台变化或未知时使用）
+    pub name_match: Option<NameMatcher>,
+}
+
+/// 广播名称匹配规则
+#[derive(Clone, Debug)]
+pub enum NameMatcher {
+    /// 完全相同的名称
+    Exact(String),
+    /// 正则表达式（如 `^RFstar_C5D6$`）
+    Pattern(Regex),
+}
+
+impl NameMatcher {
+    /// 名称是否匹配
+    pub fn is_match(&self, name: &str) -> bool {
+        match self {
+            NameMatcher::Exact(expected) => expected == name,
+            NameMatcher::Pattern(regex) => regex.is_match(name),
+        }
+    }
+}
+
+/// 正则按表达式源码比较
+impl PartialEq for NameMatcher {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (NameMatcher::Exact(a), NameMatcher::Exact(b)) => a == b,
+            (NameMatcher::Pattern(a), NameMatcher::Pattern(b)) => a.as_str() == b.as_str(),
+            _ => false,
+        }
+    }
 }
 
 impl Beacon {
     /// 创建新的信标
     pub fn new(id: String, name: String, x: f64, y: f64, z: f64) -> Self {
-        Beacon { id, name, x, y, z }
+        Beacon {
+            id,
+            name,
+            x,
+            y,
+            z,
+            aliases: Vec::new(),
+            name_match: None,
+        }
+    }
+
+    /// 按广播名称精确匹配
+    pub fn with_name_exact(mut self, name: impl Into<String>) -> Self {
+        self.name_match = Some(NameMatcher::Exact(name.into()));
+        self
+    }
+
+    /// 按广播名称正则匹配
+    pub fn with_name_pattern(mut self, pattern: &str) -> Result<Self, String> {
+        let regex = Regex::new(pattern).map_err(|e| format!("名称正则表达式无效: {}", e))?;
+        self.name_match = Some(NameMatcher::Pattern(regex));
+        Ok(self)
     }
 
     /// 从元组创建（简洁方式）
@@ -42,7 +98,141 @@ impl Beacon {
     }
 }
 
+/// 平面仿射变换 - 作用于 x/y，z 单独缩放和平移
+///
+/// 变换公式: (x', y') = M * (x, y) + t, z' = z * z_scale + z_offset
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AffineTransform {
+    /// 2x2 线性部分 [[m00, m01], [m10, m11]]
+    pub matrix: [[f64; 2]; 2],
+    /// 平移 (tx, ty)
+    pub translation: (f64, f64),
+    /// z 缩放系数
+    pub z_scale: f64,
+    /// z 平移量
+    pub z_offset: f64,
+}
+
+impl AffineTransform {
+    /// 恒等变换
+    pub fn identity() -> Self {
+        AffineTransform {
+            matrix: [[1.0, 0.0], [0.0, 1.0]],
+            translation: (0.0, 0.0),
+            z_scale: 1.0,
+            z_offset: 0.0,
+        }
+    }
+
+    /// 平移变换
+    pub fn translation(dx: f64, dy: f64, dz: f64) -> Self {
+        AffineTransform {
+            translation: (dx, dy),
+            z_offset: dz,
+            ..Self::identity()
+        }
+    }
+
+    /// 绕原点旋转（弧度，逆时针为正），z 不变
+    pub fn rotation(angle_rad: f64) -> Self {
+        let (sin, cos) = angle_rad.sin_cos();
+        AffineTransform {
+            matrix: [[cos, -sin], [sin, cos]],
+            ..Self::identity()
+        }
+    }
+
+    /// 绕指定点旋转（弧度，逆时针为正）
+    pub fn rotation_about(angle_rad: f64, cx: f64, cy: f64) -> Self {
+        Self::translation(-cx, -cy, 0.0)
+            .then(&Self::rotation(angle_rad))
+            .then(&Self::translation(cx, cy, 0.0))
+    }
+
+    /// 等比缩放（包括 z），例如米转厘米用 100.0
+    pub fn scale(factor: f64) -> Self {
+        AffineTransform {
+            matrix: [[factor, 0.0], [0.0, factor]],
+            z_scale: factor,
+            ..Self::identity()
+        }
+    }
+
+    /// 组合变换：先应用 self，再应用 next
+    pub fn then(&self, next: &AffineTransform) -> Self {
+        let a = &self.matrix;
+        let b = &next.matrix;
+        let (tx, ty) = self.translation;
+        AffineTransform {
+            matrix: [
+                [
+                    b[0][0] * a[0][0] + b[0][1] * a[1][0],
+                    b[0][0] * a[0][1] + b[0][1] * a[1][1],
+                ],
+                [
+                    b[1][0] * a[0][0] + b[1][1] * a[1][0],
+                    b[1][0] * a[0][1] + b[1][1] * a[1][1],
+                ],
+            ],
+            translation: (
+                b[0][0] * tx + b[0][1] * ty + next.translation.0,
+                b[1][0] * tx + b[1][1] * ty + next.translation.1,
+            ),
+            z_scale: self.z_scale * next.z_scale,
+            z_offset: self.z_offset * next.z_scale + next.z_offset,
+        }
+    }
+
+    /// 变换一个 3D 坐标
+    pub fn apply(&self, x: f64, y: f64, z: f64) -> (f64, f64, f64) {
+        let m = &self.matrix;
+        (
+            m[0][0] * x + m[0][1] * y + self.translation.0,
+            m[1][0] * x + m[1][1] * y + self.translation.1,
+            z * self.z_scale + self.z_offset,
+        )
+    }
+}
+
+impl Default for AffineTransform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// 两个信标集合之间的差异
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BeaconSetDiff {
+    /// 新增的信标 ID
+    pub added: Vec<String>,
+    /// 删除的信标 ID
+    pub removed: Vec<String>,
+    /// 位置变化的信标 (ID, 移动距离)
+    pub moved: Vec<(String, f64)>,
+    /// 仅名称、别名或名称匹配规则等属性变化的信标 ID
+    pub modified: Vec<String>,
+}
+
+impl BeaconSetDiff {
+    /// 是否没有任何差异
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// 合并时同一 ID 信标的冲突处理策略
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// 保留本地配置，只添加新信标
+    KeepExisting,
+    /// 使用传入配置覆盖同 ID 信标，本地多出的信标保留
+    TakeIncoming,
+    /// 与传入配置完全一致（覆盖并删除传入配置中没有的信标）
+    Replace,
+}
+
 /// 信标集合管理器 - 支持多个不同的信标配置集
+#[derive(Clone, Debug)]
 pub struct BeaconSet {
     /// 信标 ID -> Beacon 的映射
     beacons: HashMap<String, Beacon>,
@@ -121,6 +311,112 @@ impl BeaconSet {
     pub fn iter(&self) -> impl Iterator<Item = (&String, &Beacon)> {
         self.beacons.iter()
     }
+
+    /// 计算从当前集合变为 `other` 所需的变更，各列表按 ID 排序
+    pub fn diff(&self, other: &BeaconSet) -> BeaconSetDiff {
+        let mut diff = BeaconSetDiff::default();
+        for (id, beacon) in &other.beacons {
+            match self.beacons.get(id) {
+                None => diff.added.push(id.clone()),
+                Some(current) => {
+                    let distance = current.distance_to(beacon);
+                    if distance > 1e-9 {
+                        diff.moved.push((id.clone(), distance));
+                    } else if current.name != beacon.name || current.aliases != beacon.aliases || current.name_match != beacon.name_match {
+                        diff.modified.push(id.clone());
+                    }
+                }
+            }
+        }
+        diff.removed = self
+            .beacons
+            .keys()
+            .filter(|id| !other.beacons.contains_key(*id))
+            .cloned()
+            .collect();
+
+        diff.added.sort();
+        diff.removed.sort();
+        diff.moved.sort_by(|a, b| a.0.cmp(&b.0));
+        diff.modified.sort();
+        diff
+    }
+
+    /// 合并另一个集合，返回实际应用的变更
+    pub fn merge(&mut self, other: BeaconSet, policy: ConflictPolicy) -> BeaconSetDiff {
+        let mut applied = self.diff(&other);
+        match policy {
+            ConflictPolicy::KeepExisting => {
+                applied.removed.clear();
+                applied.moved.clear();
+                applied.modified.clear();
+                for (id, beacon) in other.beacons {
+                    self.beacons.entry(id).or_insert(beacon);
+                }
+            }
+            ConflictPolicy::TakeIncoming => {
+                applied.removed.clear();
+                self.beacons.extend(other.beacons);
+            }
+            ConflictPolicy::Replace => {
+                self.beacons = other.beacons;
+            }
+        }
+        applied
+    }
+
+    /// 根据广播地址和名称匹配已配置的信标
+    ///
+    /// 冲突处理规则（按优先级）：
+    /// 1. 地址匹配（主 ID 或别名）优先于名称匹配
+    /// 2. 精确名称匹配优先于正则匹配
+    /// 3. 同一优先级下匹配到多个信标视为有歧义，返回 None
+    pub fn match_advertisement(&self, address: &str, name: Option<&str>) -> Option<&Beacon> {
+        if let Some(beacon) = self.resolve(address) {
+            return Some(beacon);
+        }
+        let name = name?;
+
+        let unique = |exact: bool| -> Option<Option<&Beacon>> {
+            let mut found = self.beacons.values().filter(|b| match &b.name_match {
+                Some(m @ NameMatcher::Exact(_)) if exact => m.is_match(name),
+                Some(m @ NameMatcher::Pattern(_)) if !exact => m.is_match(name),
+                _ => false,
+            });
+            let first = found.next()?;
+            Some(if found.next().is_none() { Some(first) } else { None })
+        };
+
+        match unique(true) {
+            Some(result) => result,
+            None => unique(false).flatten(),
+        }
+    }
+
+    /// 对所有信标坐标应用仿射变换
+    pub fn transform(&mut self, transform: &AffineTransform) {
+        for beacon in self.beacons.values_mut() {
+            let (x, y, z) = transform.apply(beacon.x, beacon.y, beacon.z);
+            beacon.x = x;
+            beacon.y = y;
+            beacon.z = z;
+        }
+    }
+
+    /// 平移所有信标
+    pub fn translate(&mut self, dx: f64, dy: f64, dz: f64) {
+        self.transform(&AffineTransform::translation(dx, dy, dz));
+    }
+
+    /// 绕指定点旋转所有信标（弧度，逆时针为正）
+    pub fn rotate(&mut self, angle_rad: f64, cx: f64, cy: f64) {
+        self.transform(&AffineTransform::rotation_about(angle_rad, cx, cy));
+    }
+
+    /// 等比缩放所有信标坐标（包括高度）
+    pub fn scale(&mut self, factor: f64) {
+        self.transform(&AffineTransform::scale(factor));
+    }
 }
 
 impl Default for BeaconSet {
@@ -155,4 +451,88 @@ mod tests {
         assert_eq!(set.len(), 1);
         assert!(set.get("B1").is_some());
     }
+
+    #[test]
+    fn test_beacon_set_transform() {
+        let mut set = BeaconSet::new();
+        set.add_beacon(Beacon::new("B1".to_string(), "B1".to_string(), 1.0, 0.0, 2.0));
+
+        // 米转厘米后绕原点旋转 90°，再平移
+        let transform = AffineTransform::scale(100.0)
+            .then(&AffineTransform::rotation(std::f64::consts::FRAC_PI_2))
+            .then(&AffineTransform::translation(10.0, 20.0, 0.0));
+        set.transform(&transform);
+
+        let (x, y, z) = set.get("B1").unwrap().coordinates();
+        assert!((x - 10.0).abs() < 1e-9);
+        assert!((y - 120.0).abs() < 1e-9);
+        assert!((z - 200.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_beacon_set_rotate_about_point() {
+        let mut set = BeaconSet::new();
+        set.add_beacon(Beacon::new("B1".to_string(), "B1".to_string(), 2.0, 1.0, 0.0));
+        set.rotate(std::f64::consts::PI, 1.0, 1.0);
+
+        let (x, y, _) = set.get("B1").unwrap().coordinates();
+        assert!((x - 0.0).abs() < 1e-9);
+        assert!((y - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_diff_and_merge() {
+        let beacon = |id: &str, x: f64| Beacon::new(id.to_string(), id.to_string(), x, 0.0, 0.0);
+        let local = BeaconSet::from_vec(vec![beacon("B1", 0.0), beacon("B2", 100.0), beacon("B3", 200.0)]);
+        let mut renamed = beacon("B3", 200.0);
+        renamed.name = "hall".to_string();
+        let matched = beacon("B1", 0.0).with_name_pattern("^RFstar_").unwrap();
+        let incoming = BeaconSet::from_vec(vec![matched.clone(), beacon("B2", 130.0), renamed, beacon("B4", 0.0)]);
+
+        let diff = local.diff(&incoming);
+        assert_eq!(diff.added, vec!["B4"]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.moved, vec![("B2".to_string(), 30.0)]);
+        assert_eq!(diff.modified, vec!["B1", "B3"]);
+
+        // 名称匹配规则按内容比较：相同的正则不算修改，改为精确匹配算修改
+        let same = BeaconSet::from_vec(vec![beacon("B1", 0.0).with_name_pattern("^RFstar_").unwrap()]);
+        assert!(BeaconSet::from_vec(vec![matched.clone()]).diff(&same).modified.is_empty());
+        let exact = BeaconSet::from_vec(vec![matched.with_name_exact("RFstar_C5D6")]);
+        assert_eq!(same.diff(&exact).modified, vec!["B1"]);
+
+        let mut keep = local.clone();
+        let applied = keep.merge(incoming.clone(), ConflictPolicy::KeepExisting);
+        assert_eq!((applied.added.len(), applied.moved.len()), (1, 0));
+        assert_eq!(keep.get("B2").unwrap().x, 100.0);
+
+        let mut replace = local.clone();
+        replace.merge(BeaconSet::from_vec(vec![beacon("B1", 5.0)]), ConflictPolicy::Replace);
+        assert_eq!(replace.len(), 1);
+        assert_eq!(replace.diff(&local).added, vec!["B2", "B3"]);
+    }
+
+    #[test]
+    fn test_match_advertisement_priority() {
+        let set = BeaconSet::from_vec(vec![
+            Beacon::new("AA:BB:CC:DD:EE:01".to_string(), "C5D6".to_string(), 0.0, 0.0, 0.0)
+                .with_name_exact("RFstar_C5D6"),
+            Beacon::new("B2".to_string(), "rf".to_string(), 0.0, 0.0, 0.0)
+                .with_name_pattern("^RFstar_")
+                .unwrap(),
+            Beacon::new("B3".to_string(), "rf2".to_string(), 0.0, 0.0, 0.0)
+                .with_name_pattern("_0CF1$")
+                .unwrap(),
+        ]);
+
+        // 地址优先
+        assert_eq!(set.match_advertisement("aa:bb:cc:dd:ee:01", Some("RFstar_0CF1")).unwrap().id, "AA:BB:CC:DD:EE:01");
+        // 精确名称优先于正则
+        assert_eq!(set.match_advertisement("unknown", Some("RFstar_C5D6")).unwrap().id, "AA:BB:CC:DD:EE:01");
+        // 唯一正则匹配
+        assert_eq!(set.match_advertisement("unknown", Some("RFstar_FBFC")).unwrap().id, "B2");
+        // 两个正则同时匹配，有歧义
+        assert!(set.match_advertisement("unknown", Some("RFstar_0CF1")).is_none());
+        assert!(Beacon::new("B4".to_string(), String::new(), 0.0, 0.0, 0.0).with_name_pattern("(").is_err());
+    }
 }
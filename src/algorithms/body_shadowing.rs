@@ -0,0 +1,129 @@
+/// 人体遮挡补偿
+///
+/// 佩戴式标签（胸卡、手环）与信标之间常隔着佩戴者的身体，
+/// RSSI 比同距离下的空旷环境低若干 dB，直接换算会高估距离。
+/// 本模块在换算距离前把这部分衰减补回去；已知朝向时按信标相对朝向的角度补偿
+
+use crate::algorithms::{BeaconSet, SignalReadings};
+use std::f64::consts::PI;
+
+/// 人体遮挡补偿配置
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BodyShadowing {
+    /// 信标位于佩戴者正前方时的衰减 (dB)
+    pub front_db: f64,
+    /// 信标位于佩戴者正后方时的衰减 (dB)
+    pub back_db: f64,
+}
+
+impl BodyShadowing {
+    /// 与方向无关的固定衰减
+    pub fn uniform(attenuation_db: f64) -> Self {
+        BodyShadowing {
+            front_db: attenuation_db,
+            back_db: attenuation_db,
+        }
+    }
+
+    /// 按方向变化的衰减
+    pub fn directional(front_db: f64, back_db: f64) -> Self {
+        BodyShadowing { front_db, back_db }
+    }
+
+    /// 胸卡的典型值：正面 1 dB，背面 8 dB
+    pub fn badge() -> Self {
+        Self::directional(1.0, 8.0)
+    }
+
+    /// 指定方向上的衰减 (dB)
+    ///
+    /// # 参数
+    /// - `bearing`: 佩戴者指向信标的方位角（弧度，与 x 轴夹角）
+    /// - `heading`: 佩戴者朝向（弧度）；未知时取正前方与正后方的平均值
+    pub fn attenuation(&self, bearing: f64, heading: Option<f64>) -> f64 {
+        match heading {
+            // 朝向与信标方向夹角为 0 时取正面值，夹角为 π 时取背面值
+            Some(heading) => {
+                let cos = (bearing - heading).cos();
+                self.front_db + (self.back_db - self.front_db) * (1.0 - cos) / 2.0
+            }
+            None => (self.front_db + self.back_db) / 2.0,
+        }
+    }
+
+    /// 补偿单个 RSSI
+    pub fn correct_rssi(&self, rssi: f64, bearing: f64, heading: Option<f64>) -> f64 {
+        rssi + self.attenuation(bearing, heading)
+    }
+
+    /// 补偿一组读数
+    ///
+    /// # 参数
+    /// - `readings`: 原始读数
+    /// - `beacons`: 信标配置（用于计算方位角）
+    /// - `position`: 佩戴者的近似位置（如上一次定位结果）
+    /// - `heading`: 佩戴者朝向（如由滤波器速度得到）
+    pub fn correct_readings(
+        &self,
+        readings: &SignalReadings,
+        beacons: &BeaconSet,
+        position: (f64, f64),
+        heading: Option<f64>,
+    ) -> SignalReadings {
+        let mut corrected = SignalReadings::new();
+        for (beacon_id, rssi) in readings.all() {
+            let bearing = beacons
+                .get(beacon_id)
+                .map(|b| (b.y - position.1).atan2(b.x - position.0))
+                .unwrap_or(0.0);
+            // 位置未知的信标只能使用方向无关的平均衰减
+            let heading = heading.filter(|_| beacons.get(beacon_id).is_some());
+            let value = self.correct_rssi(*rssi as f64, bearing, heading);
+            corrected.add(beacon_id.clone(), value.round() as i16);
+        }
+        corrected
+    }
+}
+
+/// 由速度估计朝向
+///
+/// # 返回
+/// - 朝向（弧度，范围 (-π, π]），或 None 如果速度低于 `min_speed`（静止时朝向不可靠）
+pub fn heading_from_velocity(vx: f64, vy: f64, min_speed: f64) -> Option<f64> {
+    if vx.hypot(vy) < min_speed {
+        return None;
+    }
+    let heading = vy.atan2(vx);
+    Some(if heading <= -PI { heading + 2.0 * PI } else { heading })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::Beacon;
+
+    #[test]
+    fn test_directional_attenuation() {
+        let shadow = BodyShadowing::badge();
+        assert!((shadow.attenuation(0.0, Some(0.0)) - 1.0).abs() < 1e-9);
+        assert!((shadow.attenuation(PI, Some(0.0)) - 8.0).abs() < 1e-9);
+        assert!((shadow.attenuation(PI / 2.0, Some(0.0)) - 4.5).abs() < 1e-9);
+        assert!((shadow.attenuation(1.0, None) - 4.5).abs() < 1e-9);
+        assert!(heading_from_velocity(0.1, 0.0, 1.0).is_none());
+    }
+
+    #[test]
+    fn test_correct_readings_uses_beacon_bearing() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("FRONT".to_string(), String::new(), 100.0, 0.0, 0.0),
+            Beacon::new("BACK".to_string(), String::new(), -100.0, 0.0, 0.0),
+        ]);
+        let readings = SignalReadings::from_pairs(vec![("FRONT", -60), ("BACK", -70), ("OTHER", -80)]);
+        let heading = heading_from_velocity(50.0, 0.0, 10.0);
+
+        let corrected = BodyShadowing::badge().correct_readings(&readings, &beacons, (0.0, 0.0), heading);
+        assert_eq!(corrected.get("FRONT"), Some(-59));
+        assert_eq!(corrected.get("BACK"), Some(-62));
+        assert_eq!(corrected.get("OTHER"), Some(-76));
+    }
+}
@@ -0,0 +1,479 @@
+/// 多房间标定会话
+///
+/// 按参考点逐一采集信号样本，一次完成：
+/// - RSSI 模型拟合（利用参考点与信标的已知距离）
+/// - 指纹数据库构建
+///
+/// 参考点可标注设备朝向（如 "north"、"south"），朝向不少于 2 种时
+/// 按朝向分组拟合，得到与朝向无关的平均模型及各朝向的偏移
+///
+/// 典型流程：`start_point` -> `record` / `collect_for` -> `finish_point`，
+/// 所有参考点采集完毕后调用 `finish`
+
+use crate::algorithms::{
+    BeaconSet, DistanceUnit, FingerprintDatabase, Observation, RSSIModel, ReferencePoint,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// 单个参考点的采集数据
+#[derive(Clone, Debug)]
+pub struct CalibrationPoint {
+    /// 参考点标签
+    pub label: String,
+    /// X 坐标
+    pub x: f64,
+    /// Y 坐标
+    pub y: f64,
+    /// Z 坐标
+    pub z: f64,
+    /// 采集时的设备朝向
+    pub orientation: Option<String>,
+    /// beacon_id -> 原始 RSSI 样本
+    pub samples: HashMap<String, Vec<i16>>,
+}
+
+impl CalibrationPoint {
+    /// 样本总数
+    pub fn sample_count(&self) -> usize {
+        self.samples.values().map(|v| v.len()).sum()
+    }
+}
+
+/// 单个信标在某一参考距离上的 RSSI 直方图
+#[derive(Clone, Debug, PartialEq)]
+pub struct RssiHistogram {
+    /// 信标 ID
+    pub beacon_id: String,
+    /// 参考点与信标的距离
+    pub distance: f64,
+    /// RSSI -> 出现次数
+    pub counts: BTreeMap<i16, usize>,
+}
+
+impl RssiHistogram {
+    /// 样本总数
+    pub fn sample_count(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// RSSI 均值
+    pub fn mean(&self) -> f64 {
+        let total = self.sample_count() as f64;
+        if total == 0.0 {
+            return 0.0;
+        }
+        self.counts.iter().map(|(r, c)| *r as f64 * *c as f64).sum::<f64>() / total
+    }
+
+    /// RSSI 标准差
+    pub fn std_dev(&self) -> f64 {
+        let total = self.sample_count() as f64;
+        if total == 0.0 {
+            return 0.0;
+        }
+        let mean = self.mean();
+        (self.counts.iter().map(|(r, c)| (*r as f64 - mean).powi(2) * *c as f64).sum::<f64>() / total).sqrt()
+    }
+}
+
+/// 按朝向拟合的模型
+#[derive(Clone, Debug)]
+pub struct OrientationModel {
+    /// 各朝向等权平均的模型
+    pub model: RSSIModel,
+    /// 朝向 -> 截距偏移 (dB)
+    pub offsets: HashMap<String, f64>,
+}
+
+impl OrientationModel {
+    /// 指定朝向的模型（截距加上该朝向的偏移）；未知朝向返回平均模型
+    pub fn model_for(&self, orientation: &str) -> RSSIModel {
+        let mut model = self.model.clone();
+        model.a += self.offsets.get(orientation).copied().unwrap_or(0.0);
+        model
+    }
+
+    /// 朝向引起的截距差异（最大偏移 - 最小偏移, dB）
+    pub fn spread(&self) -> f64 {
+        let max = self.offsets.values().copied().fold(f64::NEG_INFINITY, f64::max);
+        let min = self.offsets.values().copied().fold(f64::INFINITY, f64::min);
+        if max >= min { max - min } else { 0.0 }
+    }
+}
+
+/// 标定结果
+#[derive(Clone, Debug)]
+pub struct CalibrationOutput {
+    /// 拟合得到的 RSSI 模型（有多种朝向时为朝向平均模型）
+    pub model: RSSIModel,
+    /// 按朝向拟合的模型，参考点朝向少于 2 种时为 None
+    pub orientation_model: Option<OrientationModel>,
+    /// 指纹数据库
+    pub fingerprints: FingerprintDatabase,
+    /// 所有参考点的原始数据
+    pub points: Vec<CalibrationPoint>,
+}
+
+/// 标定会话
+#[derive(Clone, Debug)]
+pub struct CalibrationSession {
+    /// 已知位置的信标
+    beacons: BeaconSet,
+    /// 坐标与距离单位
+    unit: DistanceUnit,
+    /// 正在采集的参考点
+    current: Option<CalibrationPoint>,
+    /// 已完成的参考点
+    completed: Vec<CalibrationPoint>,
+}
+
+impl CalibrationSession {
+    /// 创建标定会话
+    ///
+    /// # 参数
+    /// - `beacons`: 已测量位置的信标集合
+    /// - `unit`: 信标与参考点坐标的单位
+    pub fn new(beacons: BeaconSet, unit: DistanceUnit) -> Self {
+        CalibrationSession {
+            beacons,
+            unit,
+            current: None,
+            completed: Vec::new(),
+        }
+    }
+
+    /// 开始采集一个参考点
+    pub fn start_point(&mut self, label: impl Into<String>, x: f64, y: f64, z: f64) -> Result<(), String> {
+        if let Some(current) = &self.current {
+            return Err(format!("参考点 {} 尚未完成采集", current.label));
+        }
+        self.current = Some(CalibrationPoint {
+            label: label.into(),
+            x,
+            y,
+            z,
+            orientation: None,
+            samples: HashMap::new(),
+        });
+        Ok(())
+    }
+
+    /// 开始采集一个标注了设备朝向的参考点
+    pub fn start_point_oriented(
+        &mut self,
+        label: impl Into<String>,
+        x: f64,
+        y: f64,
+        z: f64,
+        orientation: impl Into<String>,
+    ) -> Result<(), String> {
+        self.start_point(label, x, y, z)?;
+        if let Some(current) = self.current.as_mut() {
+            current.orientation = Some(orientation.into());
+        }
+        Ok(())
+    }
+
+    /// 记录一条观测到当前参考点（只接受 RSSI 观测）
+    pub fn record(&mut self, observation: impl Into<Observation>) -> Result<(), String> {
+        let measurement = observation
+            .into()
+            .to_signal_measurement()
+            .ok_or_else(|| "标定只接受 RSSI 观测".to_string())?;
+        let current = self
+            .current
+            .as_mut()
+            .ok_or_else(|| "没有正在采集的参考点".to_string())?;
+        current
+            .samples
+            .entry(measurement.beacon_id)
+            .or_default()
+            .push(measurement.rssi);
+        Ok(())
+    }
+
+    /// 在指定时长内从通道持续采集观测，返回采集到的条数
+    pub async fn collect_for<T: Into<Observation>>(
+        &mut self,
+        duration: Duration,
+        receiver: &mut mpsc::Receiver<T>,
+    ) -> Result<usize, String> {
+        let deadline = tokio::time::Instant::now() + duration;
+        let mut count = 0;
+        while let Ok(Some(observation)) = tokio::time::timeout_at(deadline, receiver.recv()).await {
+            self.record(observation)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// 完成当前参考点
+    pub fn finish_point(&mut self) -> Result<&CalibrationPoint, String> {
+        let current = self
+            .current
+            .take()
+            .ok_or_else(|| "没有正在采集的参考点".to_string())?;
+        if current.sample_count() == 0 {
+            return Err(format!("参考点 {} 没有采集到任何样本", current.label));
+        }
+        self.completed.push(current);
+        Ok(self.completed.last().expect("刚刚加入的参考点"))
+    }
+
+    /// 已完成的参考点
+    pub fn completed_points(&self) -> &[CalibrationPoint] {
+        &self.completed
+    }
+
+    /// (距离, RSSI) 拟合样本 - 仅包含位置已知的信标
+    pub fn distance_samples(&self) -> Vec<(f64, f64)> {
+        self.completed.iter().flat_map(|p| self.point_distance_samples(p)).collect()
+    }
+
+    /// 按朝向分组的 (距离, RSSI) 拟合样本，未标注朝向的参考点归入空字符串组
+    pub fn distance_samples_by_orientation(&self) -> HashMap<String, Vec<(f64, f64)>> {
+        let mut groups: HashMap<String, Vec<(f64, f64)>> = HashMap::new();
+        for point in &self.completed {
+            groups
+                .entry(point.orientation.clone().unwrap_or_default())
+                .or_default()
+                .extend(self.point_distance_samples(point));
+        }
+        groups
+    }
+
+    /// 按朝向拟合模型
+    ///
+    /// # 返回
+    /// - 朝向模型，或错误如果朝向少于 2 种或样本不足
+    pub fn fit_orientation_model(&self) -> Result<OrientationModel, String> {
+        let groups = self.distance_samples_by_orientation();
+        if groups.len() < 2 {
+            return Err("按朝向拟合至少需要 2 种朝向的参考点".to_string());
+        }
+        let (model, offsets) = RSSIModel::fit_grouped(&groups, self.unit)?;
+        Ok(OrientationModel { model, offsets })
+    }
+
+    /// 每个 (信标, 参考距离) 的 RSSI 直方图，按信标 ID 和距离排序
+    ///
+    /// 距离相同（四舍五入到 0.01）的参考点合并到同一直方图
+    pub fn histograms(&self) -> Vec<RssiHistogram> {
+        let mut grouped: BTreeMap<(String, i64), BTreeMap<i16, usize>> = BTreeMap::new();
+        for point in &self.completed {
+            for (beacon_id, values) in &point.samples {
+                let Some(beacon) = self.beacons.get(beacon_id) else {
+                    continue;
+                };
+                let distance = ((beacon.x - point.x).powi(2)
+                    + (beacon.y - point.y).powi(2)
+                    + (beacon.z - point.z).powi(2))
+                .sqrt();
+                let counts = grouped
+                    .entry((beacon_id.clone(), (distance * 100.0).round() as i64))
+                    .or_default();
+                for rssi in values {
+                    *counts.entry(*rssi).or_insert(0) += 1;
+                }
+            }
+        }
+
+        grouped
+            .into_iter()
+            .map(|((beacon_id, key), counts)| RssiHistogram {
+                beacon_id,
+                distance: key as f64 / 100.0,
+                counts,
+            })
+            .collect()
+    }
+
+    /// 由直方图估计阴影衰落 σ (dB)
+    ///
+    /// 合并各 (信标, 距离) 组内的方差，不受模型拟合误差影响
+    ///
+    /// # 返回
+    /// - σ，或 None 如果没有任何组包含 2 个以上样本
+    pub fn sigma_estimate(&self) -> Option<f64> {
+        let (sum_sq, dof) = self
+            .histograms()
+            .iter()
+            .filter(|h| h.sample_count() >= 2)
+            .fold((0.0, 0usize), |(sum_sq, dof), h| {
+                let n = h.sample_count();
+                (sum_sq + h.std_dev().powi(2) * n as f64, dof + n - 1)
+            });
+        (dof > 0).then(|| (sum_sq / dof as f64).sqrt())
+    }
+
+    /// 以 CSV 导出直方图，列为 `beacon_id,distance,rssi,count`
+    pub fn histograms_csv(&self) -> String {
+        let mut csv = String::from("beacon_id,distance,rssi,count\n");
+        for histogram in self.histograms() {
+            for (rssi, count) in &histogram.counts {
+                let _ = writeln!(csv, "{},{:.2},{},{}", histogram.beacon_id, histogram.distance, rssi, count);
+            }
+        }
+        csv
+    }
+
+    fn point_distance_samples(&self, point: &CalibrationPoint) -> Vec<(f64, f64)> {
+        let mut samples = Vec::new();
+        for (beacon_id, values) in &point.samples {
+            if let Some(beacon) = self.beacons.get(beacon_id) {
+                let distance = ((beacon.x - point.x).powi(2)
+                    + (beacon.y - point.y).powi(2)
+                    + (beacon.z - point.z).powi(2))
+                .sqrt();
+                samples.extend(values.iter().map(|r| (distance, *r as f64)));
+            }
+        }
+        samples
+    }
+
+    /// 结束会话，拟合模型并生成指纹数据库
+    pub fn finish(self) -> Result<CalibrationOutput, String> {
+        if let Some(current) = &self.current {
+            return Err(format!("参考点 {} 尚未完成采集", current.label));
+        }
+
+        let orientation_model = self.fit_orientation_model().ok();
+        let model = match &orientation_model {
+            Some(oriented) => oriented.model.clone(),
+            None => RSSIModel::fit(&self.distance_samples(), self.unit)?,
+        };
+
+        let mut fingerprints = FingerprintDatabase::new();
+        for point in &self.completed {
+            fingerprints.add_point(ReferencePoint::from_samples(
+                point.label.clone(),
+                point.x,
+                point.y,
+                point.z,
+                &point.samples,
+            ));
+        }
+
+        Ok(CalibrationOutput {
+            model,
+            orientation_model,
+            fingerprints,
+            points: self.completed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{Beacon, ObservationSource, SignalMeasurement};
+
+    #[test]
+    fn test_calibration_session_produces_model_and_fingerprints() {
+        let beacons = BeaconSet::from_vec(vec![Beacon::new(
+            "B1".to_string(),
+            "B1".to_string(),
+            0.0,
+            0.0,
+            0.0,
+        )]);
+        let truth = RSSIModel::log_distance(-50.0, -30.0, DistanceUnit::Centimeter);
+        let mut session = CalibrationSession::new(beacons, DistanceUnit::Centimeter);
+
+        for (label, x) in [("P1", 100.0), ("P2", 1000.0)] {
+            session.start_point(label, x, 0.0, 0.0).unwrap();
+            let rssi = truth.distance_to_rssi(x).round() as i16;
+            session.record(SignalMeasurement::new("B1".to_string(), rssi)).unwrap();
+            session.record(Observation::rssi(ObservationSource::Replay, "B1", rssi, None)).unwrap();
+            assert!(session.record(Observation::range(ObservationSource::Replay, "B1", x, None)).is_err());
+            session.finish_point().unwrap();
+        }
+
+        let output = session.finish().unwrap();
+        assert!((output.model.a - (-50.0)).abs() < 1.0);
+        assert!((output.model.b - (-30.0)).abs() < 1.0);
+        assert_eq!(output.fingerprints.len(), 2);
+        assert!(output.orientation_model.is_none());
+    }
+
+    #[test]
+    fn test_orientation_averaged_model() {
+        let beacons = BeaconSet::from_vec(vec![Beacon::new(
+            "B1".to_string(),
+            "B1".to_string(),
+            0.0,
+            0.0,
+            0.0,
+        )]);
+        let truth = RSSIModel::log_distance(-50.0, -30.0, DistanceUnit::Centimeter);
+        let mut session = CalibrationSession::new(beacons, DistanceUnit::Centimeter);
+
+        // 背对信标时信号衰减 8 dB，且背对时多采了一个点
+        let points = [
+            ("F1", 100.0, "facing", 0.0),
+            ("F2", 1000.0, "facing", 0.0),
+            ("A1", 100.0, "away", -8.0),
+            ("A2", 300.0, "away", -8.0),
+            ("A3", 1000.0, "away", -8.0),
+        ];
+        for (label, x, orientation, offset) in points {
+            session.start_point_oriented(label, x, 0.0, 0.0, orientation).unwrap();
+            let rssi = (truth.distance_to_rssi(x) + offset).round() as i16;
+            session.record(SignalMeasurement::new("B1".to_string(), rssi)).unwrap();
+            session.finish_point().unwrap();
+        }
+
+        let output = session.finish().unwrap();
+        let oriented = output.orientation_model.unwrap();
+        assert!((output.model.a - (-54.0)).abs() < 0.5);
+        assert!((output.model.b - (-30.0)).abs() < 1.0);
+        assert!((oriented.spread() - 8.0).abs() < 0.5);
+        assert!((oriented.model_for("facing").a - (-50.0)).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_histograms_and_sigma() {
+        let beacons = BeaconSet::from_vec(vec![Beacon::new(
+            "B1".to_string(),
+            "B1".to_string(),
+            0.0,
+            0.0,
+            0.0,
+        )]);
+        let mut session = CalibrationSession::new(beacons, DistanceUnit::Centimeter);
+        for (label, x, values) in [("P1", 100.0, [-50, -52, -50, -52]), ("P2", 300.0, [-60, -62, -60, -62])] {
+            session.start_point(label, x, 0.0, 0.0).unwrap();
+            for rssi in values {
+                session.record(SignalMeasurement::new("B1".to_string(), rssi)).unwrap();
+            }
+            session.finish_point().unwrap();
+        }
+
+        let histograms = session.histograms();
+        assert_eq!(histograms.len(), 2);
+        assert_eq!(histograms[0].distance, 100.0);
+        assert_eq!(histograms[0].counts.get(&-52), Some(&2));
+        assert!((histograms[1].mean() - (-61.0)).abs() < 1e-9);
+
+        // 每组方差 1 (n=4)，无偏合并 σ² = 8 / 6
+        assert!((session.sigma_estimate().unwrap() - (8.0f64 / 6.0).sqrt()).abs() < 1e-9);
+        assert!(session.histograms_csv().starts_with("beacon_id,distance,rssi,count\nB1,100.00,-52,2\n"));
+    }
+
+    #[tokio::test]
+    async fn test_collect_for_reads_channel() {
+        let mut session = CalibrationSession::new(BeaconSet::new(), DistanceUnit::Centimeter);
+        let (tx, mut rx) = mpsc::channel(8);
+        tx.send(SignalMeasurement::new("B1".to_string(), -60)).await.unwrap();
+        tx.send(SignalMeasurement::new("B1".to_string(), -62)).await.unwrap();
+        drop(tx);
+
+        session.start_point("P1", 0.0, 0.0, 0.0).unwrap();
+        let count = session.collect_for(Duration::from_millis(50), &mut rx).await.unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(session.finish_point().unwrap().sample_count(), 2);
+    }
+}
@@ -0,0 +1,113 @@
+/// 时钟抽象
+///
+/// 将"当前时间"从各组件中抽离出来：
+/// - `SystemClock`: 系统墙钟
+/// - `TokioClock`: 跟随 tokio 时间，配合 `tokio::time::pause` 可确定性测试
+/// - `MockClock`: 手动推进的模拟时钟
+
+use chrono::Utc;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 时钟接口 - 返回 Unix 毫秒时间戳
+pub trait Clock: Send + Sync + fmt::Debug {
+    /// 当前时间（Unix 毫秒）
+    fn now_ms(&self) -> u64;
+}
+
+/// 系统墙钟
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> u64 {
+        Utc::now().timestamp_millis().max(0) as u64
+    }
+}
+
+/// 跟随 tokio 运行时时间的时钟
+///
+/// 以创建时刻的墙钟为基准，之后按 `tokio::time::Instant` 推进；
+/// 在 `tokio::time::pause()` 下只随 `tokio::time::advance` 或自动推进变化。
+#[derive(Clone, Copy, Debug)]
+pub struct TokioClock {
+    base_instant: tokio::time::Instant,
+    base_ms: u64,
+}
+
+impl TokioClock {
+    /// 创建 tokio 时钟
+    pub fn new() -> Self {
+        TokioClock {
+            base_instant: tokio::time::Instant::now(),
+            base_ms: SystemClock.now_ms(),
+        }
+    }
+}
+
+impl Default for TokioClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for TokioClock {
+    fn now_ms(&self) -> u64 {
+        self.base_ms + self.base_instant.elapsed().as_millis() as u64
+    }
+}
+
+/// 模拟时钟 - 克隆体共享同一时间
+#[derive(Clone, Debug, Default)]
+pub struct MockClock {
+    now: Arc<AtomicU64>,
+}
+
+impl MockClock {
+    /// 创建指定起始时间的模拟时钟
+    pub fn new(start_ms: u64) -> Self {
+        MockClock {
+            now: Arc::new(AtomicU64::new(start_ms)),
+        }
+    }
+
+    /// 设置当前时间
+    pub fn set_ms(&self, now_ms: u64) {
+        self.now.store(now_ms, Ordering::SeqCst);
+    }
+
+    /// 推进时间
+    pub fn advance(&self, duration: Duration) {
+        self.now
+            .fetch_add(duration.as_millis() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now_ms(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_shared_advance() {
+        let clock = MockClock::new(1_000);
+        let shared = clock.clone();
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(shared.now_ms(), 1_250);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_tokio_clock_follows_paused_time() {
+        let clock = TokioClock::new();
+        let start = clock.now_ms();
+        tokio::time::sleep(Duration::from_secs(20)).await;
+        assert_eq!(clock.now_ms() - start, 20_000);
+    }
+}
@@ -0,0 +1,239 @@
+/// 信标投运验收
+///
+/// 上线前由运维人员逐个站在已配置的信标旁采集信号，确认：
+/// - 该信标是最强信号，且领先次强信标足够多（否则可能贴错或与其他信标对调）
+/// - 记录近场 RSSI 作为参考值
+///
+/// 典型流程：`start` -> `record`（多条）-> `finish`，所有信标验收后查看 `unverified`
+
+use crate::algorithms::{BeaconSet, Observation};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// 单个信标的验收结论
+#[derive(Clone, Debug, PartialEq)]
+pub enum CommissioningStatus {
+    /// 验收通过
+    Verified,
+    /// 没有收到该信标
+    NotHeard,
+    /// 该信标的样本数不足
+    TooFewSamples(usize),
+    /// 另一个信标更强，可能贴错位置或与该信标对调
+    StrongerBeacon {
+        /// 更强的信标 ID
+        beacon_id: String,
+        /// 其平均 RSSI (dBm)
+        rssi: f64,
+    },
+    /// 该信标最强，但领先次强信标不足
+    AmbiguousMargin {
+        /// 次强信标 ID
+        runner_up: String,
+        /// 领先的 dB 数
+        margin_db: f64,
+    },
+}
+
+impl fmt::Display for CommissioningStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommissioningStatus::Verified => write!(f, "验收通过"),
+            CommissioningStatus::NotHeard => write!(f, "没有收到该信标"),
+            CommissioningStatus::TooFewSamples(count) => write!(f, "样本不足（{} 条）", count),
+            CommissioningStatus::StrongerBeacon { beacon_id, rssi } => {
+                write!(f, "信标 {} 更强（{:.1} dBm），可能贴错或对调", beacon_id, rssi)
+            }
+            CommissioningStatus::AmbiguousMargin { runner_up, margin_db } => {
+                write!(f, "仅领先信标 {} {:.1} dB", runner_up, margin_db)
+            }
+        }
+    }
+}
+
+/// 单个信标的验收记录
+#[derive(Clone, Debug, PartialEq)]
+pub struct BeaconVerification {
+    /// 信标 ID
+    pub beacon_id: String,
+    /// 近场平均 RSSI (dBm)，未收到时为 None
+    pub near_field_rssi: Option<f64>,
+    /// 该信标的样本数
+    pub samples: usize,
+    /// 结论
+    pub status: CommissioningStatus,
+}
+
+impl BeaconVerification {
+    /// 是否验收通过
+    pub fn is_verified(&self) -> bool {
+        self.status == CommissioningStatus::Verified
+    }
+}
+
+/// 验收会话
+#[derive(Clone, Debug)]
+pub struct CommissioningSession {
+    beacons: BeaconSet,
+    /// 最强信标须领先次强信标的 dB 数
+    min_margin_db: f64,
+    /// 被验收信标的最少样本数
+    min_samples: usize,
+    /// 正在验收的信标及采集到的样本
+    current: Option<(String, HashMap<String, Vec<i16>>)>,
+    /// 信标 ID -> 最近一次验收记录
+    results: BTreeMap<String, BeaconVerification>,
+}
+
+impl CommissioningSession {
+    /// 创建验收会话（默认领先 6 dB、至少 10 条样本）
+    pub fn new(beacons: BeaconSet) -> Self {
+        CommissioningSession {
+            beacons,
+            min_margin_db: 6.0,
+            min_samples: 10,
+            current: None,
+            results: BTreeMap::new(),
+        }
+    }
+
+    /// 设置最强信标须领先的 dB 数
+    pub fn with_min_margin_db(mut self, margin_db: f64) -> Self {
+        self.min_margin_db = margin_db;
+        self
+    }
+
+    /// 设置被验收信标的最少样本数
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples;
+        self
+    }
+
+    /// 开始验收一个信标（运维人员站在该信标旁）
+    pub fn start(&mut self, beacon_id: &str) -> Result<(), String> {
+        if let Some((current, _)) = &self.current {
+            return Err(format!("信标 {} 尚未完成验收", current));
+        }
+        if self.beacons.get(beacon_id).is_none() {
+            return Err(format!("未配置的信标 {}", beacon_id));
+        }
+        self.current = Some((beacon_id.to_string(), HashMap::new()));
+        Ok(())
+    }
+
+    /// 记录一条观测（只接受 RSSI 观测）
+    pub fn record(&mut self, observation: impl Into<Observation>) -> Result<(), String> {
+        let measurement = observation.into().to_signal_measurement().ok_or_else(|| "验收只接受 RSSI 观测".to_string())?;
+        let (_, samples) = self.current.as_mut().ok_or_else(|| "没有正在验收的信标".to_string())?;
+        samples.entry(measurement.beacon_id).or_default().push(measurement.rssi);
+        Ok(())
+    }
+
+    /// 完成当前信标的验收并给出结论，重复验收会覆盖之前的记录
+    pub fn finish(&mut self) -> Result<&BeaconVerification, String> {
+        let (beacon_id, samples) = self.current.take().ok_or_else(|| "没有正在验收的信标".to_string())?;
+        let mut means: Vec<(String, f64)> = samples
+            .iter()
+            .filter(|(_, values)| !values.is_empty())
+            .map(|(id, values)| (id.clone(), values.iter().map(|&v| v as f64).sum::<f64>() / values.len() as f64))
+            .collect();
+        means.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let count = samples.get(&beacon_id).map_or(0, Vec::len);
+        let near_field_rssi = means.iter().find(|(id, _)| *id == beacon_id).map(|(_, rssi)| *rssi);
+        let status = match near_field_rssi {
+            None => CommissioningStatus::NotHeard,
+            Some(_) if count < self.min_samples => CommissioningStatus::TooFewSamples(count),
+            Some(own) => match (means[0].0 == beacon_id, means.get(1)) {
+                (false, _) => CommissioningStatus::StrongerBeacon {
+                    beacon_id: means[0].0.clone(),
+                    rssi: means[0].1,
+                },
+                (true, Some((runner_up, rssi))) if own - rssi < self.min_margin_db => {
+                    CommissioningStatus::AmbiguousMargin {
+                        runner_up: runner_up.clone(),
+                        margin_db: own - rssi,
+                    }
+                }
+                _ => CommissioningStatus::Verified,
+            },
+        };
+
+        let verification = BeaconVerification {
+            beacon_id: beacon_id.clone(),
+            near_field_rssi,
+            samples: count,
+            status,
+        };
+        self.results.insert(beacon_id.clone(), verification);
+        Ok(&self.results[&beacon_id])
+    }
+
+    /// 某信标最近一次的验收记录
+    pub fn verification(&self, beacon_id: &str) -> Option<&BeaconVerification> {
+        self.results.get(beacon_id)
+    }
+
+    /// 所有验收记录，按信标 ID 排序
+    pub fn report(&self) -> Vec<&BeaconVerification> {
+        self.results.values().collect()
+    }
+
+    /// 尚未验收或未通过的信标 ID，按 ID 排序
+    pub fn unverified(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = self
+            .beacons
+            .iter()
+            .map(|(id, _)| id.as_str())
+            .filter(|id| !self.results.get(*id).is_some_and(|v| v.is_verified()))
+            .collect();
+        ids.sort();
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{Beacon, SignalMeasurement};
+
+    fn record_all(session: &mut CommissioningSession, readings: &[(&str, i16)]) {
+        for _ in 0..10 {
+            for (id, rssi) in readings {
+                session.record(SignalMeasurement::new(id.to_string(), *rssi)).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_commissioning_detects_swapped_beacon() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("B1".to_string(), "b1".to_string(), 0.0, 0.0, 0.0),
+            Beacon::new("B2".to_string(), "b2".to_string(), 1000.0, 0.0, 0.0),
+            Beacon::new("B3".to_string(), "b3".to_string(), 0.0, 1000.0, 0.0),
+        ]);
+        let mut session = CommissioningSession::new(beacons);
+        assert!(session.start("X9").is_err());
+
+        session.start("B1").unwrap();
+        record_all(&mut session, &[("B1", -45), ("B2", -75)]);
+        let b1 = session.finish().unwrap();
+        assert!(b1.is_verified());
+        assert_eq!(b1.near_field_rssi, Some(-45.0));
+
+        // 站在 B2 的位置，最强的却是 B3
+        session.start("B2").unwrap();
+        record_all(&mut session, &[("B2", -70), ("B3", -48)]);
+        assert!(matches!(
+            &session.finish().unwrap().status,
+            CommissioningStatus::StrongerBeacon { beacon_id, .. } if beacon_id == "B3"
+        ));
+
+        session.start("B3").unwrap();
+        record_all(&mut session, &[("B3", -50), ("B1", -53)]);
+        assert!(matches!(session.finish().unwrap().status, CommissioningStatus::AmbiguousMargin { .. }));
+
+        assert_eq!(session.unverified(), vec!["B2", "B3"]);
+        assert_eq!(session.report().len(), 3);
+    }
+}
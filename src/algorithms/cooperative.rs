@@ -0,0 +1,343 @@
+/// 移动标签之间的协同定位
+///
+/// 标签自身也扫描时，可以得到标签之间的 RSSI 观测。固定信标稀疏的区域里，
+/// 单个标签可能只收到两三个信标，位置存在歧义；把标签之间的距离作为额外约束联合求解，
+/// 定位好的标签可以帮助定位差的标签。
+///
+/// 求解采用分块 Gauss-Newton：每轮依次固定其他标签的当前估计，对单个标签做一次二维更新
+
+use crate::algorithms::{BeaconSet, DistanceEstimator, LocationResult, SignalReadings};
+use std::collections::HashMap;
+
+/// 参与协同定位的标签
+#[derive(Clone, Debug, PartialEq)]
+pub struct CooperativeTag {
+    /// 标签 ID
+    pub id: String,
+    /// 初始位置估计
+    pub initial: (f64, f64),
+    /// 标签高度（求解只在该高度的水平面内进行）
+    pub z: f64,
+    /// 到固定信标的水平距离：(信标 x, 信标 y, 水平距离)
+    pub anchor_ranges: Vec<(f64, f64, f64)>,
+}
+
+impl CooperativeTag {
+    /// 由标签收到的信标读数创建，初始位置取所收到信标的质心
+    ///
+    /// 与 [`LocationAlgorithm::from_ranges`](crate::algorithms::LocationAlgorithm::from_ranges) 相同，
+    /// 标签高度假定为所收到信标的平均高度；已知标签高度时应使用 [`CooperativeTag::from_readings_at_height`]
+    ///
+    /// # 返回
+    /// - 标签，或 None 如果没有收到任何已知信标
+    pub fn from_readings(
+        id: impl Into<String>,
+        signals: &SignalReadings,
+        beacons: &BeaconSet,
+        model: &(impl DistanceEstimator + ?Sized),
+    ) -> Option<Self> {
+        let slant = Self::_slant_ranges(signals, beacons, model);
+        if slant.is_empty() {
+            return None;
+        }
+        let z = slant.iter().map(|a| a.2).sum::<f64>() / slant.len() as f64;
+        Some(Self::_from_slant_ranges(id.into(), &slant, z))
+    }
+
+    /// 由信标读数和已知的标签高度创建
+    ///
+    /// 测距（斜距）按信标与标签的高度差投影为水平距离 √(d² − Δz²)
+    pub fn from_readings_at_height(
+        id: impl Into<String>,
+        signals: &SignalReadings,
+        beacons: &BeaconSet,
+        model: &(impl DistanceEstimator + ?Sized),
+        z: f64,
+    ) -> Option<Self> {
+        let slant = Self::_slant_ranges(signals, beacons, model);
+        if slant.is_empty() || !z.is_finite() {
+            return None;
+        }
+        Some(Self::_from_slant_ranges(id.into(), &slant, z))
+    }
+
+    /// 已知信标的斜距：(信标 x, 信标 y, 信标 z, 距离)
+    fn _slant_ranges(
+        signals: &SignalReadings,
+        beacons: &BeaconSet,
+        model: &(impl DistanceEstimator + ?Sized),
+    ) -> Vec<(f64, f64, f64, f64)> {
+        signals
+            .all()
+            .iter()
+            .filter_map(|(beacon_id, rssi)| {
+                let beacon = beacons.get(beacon_id)?;
+                model.distance(beacon_id, *rssi).map(|d| (beacon.x, beacon.y, beacon.z, d))
+            })
+            .collect()
+    }
+
+    fn _from_slant_ranges(id: String, slant: &[(f64, f64, f64, f64)], z: f64) -> Self {
+        let anchor_ranges: Vec<(f64, f64, f64)> = slant
+            .iter()
+            .map(|(bx, by, bz, d)| (*bx, *by, (d * d - (z - bz).powi(2)).max(0.0).sqrt()))
+            .collect();
+        let n = anchor_ranges.len() as f64;
+        let initial = (
+            anchor_ranges.iter().map(|a| a.0).sum::<f64>() / n,
+            anchor_ranges.iter().map(|a| a.1).sum::<f64>() / n,
+        );
+        CooperativeTag { id, initial, z, anchor_ranges }
+    }
+
+    /// 使用指定的初始位置（如上一次定位结果）
+    pub fn with_initial(mut self, x: f64, y: f64) -> Self {
+        self.initial = (x, y);
+        self
+    }
+}
+
+/// 标签之间的距离观测
+#[derive(Clone, Debug, PartialEq)]
+pub struct PeerRange {
+    /// 标签 A
+    pub tag_a: String,
+    /// 标签 B
+    pub tag_b: String,
+    /// 距离
+    pub distance: f64,
+}
+
+impl PeerRange {
+    /// 创建距离观测
+    pub fn new(tag_a: impl Into<String>, tag_b: impl Into<String>, distance: f64) -> Self {
+        PeerRange {
+            tag_a: tag_a.into(),
+            tag_b: tag_b.into(),
+            distance,
+        }
+    }
+
+    /// 由标签 A 收到标签 B 的 RSSI 创建
+    pub fn from_rssi(
+        tag_a: impl Into<String>,
+        tag_b: impl Into<String>,
+        rssi: i16,
+        model: &(impl DistanceEstimator + ?Sized),
+    ) -> Option<Self> {
+        let tag_b = tag_b.into();
+        let distance = model.distance(&tag_b, rssi)?;
+        Some(Self::new(tag_a, tag_b, distance))
+    }
+}
+
+/// 协同定位求解器
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CooperativeSolver {
+    /// 迭代轮数
+    pub iterations: usize,
+    /// 标签间距离相对固定信标距离的权重（标签位置本身不准，通常小于 1）
+    pub peer_weight: f64,
+}
+
+impl Default for CooperativeSolver {
+    fn default() -> Self {
+        CooperativeSolver {
+            iterations: 20,
+            peer_weight: 0.5,
+        }
+    }
+}
+
+impl CooperativeSolver {
+    /// 创建求解器（默认 20 轮、标签间权重 0.5）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置迭代轮数
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations.max(1);
+        self
+    }
+
+    /// 设置标签间距离的权重
+    pub fn with_peer_weight(mut self, weight: f64) -> Self {
+        self.peer_weight = weight.max(0.0);
+        self
+    }
+
+    /// 联合求解所有标签的位置
+    ///
+    /// 引用未知标签的距离观测被忽略；约束少于 2 个的标签保持初始位置
+    ///
+    /// # 返回
+    /// - 标签 ID -> 定位结果，误差为加权距离残差的均方根
+    pub fn solve(&self, tags: &[CooperativeTag], peers: &[PeerRange]) -> HashMap<String, LocationResult> {
+        let index: HashMap<&str, usize> = tags.iter().enumerate().map(|(i, t)| (t.id.as_str(), i)).collect();
+        let mut neighbours: Vec<Vec<(usize, f64)>> = vec![Vec::new(); tags.len()];
+        for peer in peers {
+            if let (Some(&a), Some(&b)) = (index.get(peer.tag_a.as_str()), index.get(peer.tag_b.as_str()))
+                && a != b
+            {
+                neighbours[a].push((b, peer.distance));
+                neighbours[b].push((a, peer.distance));
+            }
+        }
+
+        let mut positions: Vec<(f64, f64)> = tags.iter().map(|t| t.initial).collect();
+        for _ in 0..self.iterations {
+            for i in 0..tags.len() {
+                let constraints = self.constraints(&tags[i], &neighbours[i], &positions);
+                if constraints.len() >= 2
+                    && let Some(step) = gauss_newton_step(positions[i], &constraints)
+                {
+                    positions[i] = (positions[i].0 + step.0, positions[i].1 + step.1);
+                }
+            }
+        }
+
+        tags.iter()
+            .enumerate()
+            .map(|(i, tag)| {
+                let constraints = self.constraints(tag, &neighbours[i], &positions);
+                let (x, y) = positions[i];
+                let (sum_sq, weight) = constraints.iter().fold((0.0, 0.0), |(s, w), (qx, qy, d, wi)| {
+                    (s + wi * ((x - qx).hypot(y - qy) - d).powi(2), w + wi)
+                });
+                let error = if weight > 0.0 { (sum_sq / weight).sqrt() } else { 0.0 };
+                let confidence = (constraints.len() as f64 / 4.0).min(1.0);
+                let result = LocationResult::new(x, y, tag.z, confidence, error, "cooperative".to_string(), tag.anchor_ranges.len());
+                (tag.id.clone(), result)
+            })
+            .collect()
+    }
+
+    /// 标签的所有距离约束：(参考点 x, 参考点 y, 距离, 权重)
+    fn constraints(&self, tag: &CooperativeTag, neighbours: &[(usize, f64)], positions: &[(f64, f64)]) -> Vec<(f64, f64, f64, f64)> {
+        let anchors = tag.anchor_ranges.iter().map(|(x, y, d)| (*x, *y, *d, 1.0));
+        let peers = neighbours
+            .iter()
+            .filter(|_| self.peer_weight > 0.0)
+            .map(|(j, d)| (positions[*j].0, positions[*j].1, *d, self.peer_weight));
+        anchors.chain(peers).collect()
+    }
+}
+
+/// 单个位置的一次带阻尼的 Gauss-Newton 更新
+///
+/// 约束为 (参考点 x, 参考点 y, 水平距离, 权重)，返回位置增量；协同定位与
+/// [`LocationAlgorithm::from_ranges`](crate::algorithms::LocationAlgorithm::from_ranges) 共用
+pub(crate) fn gauss_newton_step(position: (f64, f64), constraints: &[(f64, f64, f64, f64)]) -> Option<(f64, f64)> {
+    let mut jtj = [[0.0; 2]; 2];
+    let mut jtr = [0.0; 2];
+    for (qx, qy, distance, weight) in constraints {
+        let (dx, dy) = (position.0 - qx, position.1 - qy);
+        let norm = dx.hypot(dy).max(1e-6);
+        let (ux, uy) = (dx / norm, dy / norm);
+        let residual = norm - distance;
+        jtj[0][0] += weight * ux * ux;
+        jtj[0][1] += weight * ux * uy;
+        jtj[1][1] += weight * uy * uy;
+        jtr[0] += weight * ux * residual;
+        jtr[1] += weight * uy * residual;
+    }
+    let damping = 1e-3 * (jtj[0][0] + jtj[1][1]).max(1e-9);
+    jtj[0][0] += damping;
+    jtj[1][1] += damping;
+    let det = jtj[0][0] * jtj[1][1] - jtj[0][1] * jtj[0][1];
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    Some((
+        -(jtr[0] * jtj[1][1] - jtr[1] * jtj[0][1]) / det,
+        -(jtr[1] * jtj[0][0] - jtr[0] * jtj[0][1]) / det,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{Beacon, RangeEstimate};
+
+    #[test]
+    fn test_peer_range_resolves_ambiguous_tag() {
+        let distance = |ax: f64, ay: f64, bx: f64, by: f64| (ax - bx).hypot(ay - by);
+        let tag_a = CooperativeTag {
+            id: "A".to_string(),
+            initial: (333.0, 333.0),
+            z: 0.0,
+            anchor_ranges: vec![
+                (0.0, 0.0, distance(0.0, 0.0, 300.0, 300.0)),
+                (1000.0, 0.0, distance(1000.0, 0.0, 300.0, 300.0)),
+                (0.0, 1000.0, distance(0.0, 1000.0, 300.0, 300.0)),
+            ],
+        };
+        // B 只收到两个信标，(300, 300) 与 (700, 700) 都满足
+        let tag_b = CooperativeTag {
+            id: "B".to_string(),
+            initial: (500.0, 500.0),
+            z: 0.0,
+            anchor_ranges: vec![
+                (1000.0, 0.0, distance(1000.0, 0.0, 700.0, 700.0)),
+                (0.0, 1000.0, distance(0.0, 1000.0, 700.0, 700.0)),
+            ],
+        };
+        let tags = vec![tag_a, tag_b];
+        let peers = vec![PeerRange::new("A", "B", distance(300.0, 300.0, 700.0, 700.0))];
+
+        let solved = CooperativeSolver::new().with_iterations(50).solve(&tags, &peers);
+        let a = &solved["A"];
+        let b = &solved["B"];
+        assert!(distance(a.x, a.y, 300.0, 300.0) < 1.0);
+        assert!(distance(b.x, b.y, 700.0, 700.0) < 1.0);
+        assert_eq!(b.method, "cooperative");
+        assert!(b.error < 1.0);
+
+        // 引用未知标签的观测被忽略
+        let solved = CooperativeSolver::new().solve(&tags[..1], &peers);
+        assert_eq!(solved.len(), 1);
+    }
+
+    /// 测试用：距离 = -RSSI
+    struct NegatedRssi;
+
+    impl DistanceEstimator for NegatedRssi {
+        fn estimate(&self, _beacon_id: &str, rssi_window: &[i16]) -> Option<RangeEstimate> {
+            let distance = -(*rssi_window.first()? as f64);
+            Some(RangeEstimate { distance, std_dev: None })
+        }
+    }
+
+    #[test]
+    fn test_from_readings_projects_ceiling_beacons() {
+        let corners = [(0.0, 0.0), (100.0, 0.0), (0.0, 100.0), (100.0, 100.0)];
+        let beacons = BeaconSet::from_vec(
+            corners
+                .iter()
+                .enumerate()
+                .map(|(i, (x, y))| Beacon::new(format!("b{}", i), String::new(), *x, *y, 30.0))
+                .collect(),
+        );
+        // 标签在地面 (40, 30, 0)，读数为斜距
+        let mut signals = SignalReadings::new();
+        for (i, (x, y)) in corners.iter().enumerate() {
+            let slant: f64 = (40.0 - x).hypot(30.0 - y).hypot(30.0);
+            signals.add(format!("b{}", i), -(slant.round() as i32));
+        }
+
+        let tag = CooperativeTag::from_readings_at_height("T", &signals, &beacons, &NegatedRssi, 0.0).unwrap();
+        let (_, _, d0) = tag.anchor_ranges.iter().find(|a| a.0 == 0.0 && a.1 == 0.0).unwrap();
+        assert!((d0 - 50.0).abs() < 1.0);
+
+        let solved = CooperativeSolver::new().solve(&[tag], &[]);
+        let t = &solved["T"];
+        assert!((t.x - 40.0).hypot(t.y - 30.0) < 2.0);
+        assert_eq!(t.z, 0.0);
+
+        // 未给高度时假定与信标同高，斜距不做投影
+        let tag = CooperativeTag::from_readings("T", &signals, &beacons, &NegatedRssi).unwrap();
+        assert_eq!(tag.z, 30.0);
+        assert!(tag.anchor_ranges.iter().any(|a| a.0 == 0.0 && a.1 == 0.0 && (a.2 - 58.3).abs() < 1.0));
+    }
+}
@@ -0,0 +1,244 @@
+/// 脱敏的调试数据包
+///
+/// 用户反馈定位不准时，远程诊断需要配置、近期观测和引擎状态。
+/// `export_debug_bundle` 把这些信息连同版本号汇总为一个 JSON 文档，
+/// 其中信标和观测目标的标识按 `PrivacyMode` 脱敏，可直接附在问题报告中
+
+use crate::algorithms::{LocationResult, Observation, PositioningEngine, PrivacyMode};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 调试数据包格式版本
+pub const DEBUG_BUNDLE_VERSION: u16 = 1;
+
+/// 调试数据包
+#[derive(Clone, Debug, Serialize)]
+pub struct DebugBundle {
+    /// 数据包格式版本
+    pub bundle_version: u16,
+    /// 库版本
+    pub crate_version: String,
+    /// 生成时间
+    pub generated_at: DateTime<Utc>,
+    /// 引擎配置
+    pub config: DebugConfig,
+    /// 信标（标识已脱敏）
+    pub beacons: Vec<DebugBeacon>,
+    /// 运行统计
+    pub stats: DebugStats,
+    /// 当前窗口内各信标的 RSSI（标识已脱敏）
+    pub current_readings: BTreeMap<String, i16>,
+    /// 最近一次定位结果
+    pub last_fix: Option<DebugFix>,
+    /// 近期观测（标识已脱敏）
+    pub observations: Vec<DebugObservation>,
+}
+
+/// 引擎配置摘要
+#[derive(Clone, Debug, Serialize)]
+pub struct DebugConfig {
+    /// 聚合窗口（毫秒）
+    pub window_ms: u64,
+    /// RSSI 聚合方式
+    pub rssi_aggregation: String,
+    /// 积压处理策略
+    pub ingest_policy: String,
+    /// 求解时机策略
+    pub epoch_strategy: String,
+}
+
+/// 信标
+#[derive(Clone, Debug, Serialize)]
+pub struct DebugBeacon {
+    /// 脱敏后的信标 ID
+    pub id: String,
+    /// X 坐标
+    pub x: f64,
+    /// Y 坐标
+    pub y: f64,
+    /// Z 坐标
+    pub z: f64,
+}
+
+/// 运行统计摘要
+#[derive(Clone, Debug, Serialize)]
+pub struct DebugStats {
+    /// 接收的观测数
+    pub observations_fed: u64,
+    /// 忽略的观测数
+    pub observations_ignored: u64,
+    /// 求解次数
+    pub solves: u64,
+    /// 输出的定位结果数
+    pub fixes: u64,
+    /// 因积压丢弃的观测数
+    pub observations_dropped: u64,
+    /// 平均延迟（毫秒）
+    pub latency_mean_ms: Option<f64>,
+    /// 95 分位延迟（毫秒）
+    pub latency_p95_ms: Option<f64>,
+    /// 各来源：(来源, 接收数, 忽略数)
+    pub sources: Vec<(String, u64, u64)>,
+}
+
+/// 定位结果
+#[derive(Clone, Debug, Serialize)]
+pub struct DebugFix {
+    /// X 坐标
+    pub x: f64,
+    /// Y 坐标
+    pub y: f64,
+    /// Z 坐标
+    pub z: f64,
+    /// 置信度
+    pub confidence: f64,
+    /// 估计误差
+    pub error: f64,
+    /// 算法名称
+    pub method: String,
+    /// 参与定位的信标数
+    pub beacon_count: usize,
+    /// 时间戳
+    pub timestamp: DateTime<Utc>,
+}
+
+impl From<&LocationResult> for DebugFix {
+    fn from(result: &LocationResult) -> Self {
+        DebugFix {
+            x: result.x,
+            y: result.y,
+            z: result.z,
+            confidence: result.confidence,
+            error: result.error,
+            method: result.method.clone(),
+            beacon_count: result.beacon_count,
+            timestamp: result.timestamp,
+        }
+    }
+}
+
+/// 观测
+#[derive(Clone, Debug, Serialize)]
+pub struct DebugObservation {
+    /// 来源
+    pub source: String,
+    /// 脱敏后的目标标识
+    pub target: String,
+    /// 观测类型
+    pub kind: String,
+    /// 观测值
+    pub value: f64,
+    /// 时间戳（毫秒）
+    pub timestamp_ms: Option<u64>,
+}
+
+impl DebugBundle {
+    /// 序列化为格式化的 JSON
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("调试数据包序列化失败: {}", e))
+    }
+}
+
+impl PositioningEngine {
+    /// 生成脱敏的调试数据包
+    ///
+    /// # 参数
+    /// - `privacy`: 标识脱敏方式，通常为 `PrivacyMode::hashed`（盐值不要随数据包提供）
+    /// - `recent`: 近期观测（如录制的最后若干条），只保留最后 `max_observations` 条
+    /// - `max_observations`: 观测条数上限
+    pub fn export_debug_bundle(&self, privacy: &PrivacyMode, recent: &[Observation], max_observations: usize) -> DebugBundle {
+        let stats = self.stats();
+        let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+        let mut beacons: Vec<DebugBeacon> = self
+            .beacons()
+            .all()
+            .into_iter()
+            .map(|b| DebugBeacon {
+                id: privacy.anonymize_id(&b.id),
+                x: b.x,
+                y: b.y,
+                z: b.z,
+            })
+            .collect();
+        beacons.sort_by(|a, b| a.id.cmp(&b.id));
+
+        DebugBundle {
+            bundle_version: DEBUG_BUNDLE_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            generated_at: Utc::now(),
+            config: DebugConfig {
+                window_ms: self.window().as_millis() as u64,
+                rssi_aggregation: format!("{:?}", self.rssi_aggregation()),
+                ingest_policy: format!("{:?}", self.ingest_policy()),
+                epoch_strategy: format!("{:?}", self.epoch_strategy()),
+            },
+            beacons,
+            stats: DebugStats {
+                observations_fed: stats.observations_fed,
+                observations_ignored: stats.observations_ignored,
+                solves: stats.solves,
+                fixes: stats.fixes,
+                observations_dropped: stats.observations_dropped,
+                latency_mean_ms: stats.latency.mean().map(ms),
+                latency_p95_ms: stats.latency.percentile(0.95).map(ms),
+                sources: stats
+                    .sources
+                    .all()
+                    .iter()
+                    .map(|c| (c.source.to_string(), c.accepted, c.ignored))
+                    .collect(),
+            },
+            current_readings: self
+                .current_readings()
+                .all()
+                .iter()
+                .map(|(id, rssi)| (privacy.anonymize_id(id), *rssi))
+                .collect(),
+            last_fix: self.last_known_position().as_ref().map(DebugFix::from),
+            observations: recent[recent.len().saturating_sub(max_observations)..]
+                .iter()
+                .map(|o| DebugObservation {
+                    source: o.source.to_string(),
+                    target: privacy.anonymize_id(&o.target),
+                    kind: format!("{:?}", o.kind),
+                    value: o.value,
+                    timestamp_ms: o.timestamp_ms,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{Beacon, BeaconSet, MockClock, ObservationSource, RSSIModel};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_debug_bundle_is_anonymized() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("AA:BB:CC:00:00:01".to_string(), "lobby".to_string(), 0.0, 0.0, 0.0),
+            Beacon::new("AA:BB:CC:00:00:02".to_string(), "hall".to_string(), 500.0, 0.0, 0.0),
+        ]);
+        let clock = Arc::new(MockClock::new(1_000));
+        let mut engine = PositioningEngine::manual(beacons, RSSIModel::default()).with_clock(clock);
+        let observations: Vec<Observation> = (0..5)
+            .map(|i| Observation::rssi(ObservationSource::Replay, "AA:BB:CC:00:00:01", -60 - i, Some(1_000)))
+            .collect();
+        for observation in &observations {
+            engine.feed_observation(observation.clone());
+        }
+
+        let bundle = engine.export_debug_bundle(&PrivacyMode::hashed("site-salt"), &observations, 3);
+        assert_eq!(bundle.observations.len(), 3);
+        assert_eq!(bundle.stats.observations_fed, 5);
+        assert_eq!(bundle.current_readings.len(), 1);
+
+        let json = bundle.to_json().unwrap();
+        assert!(!json.contains("AA:BB:CC"));
+        assert!(!json.contains("lobby"));
+        assert!(json.contains("\"bundle_version\": 1"));
+    }
+}
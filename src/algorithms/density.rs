@@ -0,0 +1,143 @@
+/// 匿名区域密度统计
+///
+/// 隐私受限的部署只输出各区域在每个时间段内的设备数与密度，
+/// 不输出任何单个设备的轨迹。设备标识只在当前时间段内用于去重，
+/// 时间段结束后即被丢弃
+
+use crate::algorithms::{LocationResult, ZoneMap};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+
+/// 单个时间段的区域统计
+#[derive(Clone, Debug, PartialEq)]
+pub struct DensitySnapshot {
+    /// 时间段起点
+    pub start: DateTime<Utc>,
+    /// 时间段长度
+    pub duration: Duration,
+    /// 区域名称 -> 设备数（低于最小计数的区域记为 0）
+    pub counts: BTreeMap<String, usize>,
+    /// 区域名称 -> 密度（设备数 / 区域面积）
+    pub density: BTreeMap<String, f64>,
+}
+
+/// 区域密度聚合器
+#[derive(Clone, Debug)]
+pub struct DensityAggregator {
+    /// 区域定义
+    zones: ZoneMap,
+    /// 时间段长度（毫秒）
+    bucket_ms: i64,
+    /// 低于该值的计数不输出，避免单个人可被识别
+    min_count: usize,
+    /// 当前时间段起点（毫秒）
+    current_bucket: Option<i64>,
+    /// 当前时间段内：区域名称 -> 出现过的设备
+    present: HashMap<String, HashSet<String>>,
+}
+
+impl DensityAggregator {
+    /// 创建聚合器
+    ///
+    /// # 参数
+    /// - `zones`: 区域定义
+    /// - `bucket`: 统计时间段长度
+    pub fn new(zones: ZoneMap, bucket: Duration) -> Self {
+        DensityAggregator {
+            zones,
+            bucket_ms: (bucket.as_millis() as i64).max(1),
+            min_count: 0,
+            current_bucket: None,
+            present: HashMap::new(),
+        }
+    }
+
+    /// 设置最小输出计数（k-匿名），低于该值的区域计数输出为 0
+    pub fn with_min_count(mut self, min_count: usize) -> Self {
+        self.min_count = min_count;
+        self
+    }
+
+    /// 记录一个设备的定位结果
+    ///
+    /// # 返回
+    /// - 时间进入新的时间段时，返回已结束时间段的统计
+    pub fn record(&mut self, device_id: &str, result: &LocationResult) -> Option<DensitySnapshot> {
+        let bucket = result.timestamp.timestamp_millis().div_euclid(self.bucket_ms) * self.bucket_ms;
+        let finished = match self.current_bucket {
+            Some(current) if bucket > current => self.flush(),
+            Some(current) if bucket < current => return None, // 过期数据直接丢弃
+            _ => None,
+        };
+        self.current_bucket = Some(bucket);
+
+        if let Some(zone) = self.zones.zone_of(result) {
+            self.present
+                .entry(zone.name.clone())
+                .or_default()
+                .insert(device_id.to_string());
+        }
+        finished
+    }
+
+    /// 结束当前时间段并输出统计
+    pub fn flush(&mut self) -> Option<DensitySnapshot> {
+        let start_ms = self.current_bucket.take()?;
+        let present = std::mem::take(&mut self.present);
+
+        let mut counts = BTreeMap::new();
+        let mut density = BTreeMap::new();
+        for zone in self.zones.all() {
+            let raw = present.get(&zone.name).map_or(0, |d| d.len());
+            let count = if raw < self.min_count { 0 } else { raw };
+            let area = zone.area();
+            counts.insert(zone.name.clone(), count);
+            density.insert(zone.name.clone(), if area > 0.0 { count as f64 / area } else { 0.0 });
+        }
+
+        Some(DensitySnapshot {
+            start: DateTime::from_timestamp_millis(start_ms).unwrap_or_default(),
+            duration: Duration::from_millis(self.bucket_ms as u64),
+            counts,
+            density,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::Zone;
+    use chrono::TimeZone;
+
+    fn fix_at(x: f64, second: i64) -> LocationResult {
+        let timestamp = Utc.timestamp_opt(1_700_000_000 + second, 0).unwrap();
+        LocationResult::with_timestamp(x, 50.0, 0.0, 0.8, 10.0, "m".to_string(), 3, timestamp)
+    }
+
+    #[test]
+    fn test_density_counts_unique_devices_per_bucket() {
+        let zones = ZoneMap::from_vec(vec![
+            Zone::rectangle("A", 0.0, 0.0, 100.0, 100.0),
+            Zone::rectangle("B", 100.0, 0.0, 200.0, 100.0),
+        ]);
+        let mut aggregator = DensityAggregator::new(zones, Duration::from_secs(60)).with_min_count(2);
+
+        // 1_700_000_000 秒恰好落在分钟边界之后 20 秒
+        assert!(aggregator.record("phone-1", &fix_at(10.0, 0)).is_none());
+        assert!(aggregator.record("phone-1", &fix_at(20.0, 5)).is_none());
+        assert!(aggregator.record("phone-2", &fix_at(30.0, 10)).is_none());
+        assert!(aggregator.record("phone-3", &fix_at(150.0, 10)).is_none());
+
+        let snapshot = aggregator.record("phone-1", &fix_at(10.0, 60)).unwrap();
+        assert_eq!(snapshot.counts["A"], 2);
+        // B 只有 1 台设备，低于最小计数
+        assert_eq!(snapshot.counts["B"], 0);
+        assert!((snapshot.density["A"] - 2.0 / 10_000.0).abs() < 1e-12);
+
+        let last = aggregator.flush().unwrap();
+        assert_eq!(last.counts["A"], 0);
+        assert!(aggregator.flush().is_none());
+    }
+}
@@ -0,0 +1,188 @@
+/// 跨平台设备标识
+///
+/// btleplug 在不同平台上给出的设备标识格式不同：
+/// Linux / Windows 为 MAC 地址，macOS 为 CoreBluetooth 分配的 UUID。
+/// `DeviceId` 将各种写法规范化，配合信标别名使同一份配置在各平台通用
+
+use crate::algorithms::{Beacon, BeaconSet, SignalReadings};
+use std::fmt;
+use std::str::FromStr;
+
+/// 规范化的设备标识
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DeviceId {
+    /// MAC 地址（Linux / Windows）
+    Mac([u8; 6]),
+    /// UUID（macOS）
+    Uuid([u8; 16]),
+    /// 无法识别的其他标识，原样保留（去除首尾空白）
+    Other(String),
+}
+
+impl DeviceId {
+    /// 解析标识
+    ///
+    /// 接受的写法：
+    /// - MAC: `AA:BB:CC:DD:EE:FF`、`aa-bb-cc-dd-ee-ff`、`AABBCCDDEEFF`
+    /// - UUID: 带或不带连字符、可带花括号，大小写不限
+    ///
+    /// 其他字符串作为 `Other` 保留
+    pub fn parse(s: &str) -> Self {
+        let trimmed = s.trim();
+        let hex: String = trimmed
+            .trim_start_matches('{')
+            .trim_end_matches('}')
+            .chars()
+            .filter(|c| !matches!(c, ':' | '-'))
+            .collect();
+
+        if hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            match hex.len() {
+                12 => {
+                    let mut bytes = [0u8; 6];
+                    Self::decode_hex(&hex, &mut bytes);
+                    return DeviceId::Mac(bytes);
+                }
+                32 => {
+                    let mut bytes = [0u8; 16];
+                    Self::decode_hex(&hex, &mut bytes);
+                    return DeviceId::Uuid(bytes);
+                }
+                _ => {}
+            }
+        }
+        DeviceId::Other(trimmed.to_string())
+    }
+
+    fn decode_hex(hex: &str, out: &mut [u8]) {
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap_or(0);
+        }
+    }
+
+    /// 是否为 MAC 地址
+    pub fn is_mac(&self) -> bool {
+        matches!(self, DeviceId::Mac(_))
+    }
+
+    /// 是否为 UUID
+    pub fn is_uuid(&self) -> bool {
+        matches!(self, DeviceId::Uuid(_))
+    }
+
+    /// 规范化字符串形式，参见 `Display`
+    pub fn normalized(s: &str) -> String {
+        Self::parse(s).to_string()
+    }
+}
+
+impl fmt::Display for DeviceId {
+    /// MAC 输出为大写冒号分隔，UUID 输出为小写带连字符的标准形式
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceId::Mac(bytes) => {
+                let parts: Vec<String> = bytes.iter().map(|b| format!("{:02X}", b)).collect();
+                write!(f, "{}", parts.join(":"))
+            }
+            DeviceId::Uuid(bytes) => {
+                let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+                write!(
+                    f,
+                    "{}-{}-{}-{}-{}",
+                    &hex[0..8],
+                    &hex[8..12],
+                    &hex[12..16],
+                    &hex[16..20],
+                    &hex[20..32]
+                )
+            }
+            DeviceId::Other(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl FromStr for DeviceId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Err("设备标识不能为空".to_string());
+        }
+        Ok(Self::parse(s))
+    }
+}
+
+impl Beacon {
+    /// 添加其他平台上的标识（如 macOS 上的 UUID）
+    pub fn with_alias(mut self, alias: impl Into<String>) -> Self {
+        self.aliases.push(alias.into());
+        self
+    }
+
+    /// 标识是否指向该信标（主 ID 或任一别名，按规范化形式比较）
+    pub fn matches_id(&self, id: &str) -> bool {
+        let target = DeviceId::parse(id);
+        DeviceId::parse(&self.id) == target || self.aliases.iter().any(|a| DeviceId::parse(a) == target)
+    }
+}
+
+impl BeaconSet {
+    /// 按任意平台的标识查找信标
+    ///
+    /// 先精确匹配主 ID，再按规范化形式匹配主 ID 和别名
+    pub fn resolve(&self, id: &str) -> Option<&Beacon> {
+        self.get(id)
+            .or_else(|| self.iter().map(|(_, b)| b).find(|b| b.matches_id(id)))
+    }
+}
+
+impl SignalReadings {
+    /// 将测量中的设备标识替换为信标主 ID
+    ///
+    /// 无法匹配到信标的测量原样保留
+    pub fn canonicalize(&self, beacons: &BeaconSet) -> SignalReadings {
+        let mut readings = SignalReadings::new();
+        for (id, rssi) in self.all() {
+            let canonical = beacons.resolve(id).map(|b| b.id.clone()).unwrap_or_else(|| id.clone());
+            readings.add(canonical, *rssi);
+        }
+        readings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_normalizes_formats() {
+        let mac = DeviceId::parse("aa-bb-cc-dd-ee-0f");
+        assert!(mac.is_mac());
+        assert_eq!(mac.to_string(), "AA:BB:CC:DD:EE:0F");
+        assert_eq!(DeviceId::parse("AABBCCDDEE0F"), mac);
+
+        let uuid = DeviceId::parse("{3F2504E0-4F89-11D3-9A0C-0305E82C3301}");
+        assert!(uuid.is_uuid());
+        assert_eq!(uuid.to_string(), "3f2504e0-4f89-11d3-9a0c-0305e82c3301");
+        assert_eq!(DeviceId::parse("3f2504e04f8911d39a0c0305e82c3301"), uuid);
+
+        assert_eq!(DeviceId::parse(" RFstar_C5D6 "), DeviceId::Other("RFstar_C5D6".to_string()));
+        assert!("  ".parse::<DeviceId>().is_err());
+    }
+
+    #[test]
+    fn test_resolve_by_alias_and_canonicalize() {
+        let beacon = Beacon::new("AA:BB:CC:DD:EE:01".to_string(), "C5D6".to_string(), 0.0, 0.0, 0.0)
+            .with_alias("3F2504E0-4F89-11D3-9A0C-0305E82C3301");
+        let set = BeaconSet::from_vec(vec![beacon]);
+
+        assert!(set.resolve("aa:bb:cc:dd:ee:01").is_some());
+        assert!(set.resolve("3f2504e0-4f89-11d3-9a0c-0305e82c3301").is_some());
+        assert!(set.resolve("AA:BB:CC:DD:EE:02").is_none());
+
+        let readings = SignalReadings::from_pairs(vec![("3f2504e0-4f89-11d3-9a0c-0305e82c3301", -60), ("X", -70)]);
+        let canonical = readings.canonicalize(&set);
+        assert_eq!(canonical.get("AA:BB:CC:DD:EE:01"), Some(-60));
+        assert_eq!(canonical.get("X"), Some(-70));
+    }
+}
@@ -0,0 +1,134 @@
+/// 可插拔的测距模型
+///
+/// 定位算法通过 `DistanceEstimator` 把 RSSI 换算为距离，
+/// 参数化模型（`RSSIModel`）、按信标区分的模型以及用户自定义模型都可以直接传给算法：
+/// - 三边定位系列按单个（聚合后的）读数调用 [`DistanceEstimator::distance`]
+/// - [`LocationAlgorithm::trilateration_estimated`](crate::algorithms::LocationAlgorithm::trilateration_estimated)
+///   传入每个信标的样本窗口，并以测距标准差作为权重
+/// - 残差诊断与接收端标定使用 [`DistanceEstimator::expected_rssi`]
+///
+/// CRLB、单位检查和 `AdaptiveNoise::position_noise` 依赖对数距离模型的参数（B、σ、单位），
+/// 仍只接受 `RSSIModel`
+
+use crate::algorithms::RSSIModel;
+use std::collections::HashMap;
+use std::f64::consts::LN_10;
+
+/// 测距结果
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RangeEstimate {
+    /// 估计距离（与坐标单位一致）
+    pub distance: f64,
+    /// 距离标准差，未知时为 None
+    pub std_dev: Option<f64>,
+}
+
+/// RSSI 测距模型
+pub trait DistanceEstimator {
+    /// 根据信标最近的 RSSI 样本估计距离
+    ///
+    /// # 参数
+    /// - `beacon_id`: 信标 ID（允许按信标使用不同参数）
+    /// - `rssi_window`: 最近的 RSSI 样本，至少 1 个
+    ///
+    /// # 返回
+    /// - 测距结果，或 None 如果该信标无法测距
+    fn estimate(&self, beacon_id: &str, rssi_window: &[i16]) -> Option<RangeEstimate>;
+
+    /// 单个 RSSI 的距离
+    fn distance(&self, beacon_id: &str, rssi: i16) -> Option<f64> {
+        self.estimate(beacon_id, &[rssi]).map(|r| r.distance)
+    }
+
+    /// 给定距离下预期的 RSSI (dBm)，模型不可逆时返回 None（默认）
+    fn expected_rssi(&self, _beacon_id: &str, _distance: f64) -> Option<f64> {
+        None
+    }
+}
+
+impl DistanceEstimator for RSSIModel {
+    /// 窗口均值换算距离；σ 已知时按一阶误差传播给出标准差：
+    /// std(d) ≈ d · ln10 · σ / (|B| · √n)
+    fn estimate(&self, _beacon_id: &str, rssi_window: &[i16]) -> Option<RangeEstimate> {
+        if rssi_window.is_empty() {
+            return None;
+        }
+        let n = rssi_window.len() as f64;
+        let mean = rssi_window.iter().map(|r| *r as f64).sum::<f64>() / n;
+        let distance = self.rssi_to_distance_f64(mean);
+        if !distance.is_finite() {
+            return None;
+        }
+        let std_dev = (self.sigma > 0.0 && self.b != 0.0)
+            .then(|| distance * LN_10 * self.sigma / (self.b.abs() * n.sqrt()));
+        Some(RangeEstimate { distance, std_dev })
+    }
+
+    fn expected_rssi(&self, _beacon_id: &str, distance: f64) -> Option<f64> {
+        Some(self.distance_to_rssi(distance)).filter(|rssi| rssi.is_finite())
+    }
+}
+
+/// 按信标区分的模型 - 未单独配置的信标使用默认模型
+#[derive(Clone, Debug)]
+pub struct PerBeaconModels {
+    /// 默认模型
+    pub default: RSSIModel,
+    /// beacon_id -> 单独标定的模型
+    pub overrides: HashMap<String, RSSIModel>,
+}
+
+impl PerBeaconModels {
+    /// 创建，所有信标使用 `default`
+    pub fn new(default: RSSIModel) -> Self {
+        PerBeaconModels {
+            default,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// 为信标单独指定模型
+    pub fn with_model(mut self, beacon_id: impl Into<String>, model: RSSIModel) -> Self {
+        self.overrides.insert(beacon_id.into(), model);
+        self
+    }
+
+    /// 信标使用的模型
+    pub fn model_for(&self, beacon_id: &str) -> &RSSIModel {
+        self.overrides.get(beacon_id).unwrap_or(&self.default)
+    }
+}
+
+impl DistanceEstimator for PerBeaconModels {
+    fn estimate(&self, beacon_id: &str, rssi_window: &[i16]) -> Option<RangeEstimate> {
+        self.model_for(beacon_id).estimate(beacon_id, rssi_window)
+    }
+
+    fn expected_rssi(&self, beacon_id: &str, distance: f64) -> Option<f64> {
+        self.model_for(beacon_id).expected_rssi(beacon_id, distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::DistanceUnit;
+
+    #[test]
+    fn test_rssi_model_estimate_with_sigma() {
+        let model = RSSIModel::log_distance(-50.0, -20.0, DistanceUnit::Meter).with_sigma(4.0);
+        let range = model.estimate("B1", &[-68, -72]).unwrap();
+        assert!((range.distance - 10.0).abs() < 1e-9);
+        let expected = 10.0 * LN_10 * 4.0 / (20.0 * 2f64.sqrt());
+        assert!((range.std_dev.unwrap() - expected).abs() < 1e-9);
+        assert!(model.estimate("B1", &[]).is_none());
+    }
+
+    #[test]
+    fn test_per_beacon_models() {
+        let models = PerBeaconModels::new(RSSIModel::log_distance(-50.0, -20.0, DistanceUnit::Meter))
+            .with_model("WEAK", RSSIModel::log_distance(-60.0, -20.0, DistanceUnit::Meter));
+        assert!((models.distance("ANY", -70).unwrap() - 10.0).abs() < 1e-9);
+        assert!((models.distance("WEAK", -70).unwrap() - 10f64.sqrt()).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,294 @@
+/// 信标长期漂移报告
+///
+/// 电池电压下降、外壳积灰、周围货架变化都会让信标的发射强度慢慢改变，
+/// 定位精度随之悄悄下降。这里在已知几何关系（固定参考标签、巡检点或高置信度定位）下，
+/// 持续记录每个信标的实测 RSSI 与标定模型预测值之差，按时间做线性回归得到漂移速率 (dB/天)，
+/// 与标定时的基线比较，漂移超出阈值时提示重新标定。
+///
+/// 典型用法：`record` 随数据持续调用，定时（如每天）调用 `report`
+
+use crate::algorithms::{BeaconSet, BlunavEvent, EventBus, RSSIModel, RssiHistogram, SignalReadings};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::Duration;
+
+/// 每天的秒数
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// 单个信标的漂移分析结果
+#[derive(Clone, Debug, PartialEq)]
+pub struct BeaconDrift {
+    /// 信标 ID
+    pub beacon_id: String,
+    /// 保留期内的样本数
+    pub samples: usize,
+    /// 样本覆盖的天数
+    pub span_days: f64,
+    /// 平均残差相对标定基线的偏移 (dB)
+    pub offset_db: f64,
+    /// 残差随时间的变化速率 (dB/天)
+    pub drift_db_per_day: f64,
+    /// 是否建议重新标定
+    pub needs_recalibration: bool,
+}
+
+/// 漂移监测器
+#[derive(Clone, Debug)]
+pub struct DriftMonitor {
+    beacons: BeaconSet,
+    /// 标定得到的模型
+    model: RSSIModel,
+    /// 信标 ID -> 标定时的平均残差 (dB)，未设置时为 0
+    baselines: HashMap<String, f64>,
+    /// 样本保留时长
+    history: Duration,
+    /// 给出结论前的最少样本数
+    min_samples: usize,
+    /// 给出结论前样本须覆盖的天数
+    min_span_days: f64,
+    /// 允许的基线偏移 (dB)
+    max_offset_db: f64,
+    /// 允许的漂移速率 (dB/天)
+    max_drift_db_per_day: f64,
+    /// 信标 ID -> (时间, 残差 dB)
+    samples: BTreeMap<String, Vec<(DateTime<Utc>, f64)>>,
+    /// 已提示重新标定的信标
+    flagged: BTreeSet<String>,
+    events: Option<EventBus>,
+}
+
+impl DriftMonitor {
+    /// 创建监测器（默认保留 30 天、至少 20 个样本且覆盖 1 天、偏移 6 dB 或 0.5 dB/天时提示）
+    ///
+    /// # 参数
+    /// - `beacons`: 配置的信标位置
+    /// - `model`: 标定得到的测距模型，单位须与信标坐标一致
+    pub fn new(beacons: BeaconSet, model: RSSIModel) -> Self {
+        DriftMonitor {
+            beacons,
+            model,
+            baselines: HashMap::new(),
+            history: Duration::from_secs(30 * 86_400),
+            min_samples: 20,
+            min_span_days: 1.0,
+            max_offset_db: 6.0,
+            max_drift_db_per_day: 0.5,
+            samples: BTreeMap::new(),
+            flagged: BTreeSet::new(),
+            events: None,
+        }
+    }
+
+    /// 设置信标的标定基线（标定时实测 RSSI 与模型预测值的平均差, dB）
+    pub fn with_baseline(mut self, beacon_id: impl Into<String>, offset_db: f64) -> Self {
+        self.baselines.insert(beacon_id.into(), offset_db);
+        self
+    }
+
+    /// 由标定会话的直方图（`CalibrationSession::histograms`）计算各信标的基线
+    pub fn with_calibration_histograms(mut self, histograms: &[RssiHistogram]) -> Self {
+        let mut sums: HashMap<&str, (f64, usize)> = HashMap::new();
+        for histogram in histograms {
+            let expected = self.model.distance_to_rssi(histogram.distance);
+            let count = histogram.sample_count();
+            if !expected.is_finite() || count == 0 {
+                continue;
+            }
+            let entry = sums.entry(histogram.beacon_id.as_str()).or_default();
+            entry.0 += (histogram.mean() - expected) * count as f64;
+            entry.1 += count;
+        }
+        for (beacon_id, (sum, count)) in sums {
+            self.baselines.insert(beacon_id.to_string(), sum / count as f64);
+        }
+        self
+    }
+
+    /// 设置样本保留时长（默认 30 天）
+    pub fn with_history(mut self, history: Duration) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// 设置给出结论所需的最少样本数与覆盖天数
+    pub fn with_min_samples(mut self, min_samples: usize, min_span_days: f64) -> Self {
+        self.min_samples = min_samples.max(2);
+        self.min_span_days = min_span_days.max(0.0);
+        self
+    }
+
+    /// 设置提示阈值
+    ///
+    /// # 参数
+    /// - `max_offset_db`: 平均残差相对基线允许的偏移 (dB)
+    /// - `max_drift_db_per_day`: 允许的漂移速率 (dB/天)
+    pub fn with_thresholds(mut self, max_offset_db: f64, max_drift_db_per_day: f64) -> Self {
+        self.max_offset_db = max_offset_db.abs();
+        self.max_drift_db_per_day = max_drift_db_per_day.abs();
+        self
+    }
+
+    /// 设置事件总线，新提示的信标以 `BeaconDrifting` 事件发布
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// 信标的标定基线 (dB)
+    pub fn baseline(&self, beacon_id: &str) -> f64 {
+        self.baselines.get(beacon_id).copied().unwrap_or(0.0)
+    }
+
+    /// 记录已知位置上的一组读数
+    ///
+    /// # 参数
+    /// - `position`: 接收端的已知位置 (x, y, z)
+    /// - `signals`: 当时的信号读数
+    /// - `timestamp`: 采集时间
+    ///
+    /// # 返回
+    /// - 记录的样本数（未知信标和与信标重合的位置被忽略）
+    pub fn record(&mut self, position: (f64, f64, f64), signals: &SignalReadings, timestamp: DateTime<Utc>) -> usize {
+        let mut recorded = 0;
+        for (beacon_id, rssi) in signals.all() {
+            let Some(beacon) = self.beacons.get(beacon_id) else {
+                continue;
+            };
+            let distance = ((position.0 - beacon.x).powi(2) + (position.1 - beacon.y).powi(2) + (position.2 - beacon.z).powi(2)).sqrt();
+            let expected = self.model.distance_to_rssi(distance);
+            if !expected.is_finite() {
+                continue;
+            }
+            self.samples.entry(beacon_id.clone()).or_default().push((timestamp, *rssi as f64 - expected));
+            recorded += 1;
+        }
+        recorded
+    }
+
+    /// 生成漂移报告，同时丢弃超出保留期的样本
+    ///
+    /// # 返回
+    /// - 有样本的信标的分析结果，按漂移速率绝对值降序排列
+    pub fn report(&mut self, now: DateTime<Utc>) -> Vec<BeaconDrift> {
+        if let Ok(history) = chrono::Duration::from_std(self.history) {
+            let cutoff = now - history;
+            for samples in self.samples.values_mut() {
+                samples.retain(|(t, _)| *t >= cutoff);
+            }
+            self.samples.retain(|_, samples| !samples.is_empty());
+        }
+
+        let mut report: Vec<BeaconDrift> = self
+            .samples
+            .iter()
+            .map(|(beacon_id, samples)| self.analyze(beacon_id, samples))
+            .collect();
+        report.sort_by(|a, b| b.drift_db_per_day.abs().total_cmp(&a.drift_db_per_day.abs()));
+
+        for drift in report.iter().filter(|d| d.needs_recalibration) {
+            if self.flagged.insert(drift.beacon_id.clone())
+                && let Some(events) = &self.events
+            {
+                events.emit(BlunavEvent::BeaconDrifting {
+                    beacon_id: drift.beacon_id.clone(),
+                    drift_db_per_day: drift.drift_db_per_day,
+                });
+            }
+        }
+        report
+    }
+
+    /// 重新标定后清除信标的样本并更新基线
+    pub fn recalibrated(&mut self, beacon_id: &str, offset_db: f64) {
+        self.samples.remove(beacon_id);
+        self.flagged.remove(beacon_id);
+        self.baselines.insert(beacon_id.to_string(), offset_db);
+    }
+
+    /// 单个信标的线性回归
+    fn analyze(&self, beacon_id: &str, samples: &[(DateTime<Utc>, f64)]) -> BeaconDrift {
+        let n = samples.len() as f64;
+        let start = samples.iter().map(|(t, _)| *t).min().unwrap_or_default();
+        let days: Vec<f64> = samples
+            .iter()
+            .map(|(t, _)| (*t - start).num_milliseconds() as f64 / 1000.0 / SECONDS_PER_DAY)
+            .collect();
+        let mean_t = days.iter().sum::<f64>() / n;
+        let mean_r = samples.iter().map(|(_, r)| r).sum::<f64>() / n;
+        let (cov, var) = days.iter().zip(samples).fold((0.0, 0.0), |(cov, var), (t, (_, r))| {
+            (cov + (t - mean_t) * (r - mean_r), var + (t - mean_t).powi(2))
+        });
+        let drift_db_per_day = if var > 0.0 { cov / var } else { 0.0 };
+        let span_days = days.iter().copied().fold(0.0, f64::max);
+        let offset_db = mean_r - self.baseline(beacon_id);
+
+        let enough = samples.len() >= self.min_samples && span_days >= self.min_span_days;
+        BeaconDrift {
+            beacon_id: beacon_id.to_string(),
+            samples: samples.len(),
+            span_days,
+            offset_db,
+            drift_db_per_day,
+            needs_recalibration: enough
+                && (offset_db.abs() > self.max_offset_db || drift_db_per_day.abs() > self.max_drift_db_per_day),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{Beacon, DistanceUnit};
+
+    #[test]
+    fn test_drift_rate_and_recalibration_prompt() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("B1".to_string(), String::new(), 0.0, 0.0, 0.0),
+            Beacon::new("B2".to_string(), String::new(), 10.0, 0.0, 0.0),
+        ]);
+        let model = RSSIModel::log_distance(-60.0, -20.0, DistanceUnit::Meter);
+        let events = EventBus::default();
+        let mut received = events.subscribe();
+        let mut monitor = DriftMonitor::new(beacons, model.clone()).with_baseline("B2", 2.0).with_events(events);
+
+        // 参考标签位于 (5, 0)：B1 每天变弱 1 dB，B2 保持在基线上
+        let t0 = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let expected = model.distance_to_rssi(5.0);
+        for hour in 0..(10 * 24) {
+            let day = hour as f64 / 24.0;
+            let signals = SignalReadings::from_pairs(vec![
+                ("B1", (expected - day).round() as i16),
+                ("B2", (expected + 2.0).round() as i16),
+            ]);
+            assert_eq!(monitor.record((5.0, 0.0, 0.0), &signals, t0 + chrono::Duration::hours(hour)), 2);
+        }
+
+        let report = monitor.report(t0 + chrono::Duration::days(10));
+        assert_eq!(report[0].beacon_id, "B1");
+        assert!((report[0].drift_db_per_day + 1.0).abs() < 0.05);
+        assert!(report[0].needs_recalibration);
+        assert!(report[1].drift_db_per_day.abs() < 0.05);
+        assert!(report[1].offset_db.abs() < 0.5);
+        assert!(!report[1].needs_recalibration);
+        assert_eq!(received.try_recv().unwrap().kind(), "beacon_drifting");
+
+        // 每个信标只提示一次，重新标定后清零
+        monitor.report(t0 + chrono::Duration::days(10));
+        assert!(received.try_recv().is_err());
+        monitor.recalibrated("B1", 0.0);
+        assert_eq!(monitor.report(t0 + chrono::Duration::days(10)).len(), 1);
+    }
+
+    #[test]
+    fn test_baseline_from_calibration_histograms() {
+        let model = RSSIModel::log_distance(-60.0, -20.0, DistanceUnit::Meter);
+        let histogram = RssiHistogram {
+            beacon_id: "B1".to_string(),
+            distance: 10.0,
+            counts: BTreeMap::from([(-77, 5), (-79, 5)]),
+        };
+        let monitor = DriftMonitor::new(BeaconSet::new(), model).with_calibration_histograms(&[histogram]);
+        assert!((monitor.baseline("B1") - 2.0).abs() < 1e-9);
+        assert_eq!(monitor.baseline("B2"), 0.0);
+    }
+}
@@ -0,0 +1,1035 @@
+/// 定位引擎
+///
+/// 将观测记录、按窗口聚合 RSSI、求解与后处理串成一个同步流程。
+/// `PositioningEngine::manual()` 不连接扫描器也不依赖 tokio 运行时，
+/// 适合已经通过串口网关、云端接入等自有通道拿到 RSSI 的场景
+
+use crate::algorithms::{
+    AdaptiveNoise, AdaptiveRate, BeaconSet, BlunavEvent, Clock, DistanceEstimator, EventBus, GateStats, KalmanFilter1D, LatencyHistogram, LocationAlgorithm, LocationResult,
+    Metadata, Observation, OutputGate, PostProcessPipeline, PostProcessor, PushOutcome, ReorderBuffer, RssiAggregation, SignalMeasurement, SignalReadings, SignalStats, SourceStats, SystemClock,
+};
+use chrono::DateTime;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 引擎运行统计
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EngineStats {
+    /// 接收的观测数
+    pub observations_fed: u64,
+    /// 因类型不符或信标未知而忽略的观测数
+    pub observations_ignored: u64,
+    /// 求解次数
+    pub solves: u64,
+    /// 输出的定位结果数
+    pub fixes: u64,
+    /// 因积压被丢弃的观测数
+    pub observations_dropped: u64,
+    /// 因到达过晚或时间戳不可信被重排序缓冲区丢弃的观测数
+    pub observations_late: u64,
+    /// 被合并到同一信标待处理样本中的观测数
+    pub observations_coalesced: u64,
+    /// 复用上一次求解结果的次数
+    pub cache_hits: u64,
+    /// 输出门限的通过与拒绝次数
+    pub gate: GateStats,
+    /// 从本历元最早参与求解的观测到输出定位结果的延迟
+    pub latency: LatencyHistogram,
+    /// 按观测来源的统计
+    pub sources: SourceStats,
+}
+
+/// 观测积压时的处理策略
+///
+/// 观测先进入待处理队列，在 `solve()` 或 `process_pending()` 时批量写入；
+/// 求解跟不上输入速率时由策略限制队列长度，而不是无限增长
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IngestPolicy {
+    /// 立即写入，不排队（默认）
+    #[default]
+    Immediate,
+    /// 每个信标最多保留 `max_per_beacon` 条待处理观测，超出时丢弃该信标最旧的一条
+    DropOldestPerBeacon {
+        /// 每个信标的待处理上限
+        max_per_beacon: usize,
+    },
+    /// 每个信标只保留一条待处理观测，新观测与之按 RSSI 均值合并
+    Coalesce,
+}
+
+/// 求解时机（历元划分）策略，由 [`PositioningEngine::poll`] 使用
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EpochStrategy {
+    /// 固定时间间隔求解一次（默认 1 秒）
+    Timer {
+        /// 求解间隔
+        interval: Duration,
+    },
+    /// 自上次求解以来收到 `count` 个不同信标的新数据后求解
+    DistinctBeacons {
+        /// 所需的不同信标数
+        count: usize,
+    },
+}
+
+impl Default for EpochStrategy {
+    fn default() -> Self {
+        EpochStrategy::Timer {
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// 一个历元的输出
+#[derive(Clone, Debug, Default)]
+pub struct EpochOutput {
+    /// 求解器的原始结果（后处理之前）
+    pub raw: Option<LocationResult>,
+    /// 经过后处理的结果，被后处理丢弃时为 None
+    pub filtered: Option<LocationResult>,
+}
+
+/// 定位引擎
+pub struct PositioningEngine {
+    /// 信标集合
+    beacons: BeaconSet,
+    /// 测距模型
+    model: Box<dyn DistanceEstimator + Send>,
+    /// 按信标保存的 RSSI 样本
+    signals: SignalStats,
+    /// 聚合窗口
+    window: Duration,
+    /// 窗口内 RSSI 的聚合方式
+    aggregation: RssiAggregation,
+    /// 自适应 RSSI 平滑：噪声估计与过程噪声
+    adaptive_noise: Option<(AdaptiveNoise, f64)>,
+    /// 各信标的 RSSI 平滑滤波器
+    rssi_filters: HashMap<String, KalmanFilter1D>,
+    /// 输出门限，在后处理之前检查
+    gate: Option<OutputGate>,
+    /// 后处理流水线
+    pipeline: PostProcessPipeline,
+    /// 时间来源
+    clock: Arc<dyn Clock>,
+    /// 事件总线
+    events: Option<EventBus>,
+    /// 积压处理策略
+    policy: IngestPolicy,
+    /// 乱序观测的重排序缓冲区
+    reorder: Option<ReorderBuffer>,
+    /// 待处理观测：信标 ID -> (样本, 合并的观测数)
+    pending: HashMap<String, VecDeque<(SignalMeasurement, u32)>>,
+    /// 复用结果的 RSSI 变化阈值 (dB)，None 表示不复用
+    cache_threshold_db: Option<i16>,
+    /// 上一次求解的输入与结果（后处理之前）
+    last_solve: Option<(SignalReadings, LocationResult)>,
+    /// 本历元内已接收观测中最早的时间戳（毫秒），记录延迟后清空
+    oldest_observation_ms: Option<u64>,
+    /// 求解时机策略
+    epoch: EpochStrategy,
+    /// 按运动状态调整求解时机
+    adaptive_rate: Option<AdaptiveRate>,
+    /// 是否暂停定时求解
+    paused: bool,
+    /// 上一次求解的时间（毫秒）
+    last_epoch_ms: Option<u64>,
+    /// 自上次求解以来收到新数据的信标
+    fresh_beacons: HashSet<String>,
+    /// 最近一次输出的定位结果
+    last_fix: Option<LocationResult>,
+    /// 置信度衰减半衰期
+    confidence_half_life: Duration,
+    /// 是否同时发送原始结果事件
+    dual_output: bool,
+    /// 运行统计
+    stats: EngineStats,
+    /// 已接收观测附带的业务数据 (时间戳毫秒, 数据)，按时间升序，随聚合窗口过期
+    metadata: VecDeque<(u64, Metadata)>,
+}
+
+impl PositioningEngine {
+    /// 创建手动输入的引擎（不连接扫描器）
+    ///
+    /// # 参数
+    /// - `beacons`: 信标集合
+    /// - `model`: 测距模型（`RSSIModel` 或任意 `DistanceEstimator`）
+    pub fn manual(beacons: BeaconSet, model: impl DistanceEstimator + Send + 'static) -> Self {
+        let window = Duration::from_secs(2);
+        PositioningEngine {
+            beacons,
+            model: Box::new(model),
+            signals: SignalStats::new(window),
+            window,
+            aggregation: RssiAggregation::default(),
+            adaptive_noise: None,
+            rssi_filters: HashMap::new(),
+            gate: None,
+            pipeline: PostProcessPipeline::new(),
+            clock: Arc::new(SystemClock),
+            events: None,
+            policy: IngestPolicy::Immediate,
+            reorder: None,
+            pending: HashMap::new(),
+            cache_threshold_db: None,
+            last_solve: None,
+            oldest_observation_ms: None,
+            epoch: EpochStrategy::default(),
+            adaptive_rate: None,
+            paused: false,
+            last_epoch_ms: None,
+            fresh_beacons: HashSet::new(),
+            last_fix: None,
+            confidence_half_life: Duration::from_secs(10),
+            dual_output: false,
+            stats: EngineStats::default(),
+            metadata: VecDeque::new(),
+        }
+    }
+
+    /// 设置 RSSI 聚合窗口（默认 2 秒）
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self.signals = SignalStats::new(window).with_clock(self.clock.clone());
+        self
+    }
+
+    /// 设置窗口内 RSSI 的聚合方式（默认取均值）
+    ///
+    /// 缓慢移动的目标建议使用 `Mode` 或 `UpperPercentile`，对快衰落更稳定
+    pub fn with_rssi_aggregation(mut self, aggregation: RssiAggregation) -> Self {
+        self.aggregation = aggregation;
+        self
+    }
+
+    /// 求解前按信标平滑聚合后的 RSSI：每个历元以窗口内的 RSSI 方差（[`AdaptiveNoise::rssi_noise`]）
+    /// 作为卡尔曼滤波的测量噪声，抖动大的信标对定位结果的影响随之减小
+    ///
+    /// # 参数
+    /// - `noise`: 自适应噪声估计
+    /// - `q`: 过程噪声 (dBm²)，越大越快跟随 RSSI 的真实变化
+    pub fn with_adaptive_noise(mut self, noise: AdaptiveNoise, q: f64) -> Self {
+        self.adaptive_noise = Some((noise, q));
+        self
+    }
+
+    /// 使用指定的时钟（测试或回放时可传入 `MockClock`）
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.signals = SignalStats::new(self.window).with_clock(clock.clone());
+        self.reorder = self.reorder.take().map(|reorder| reorder.with_clock(clock.clone()));
+        self.pipeline.set_clock(clock.clone());
+        if let Some(rate) = &mut self.adaptive_rate {
+            rate.set_clock(clock.clone());
+        }
+        self.clock = clock;
+        self
+    }
+
+    /// 设置输出门限：信标数或置信度不足的结果在后处理之前被丢弃，
+    /// 拒绝次数计入 [`EngineStats::gate`]，设置了事件总线时以 `FixRejected` 事件发出
+    pub fn with_output_gate(mut self, min_beacons: usize, min_confidence: f64) -> Self {
+        self.gate = Some(OutputGate::new(min_beacons, min_confidence));
+        self
+    }
+
+    /// 设置后处理流水线
+    pub fn with_pipeline(mut self, mut pipeline: PostProcessPipeline) -> Self {
+        pipeline.set_clock(self.clock.clone());
+        self.pipeline = pipeline;
+        self
+    }
+
+    /// 输出的定位结果同时以 `BlunavEvent::FixComputed` 发送到事件总线
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// 启用乱序重排序：带时间戳的观测先在 [`ReorderBuffer`] 中等待 `max_lateness`，
+    /// 按时间戳顺序进入样本窗口；过晚到达的观测被丢弃并计入 `observations_late`
+    pub fn with_reorder_buffer(mut self, max_lateness: Duration) -> Self {
+        self.reorder = Some(ReorderBuffer::new(max_lateness).with_clock(self.clock.clone()));
+        self
+    }
+
+    /// 设置积压处理策略
+    pub fn with_ingest_policy(mut self, policy: IngestPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// 启用结果复用：参与求解的信标不变且每个信标的 RSSI 变化都不超过 `threshold_db` 时，
+    /// 跳过求解直接复用上一次结果（更新时间戳），适合大部分标签静止的网关
+    pub fn with_result_cache(mut self, threshold_db: i16) -> Self {
+        self.cache_threshold_db = Some(threshold_db.max(0));
+        self
+    }
+
+    /// 设置求解时机策略
+    pub fn with_epoch_strategy(mut self, epoch: EpochStrategy) -> Self {
+        self.epoch = epoch;
+        self
+    }
+
+    /// 运行中调整求解时机策略（如按 `AdaptiveRate` 建议的间隔）
+    pub fn set_epoch_strategy(&mut self, epoch: EpochStrategy) {
+        self.epoch = epoch;
+    }
+
+    /// 运行中调整定时求解的间隔，等价于设置 [`EpochStrategy::Timer`]
+    ///
+    /// 启用了 [`PositioningEngine::with_adaptive_rate`] 时，运动状态变化后会改回自适应建议的间隔
+    pub fn set_update_interval(&mut self, interval: Duration) {
+        self.epoch = EpochStrategy::Timer { interval };
+    }
+
+    /// 暂停定位：[`PositioningEngine::poll`] 不再求解，观测仍照常记录，
+    /// 宿主界面隐藏等场景下无需停止扫描
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// 恢复定位，下一次 [`PositioningEngine::poll`] 立即求解
+    pub fn resume(&mut self) {
+        if self.paused {
+            self.paused = false;
+            self.last_epoch_ms = None;
+        }
+    }
+
+    /// 是否已暂停
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// 按运动状态自适应求解时机：每个输出结果都用于更新运动状态，
+    /// 状态变化时改用 [`AdaptiveRate::epoch_strategy`] 建议的定时策略，
+    /// 静止标签因此按较长间隔求解
+    pub fn with_adaptive_rate(mut self, mut rate: AdaptiveRate) -> Self {
+        rate.set_clock(self.clock.clone());
+        self.epoch = rate.epoch_strategy();
+        self.adaptive_rate = Some(rate);
+        self
+    }
+
+    /// 双路输出：每个历元同时以 `RawFixComputed` 事件发出求解器的原始结果，
+    /// 便于记录原始数据供日后重新滤波，同时实时显示平滑轨迹
+    pub fn with_dual_output(mut self) -> Self {
+        self.dual_output = true;
+        self
+    }
+
+    /// 设置 [`PositioningEngine::last_known_position`] 的置信度衰减半衰期（默认 10 秒）
+    pub fn with_confidence_half_life(mut self, half_life: Duration) -> Self {
+        self.confidence_half_life = half_life;
+        self
+    }
+
+    /// 最近一次输出的定位结果，置信度按结果的年龄衰减
+    pub fn last_known_position(&self) -> Option<LocationResult> {
+        let now = DateTime::from_timestamp_millis(self.clock.now_ms() as i64)?;
+        self.last_fix.as_ref().map(|fix| fix.aged(now, self.confidence_half_life))
+    }
+
+    /// 信标集合
+    pub fn beacons(&self) -> &BeaconSet {
+        &self.beacons
+    }
+
+    /// 运行统计
+    pub fn stats(&self) -> &EngineStats {
+        &self.stats
+    }
+
+    /// RSSI 聚合窗口
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// 窗口内 RSSI 的聚合方式
+    pub fn rssi_aggregation(&self) -> RssiAggregation {
+        self.aggregation
+    }
+
+    /// 积压处理策略
+    pub fn ingest_policy(&self) -> IngestPolicy {
+        self.policy
+    }
+
+    /// 求解时机策略
+    pub fn epoch_strategy(&self) -> EpochStrategy {
+        self.epoch
+    }
+
+    /// 输入一条观测
+    ///
+    /// 目标标识会按信标别名解析为信标主 ID，非 RSSI 观测和未知信标的观测被忽略。
+    /// 被接收观测的 `metadata` 与样本一起保存，求解时合并聚合窗口内观测的数据（同名键取最新值），
+    /// 超出窗口的数据随样本一起过期
+    ///
+    /// # 返回
+    /// - 观测是否被接收
+    pub fn feed_observation(&mut self, mut observation: Observation) -> bool {
+        self.stats.observations_fed += 1;
+        let measurement = self.resolve_measurement(&observation);
+        self.stats.sources.record(&observation, measurement.is_some(), self.clock.now_ms());
+        let Some(measurement) = measurement else {
+            self.stats.observations_ignored += 1;
+            return false;
+        };
+        if let Some(reorder) = &mut self.reorder {
+            observation.target = measurement.beacon_id;
+            return match reorder.push(observation) {
+                PushOutcome::TooLate | PushOutcome::Unconfirmed { .. } => {
+                    self.stats.observations_late += 1;
+                    false
+                }
+                PushOutcome::Accepted | PushOutcome::ClockJump { .. } => true,
+            };
+        }
+        self.ingest(measurement, observation.metadata);
+        true
+    }
+
+    /// 已按时间顺序到达的测量进入样本窗口（或积压队列）
+    fn ingest(&mut self, measurement: SignalMeasurement, metadata: Metadata) {
+        let timestamp_ms = measurement.timestamp_ms.unwrap_or_else(|| self.clock.now_ms());
+        self.oldest_observation_ms = Some(self.oldest_observation_ms.map_or(timestamp_ms, |t| t.min(timestamp_ms)));
+        self.fresh_beacons.insert(measurement.beacon_id.clone());
+        if !metadata.is_empty() {
+            let pos = self.metadata.partition_point(|(t, _)| *t <= timestamp_ms);
+            self.metadata.insert(pos, (timestamp_ms, metadata));
+        }
+
+        match self.policy {
+            IngestPolicy::Immediate => self.signals.record(&measurement),
+            IngestPolicy::DropOldestPerBeacon { max_per_beacon } => {
+                let queue = self.pending.entry(measurement.beacon_id.clone()).or_default();
+                queue.push_back((measurement, 1));
+                while queue.len() > max_per_beacon.max(1) {
+                    queue.pop_front();
+                    self.stats.observations_dropped += 1;
+                }
+            }
+            IngestPolicy::Coalesce => {
+                let queue = self.pending.entry(measurement.beacon_id.clone()).or_default();
+                match queue.back_mut() {
+                    Some((merged, count)) => {
+                        // 增量均值，时间戳取较新者
+                        let total = merged.rssi as f64 * *count as f64 + measurement.rssi as f64;
+                        *count += 1;
+                        merged.rssi = (total / *count as f64).round() as i16;
+                        merged.timestamp_ms = merged.timestamp_ms.max(measurement.timestamp_ms);
+                        self.stats.observations_coalesced += 1;
+                    }
+                    None => queue.push_back((measurement, 1)),
+                }
+            }
+        }
+    }
+
+    /// 释放重排序缓冲区中已到期的观测
+    fn release_reordered(&mut self) {
+        let Some(reorder) = &mut self.reorder else {
+            return;
+        };
+        for observation in reorder.drain_ready() {
+            if let Some(measurement) = observation.to_signal_measurement() {
+                self.ingest(measurement, observation.metadata);
+            }
+        }
+    }
+
+    /// 将 RSSI 观测转换为以信标主 ID 标识的测量，非 RSSI 观测或未知信标返回 None
+    fn resolve_measurement(&self, observation: &Observation) -> Option<SignalMeasurement> {
+        let mut measurement = observation.to_signal_measurement()?;
+        measurement.beacon_id = self.beacons.resolve(&measurement.beacon_id)?.id.clone();
+        Some(measurement)
+    }
+
+    /// 待处理的观测数
+    pub fn pending_count(&self) -> usize {
+        self.pending.values().map(|q| q.len()).sum()
+    }
+
+    /// 将待处理观测写入样本窗口
+    ///
+    /// 重排序缓冲区中已到期的观测先按时间顺序释放
+    ///
+    /// # 返回
+    /// - 从积压队列写入的样本数
+    pub fn process_pending(&mut self) -> usize {
+        self.release_reordered();
+        let mut count = 0;
+        for (_, queue) in self.pending.drain() {
+            for (measurement, _) in queue {
+                self.signals.record(&measurement);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// 当前窗口内各信标按聚合方式得到的 RSSI
+    pub fn current_readings(&self) -> SignalReadings {
+        self.signals.aggregated_readings(self.window, self.aggregation)
+    }
+
+    /// 用各信标的卡尔曼滤波器平滑 RSSI，未启用自适应噪声时原样返回
+    fn smooth_readings(&mut self, readings: SignalReadings) -> SignalReadings {
+        let Some((noise, q)) = &self.adaptive_noise else {
+            return readings;
+        };
+        let mut smoothed = SignalReadings::new();
+        for (id, rssi) in readings.all() {
+            let r = noise.rssi_noise(&self.signals, id);
+            let filter = self
+                .rssi_filters
+                .entry(id.clone())
+                .or_insert_with(|| KalmanFilter1D::new(*q, r, *rssi as f64));
+            smoothed.add(id.clone(), filter.update_with_noise(*rssi as f64, r).round() as i16);
+        }
+        smoothed
+    }
+
+    /// 按求解时机策略判断当前是否应开始新的历元
+    pub fn epoch_ready(&self) -> bool {
+        match self.epoch {
+            EpochStrategy::Timer { interval } => self
+                .last_epoch_ms
+                .is_none_or(|last| self.clock.now_ms().saturating_sub(last) >= interval.as_millis() as u64),
+            EpochStrategy::DistinctBeacons { count } => self.fresh_beacons.len() >= count.max(1),
+        }
+    }
+
+    /// 历元到达时求解，否则返回 None
+    ///
+    /// 输入观测后或定时调用均可，由 [`EpochStrategy`] 决定是否真正求解；暂停期间始终返回 None
+    pub fn poll(&mut self) -> Option<LocationResult> {
+        self.release_reordered();
+        if self.paused || !self.epoch_ready() {
+            return None;
+        }
+        self.solve()
+    }
+
+    /// 用当前窗口内的数据求解一次
+    ///
+    /// # 返回
+    /// - 经过后处理的定位结果，或 None 如果信标不足或被后处理丢弃
+    pub fn solve(&mut self) -> Option<LocationResult> {
+        self.solve_epoch().filtered
+    }
+
+    /// 求解一次并同时返回原始结果与后处理结果
+    pub fn solve_epoch(&mut self) -> EpochOutput {
+        let raw = self.solve_raw();
+        // 延迟按本历元新消费的观测中最早的一个计算（结果至少要等它），未收到新观测的历元不计入
+        let observed_ms = self.oldest_observation_ms.take();
+        let Some(raw) = raw else {
+            return EpochOutput::default();
+        };
+        if self.dual_output
+            && let Some(events) = &self.events
+        {
+            events.emit(BlunavEvent::RawFixComputed(raw.clone()));
+        }
+
+        let gated = match &self.gate {
+            Some(gate) => {
+                let outcome = gate.check(&raw);
+                self.stats.gate.record(&outcome);
+                match outcome {
+                    Ok(()) => Some(raw.clone()),
+                    Err(reason) => {
+                        if let Some(events) = &self.events {
+                            events.emit(BlunavEvent::FixRejected { result: raw.clone(), reason });
+                        }
+                        None
+                    }
+                }
+            }
+            None => Some(raw.clone()),
+        };
+        let filtered = gated.and_then(|result| self.pipeline.process(result));
+        if let Some(result) = &filtered {
+            self.stats.fixes += 1;
+            if let Some(observed_ms) = observed_ms {
+                let now_ms = raw.timestamp.timestamp_millis().max(0) as u64;
+                self.stats.latency.record(Duration::from_millis(now_ms.saturating_sub(observed_ms)));
+            }
+            if let Some(events) = &self.events {
+                events.emit(BlunavEvent::FixComputed(result.clone()));
+            }
+            if let Some(rate) = &mut self.adaptive_rate
+                && rate.observe(result)
+            {
+                self.epoch = rate.epoch_strategy();
+            }
+            self.last_fix = Some(result.clone());
+        }
+        EpochOutput {
+            raw: Some(raw),
+            filtered,
+        }
+    }
+
+    /// 开始新的历元并运行求解器（不经过后处理）
+    fn solve_raw(&mut self) -> Option<LocationResult> {
+        self.stats.solves += 1;
+        self.last_epoch_ms = Some(self.clock.now_ms());
+        self.fresh_beacons.clear();
+        self.process_pending();
+        let readings = self.current_readings();
+        let readings = self.smooth_readings(readings);
+        let mut result = match self.cached_result(&readings) {
+            Some(cached) => {
+                self.stats.cache_hits += 1;
+                cached
+            }
+            None => {
+                let beacons = self.beacons.all_cloned();
+                let solved = LocationAlgorithm::trilateration_least_squares(&beacons, &readings, self.model.as_ref());
+                if self.cache_threshold_db.is_some() {
+                    self.last_solve = solved.clone().map(|r| (readings, r));
+                }
+                solved?
+            }
+        };
+        if let Some(timestamp) = DateTime::from_timestamp_millis(self.clock.now_ms() as i64) {
+            result.timestamp = timestamp;
+        }
+        result.metadata = self.window_metadata();
+        Some(result)
+    }
+
+    /// 合并聚合窗口内观测的业务数据，并丢弃已过期的数据
+    fn window_metadata(&mut self) -> Metadata {
+        let now = self.clock.now_ms();
+        let start = now.saturating_sub(self.window.as_millis() as u64);
+        while self.metadata.front().is_some_and(|(t, _)| *t < start) {
+            self.metadata.pop_front();
+        }
+        let mut merged = Metadata::new();
+        for (_, metadata) in self.metadata.iter().take_while(|(t, _)| *t <= now) {
+            merged.extend(metadata.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        merged
+    }
+
+    /// 输入与上一次求解足够接近时返回上一次的结果
+    fn cached_result(&self, readings: &SignalReadings) -> Option<LocationResult> {
+        let threshold = self.cache_threshold_db?;
+        let (previous, result) = self.last_solve.as_ref()?;
+        let unchanged = previous.count() == readings.count()
+            && readings
+                .all()
+                .iter()
+                .all(|(id, rssi)| previous.get(id).is_some_and(|p| (p - rssi).abs() <= threshold));
+        unchanged.then(|| result.clone())
+    }
+
+    /// 清空已记录的样本和最近一次定位结果，并重置后处理状态
+    pub fn reset(&mut self) {
+        self.signals.clear();
+        self.pending.clear();
+        if let Some(reorder) = &mut self.reorder {
+            reorder.clear();
+        }
+        self.last_solve = None;
+        self.oldest_observation_ms = None;
+        self.last_epoch_ms = None;
+        self.fresh_beacons.clear();
+        self.rssi_filters.clear();
+        self.metadata.clear();
+        self.last_fix = None;
+        if let Some(rate) = &mut self.adaptive_rate {
+            rate.reset();
+            self.epoch = rate.epoch_strategy();
+        }
+        self.pipeline.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{AdaptiveNoise, AdaptiveRate, Beacon, MockClock, ObservationSource, OutputGate, RSSIModel};
+
+    #[test]
+    fn test_manual_feed_and_solve() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("B1".to_string(), "b1".to_string(), 0.0, 0.0, 0.0).with_alias("AA:BB:CC:DD:EE:01"),
+            Beacon::new("B2".to_string(), "b2".to_string(), 1000.0, 0.0, 0.0),
+            Beacon::new("B3".to_string(), "b3".to_string(), 0.0, 1000.0, 0.0),
+            Beacon::new("B4".to_string(), "b4".to_string(), 1000.0, 1000.0, 0.0),
+        ]);
+        let model = RSSIModel::default();
+        let clock = Arc::new(MockClock::new(10_000));
+        let mut engine = PositioningEngine::manual(beacons, model.clone()).with_clock(clock.clone());
+
+        let gateway = ObservationSource::Gateway { gateway: "serial0".to_string() };
+        let rssi_at = |d: f64| model.distance_to_rssi(d).round() as i16;
+        assert!(engine.feed_observation(Observation::rssi(gateway.clone(), "aa:bb:cc:dd:ee:01", rssi_at(707.0), Some(9_700))));
+        assert!(engine.feed_observation(Observation::rssi(gateway.clone(), "B2", rssi_at(707.0), Some(9_900))));
+        assert!(engine.feed_observation(Observation::rssi(gateway.clone(), "B3", rssi_at(707.0), Some(9_900))));
+        assert!(engine.feed_observation(Observation::rssi(gateway.clone(), "B4", rssi_at(707.0), Some(9_900))));
+        assert!(!engine.feed_observation(Observation::rssi(gateway.clone(), "B9", -60, Some(9_900))));
+        assert!(!engine.feed_observation(Observation::range(gateway, "B1", 100.0, Some(9_900))));
+
+        let result = engine.solve().unwrap();
+        assert!((result.x - 500.0).abs() < 1.0 && (result.y - 500.0).abs() < 1.0);
+        assert_eq!(result.timestamp.timestamp_millis(), 10_000);
+        assert_eq!(engine.stats().observations_ignored, 2);
+        let serial = engine.stats().sources.get(&ObservationSource::Gateway { gateway: "serial0".to_string() }).unwrap();
+        assert_eq!((serial.accepted, serial.ignored), (4, 2));
+        assert_eq!(engine.stats().fixes, 1);
+        // 延迟从最早参与求解的观测算起
+        assert_eq!(engine.stats().latency.max(), Some(Duration::from_millis(300)));
+
+        // 没有新观测的历元不记录延迟
+        clock.advance(Duration::from_secs(1));
+        let result = engine.solve().unwrap();
+        assert_eq!(engine.stats().latency.count(), 1);
+
+        // 超出聚合窗口后不再有可用数据，最近结果的置信度随时间衰减
+        clock.advance(Duration::from_secs(10));
+        assert!(engine.solve().is_none());
+        let last = engine.last_known_position().unwrap();
+        assert!((last.confidence - result.confidence / 2.0).abs() < 1e-9);
+        assert_eq!(last.timestamp, result.timestamp);
+
+        engine.reset();
+        assert!(engine.last_known_position().is_none());
+    }
+
+    #[test]
+    fn test_result_cache_reuses_unchanged_epochs() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("B1".to_string(), "b1".to_string(), 0.0, 0.0, 0.0),
+            Beacon::new("B2".to_string(), "b2".to_string(), 1000.0, 0.0, 0.0),
+            Beacon::new("B3".to_string(), "b3".to_string(), 0.0, 1000.0, 0.0),
+        ]);
+        let clock = Arc::new(MockClock::new(10_000));
+        let mut engine = PositioningEngine::manual(beacons, RSSIModel::default())
+            .with_clock(clock.clone())
+            .with_result_cache(2);
+        let feed = |engine: &mut PositioningEngine, rssi: [i16; 3], t: u64| {
+            for (id, r) in ["B1", "B2", "B3"].iter().zip(rssi) {
+                engine.feed_observation(Observation::rssi(ObservationSource::Replay, *id, r, Some(t)));
+            }
+        };
+
+        feed(&mut engine, [-60, -70, -70], 10_000);
+        let first = engine.solve().unwrap();
+        clock.advance(Duration::from_millis(500));
+        let second = engine.solve().unwrap();
+        assert_eq!(engine.stats().cache_hits, 1);
+        assert_eq!((first.x, first.y), (second.x, second.y));
+        assert_eq!(second.timestamp.timestamp_millis(), 10_500);
+
+        // 窗口均值变化超过阈值后重新求解
+        clock.advance(Duration::from_millis(3_000));
+        feed(&mut engine, [-80, -60, -70], 13_500);
+        engine.solve().unwrap();
+        assert_eq!(engine.stats().cache_hits, 1);
+    }
+
+    #[test]
+    fn test_epoch_strategies() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("B1".to_string(), "b1".to_string(), 0.0, 0.0, 0.0),
+            Beacon::new("B2".to_string(), "b2".to_string(), 1000.0, 0.0, 0.0),
+            Beacon::new("B3".to_string(), "b3".to_string(), 0.0, 1000.0, 0.0),
+        ]);
+        let clock = Arc::new(MockClock::new(10_000));
+        let feed = |engine: &mut PositioningEngine, id: &str| {
+            engine.feed_observation(Observation::rssi(ObservationSource::Replay, id, -65, Some(10_000)));
+        };
+
+        let mut engine = PositioningEngine::manual(beacons.clone(), RSSIModel::default())
+            .with_clock(clock.clone())
+            .with_epoch_strategy(EpochStrategy::DistinctBeacons { count: 3 });
+        feed(&mut engine, "B1");
+        feed(&mut engine, "B1");
+        feed(&mut engine, "B2");
+        assert!(engine.poll().is_none());
+        feed(&mut engine, "B3");
+        assert!(engine.poll().is_some());
+        assert!(!engine.epoch_ready());
+
+        let mut engine = PositioningEngine::manual(beacons, RSSIModel::default())
+            .with_clock(clock.clone())
+            .with_epoch_strategy(EpochStrategy::Timer { interval: Duration::from_millis(500) });
+        for id in ["B1", "B2", "B3"] {
+            feed(&mut engine, id);
+        }
+        assert!(engine.poll().is_some());
+        assert!(engine.poll().is_none());
+        clock.advance(Duration::from_millis(500));
+        assert!(engine.poll().is_some());
+        assert_eq!(engine.stats().solves, 2);
+    }
+
+    #[test]
+    fn test_dual_output_tags_raw_and_filtered() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("B1".to_string(), "b1".to_string(), 0.0, 0.0, 0.0),
+            Beacon::new("B2".to_string(), "b2".to_string(), 1000.0, 0.0, 0.0),
+            Beacon::new("B3".to_string(), "b3".to_string(), 0.0, 1000.0, 0.0),
+        ]);
+        let events = EventBus::default();
+        let mut received = events.subscribe();
+        let clock = Arc::new(MockClock::new(10_000));
+        let mut engine = PositioningEngine::manual(beacons, RSSIModel::default())
+            .with_clock(clock)
+            .with_pipeline(PostProcessPipeline::new().with_stage(OutputGate::new(4, 0.0)))
+            .with_events(events)
+            .with_dual_output();
+        for id in ["B1", "B2", "B3"] {
+            engine.feed_observation(Observation::rssi(ObservationSource::Replay, id, -65, Some(10_000)));
+        }
+
+        // 门限要求 4 个信标：原始结果仍然输出，后处理结果被丢弃
+        let output = engine.solve_epoch();
+        assert!(output.raw.is_some());
+        assert!(output.filtered.is_none());
+        assert_eq!(received.try_recv().unwrap().kind(), "raw_fix_computed");
+        assert!(received.try_recv().is_err());
+        assert_eq!(engine.stats().fixes, 0);
+    }
+
+    #[test]
+    fn test_ingest_policies_bound_pending_queue() {
+        let beacons = BeaconSet::from_vec(vec![Beacon::new("B1".to_string(), "b1".to_string(), 0.0, 0.0, 0.0)]);
+        let source = ObservationSource::Replay;
+
+        let mut engine = PositioningEngine::manual(beacons.clone(), RSSIModel::default())
+            .with_ingest_policy(IngestPolicy::DropOldestPerBeacon { max_per_beacon: 3 });
+        for i in 0..10 {
+            engine.feed_observation(Observation::rssi(source.clone(), "B1", -60, Some(i)));
+        }
+        assert_eq!(engine.pending_count(), 3);
+        assert_eq!(engine.stats().observations_dropped, 7);
+        assert_eq!(engine.process_pending(), 3);
+        assert_eq!(engine.pending_count(), 0);
+
+        let mut engine = PositioningEngine::manual(beacons, RSSIModel::default()).with_ingest_policy(IngestPolicy::Coalesce);
+        engine.feed_observation(Observation::rssi(source.clone(), "B1", -60, Some(1)));
+        engine.feed_observation(Observation::rssi(source.clone(), "B1", -70, Some(2)));
+        engine.feed_observation(Observation::rssi(source, "B1", -80, Some(3)));
+        assert_eq!(engine.pending_count(), 1);
+        assert_eq!(engine.stats().observations_coalesced, 2);
+        assert_eq!(engine.pending["B1"][0].0.rssi, -70);
+        assert_eq!(engine.pending["B1"][0].0.timestamp_ms, Some(3));
+    }
+
+    #[test]
+    fn test_observation_metadata_reaches_result() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("B1".to_string(), "b1".to_string(), 0.0, 0.0, 0.0),
+            Beacon::new("B2".to_string(), "b2".to_string(), 1000.0, 0.0, 0.0),
+            Beacon::new("B3".to_string(), "b3".to_string(), 0.0, 1000.0, 0.0),
+        ]);
+        let mut engine = PositioningEngine::manual(beacons, RSSIModel::default());
+        let source = ObservationSource::Replay;
+        engine.feed_observation(Observation::rssi(source.clone(), "B1", -60, None).with_metadata("order_id", "SO-1"));
+        engine.feed_observation(Observation::rssi(source.clone(), "B2", -65, None).with_metadata("order_id", "SO-2"));
+        engine.feed_observation(Observation::rssi(source.clone(), "B3", -65, None).with_metadata("patient_id", 42));
+        // 未知信标的观测被忽略，附带的数据也不会合并
+        engine.feed_observation(Observation::rssi(source, "X9", -50, None).with_metadata("order_id", "ignored"));
+
+        let result = engine.solve().unwrap();
+        assert_eq!(result.metadata["order_id"], "SO-2");
+        assert_eq!(result.metadata["patient_id"], 42);
+
+        engine.reset();
+        assert!(engine.metadata.is_empty());
+    }
+
+    #[test]
+    fn test_metadata_expires_with_window() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("B1".to_string(), "b1".to_string(), 0.0, 0.0, 0.0),
+            Beacon::new("B2".to_string(), "b2".to_string(), 1000.0, 0.0, 0.0),
+            Beacon::new("B3".to_string(), "b3".to_string(), 0.0, 1000.0, 0.0),
+        ]);
+        let clock = Arc::new(MockClock::new(10_000));
+        let mut engine = PositioningEngine::manual(beacons, RSSIModel::default()).with_clock(clock.clone());
+        let source = ObservationSource::Replay;
+        engine.feed_observation(Observation::rssi(source.clone(), "B1", -60, None).with_metadata("order_id", "SO-1"));
+        engine.feed_observation(Observation::rssi(source.clone(), "B2", -65, None));
+        engine.feed_observation(Observation::rssi(source.clone(), "B3", -65, None));
+        assert_eq!(engine.solve().unwrap().metadata["order_id"], "SO-1");
+
+        // 窗口（2 秒）过后只剩新观测，旧订单号不再附加
+        clock.advance(Duration::from_secs(5));
+        for id in ["B1", "B2", "B3"] {
+            engine.feed_observation(Observation::rssi(source.clone(), id, -62, None));
+        }
+        assert!(engine.solve().unwrap().metadata.is_empty());
+        assert!(engine.metadata.is_empty());
+    }
+
+    #[test]
+    fn test_reorder_buffer_in_engine() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("B1".to_string(), "b1".to_string(), 0.0, 0.0, 0.0),
+            Beacon::new("B2".to_string(), "b2".to_string(), 1000.0, 0.0, 0.0),
+            Beacon::new("B3".to_string(), "b3".to_string(), 0.0, 1000.0, 0.0),
+        ]);
+        let clock = Arc::new(MockClock::new(10_000));
+        let mut engine = PositioningEngine::manual(beacons, RSSIModel::default())
+            .with_clock(clock.clone())
+            .with_reorder_buffer(Duration::from_millis(500));
+        let source = ObservationSource::Replay;
+        assert!(engine.feed_observation(Observation::rssi(source.clone(), "B1", -60, Some(9_900))));
+        assert!(engine.feed_observation(Observation::rssi(source.clone(), "B2", -65, Some(9_700))));
+        assert!(engine.feed_observation(Observation::rssi(source.clone(), "B3", -65, Some(9_800))));
+
+        // 等待期内尚未进入窗口
+        assert!(engine.solve().is_none());
+        clock.advance(Duration::from_millis(500));
+        assert!(engine.solve().is_some());
+
+        // 早于已释放时间点、或落后本地时钟数分钟的观测被丢弃
+        assert!(!engine.feed_observation(Observation::rssi(source.clone(), "B1", -50, Some(9_850))));
+        assert!(!engine.feed_observation(Observation::rssi(source, "B1", -50, Some(0))));
+        assert_eq!(engine.stats().observations_late, 2);
+    }
+
+    #[test]
+    fn test_output_gate_counts_rejections() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("B1".to_string(), "b1".to_string(), 0.0, 0.0, 0.0),
+            Beacon::new("B2".to_string(), "b2".to_string(), 1000.0, 0.0, 0.0),
+            Beacon::new("B3".to_string(), "b3".to_string(), 0.0, 1000.0, 0.0),
+            Beacon::new("B4".to_string(), "b4".to_string(), 1000.0, 1000.0, 0.0),
+        ]);
+        let events = EventBus::default();
+        let mut received = events.subscribe();
+        let mut engine = PositioningEngine::manual(beacons, RSSIModel::default())
+            .with_clock(Arc::new(MockClock::new(10_000)))
+            .with_output_gate(4, 0.0)
+            .with_events(events);
+        for id in ["B1", "B2", "B3"] {
+            engine.feed_observation(Observation::rssi(ObservationSource::Replay, id, -65, Some(10_000)));
+        }
+        assert!(engine.solve().is_none());
+        assert_eq!(engine.stats().gate.rejected_too_few_beacons, 1);
+        assert_eq!(received.try_recv().unwrap().kind(), "fix_rejected");
+
+        engine.feed_observation(Observation::rssi(ObservationSource::Replay, "B4", -65, Some(10_000)));
+        assert!(engine.solve().is_some());
+        assert_eq!(engine.stats().gate.accepted, 1);
+        assert_eq!(engine.stats().gate.rejected(), 1);
+        assert_eq!(engine.stats().fixes, 1);
+    }
+
+    #[test]
+    fn test_adaptive_rate_slows_stationary_epochs() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("B1".to_string(), "b1".to_string(), 0.0, 0.0, 0.0),
+            Beacon::new("B2".to_string(), "b2".to_string(), 1000.0, 0.0, 0.0),
+            Beacon::new("B3".to_string(), "b3".to_string(), 0.0, 1000.0, 0.0),
+        ]);
+        let clock = Arc::new(MockClock::new(10_000));
+        let rate = AdaptiveRate::new(30.0, Duration::from_secs(5), Duration::from_secs(1), Duration::from_secs(30));
+        let mut engine = PositioningEngine::manual(beacons, RSSIModel::default())
+            .with_clock(clock.clone())
+            .with_adaptive_rate(rate);
+        assert_eq!(engine.epoch_strategy(), EpochStrategy::Timer { interval: Duration::from_secs(1) });
+
+        // 每秒输入一次相同的观测并轮询 60 秒
+        for _ in 0..60 {
+            let now = clock.now_ms();
+            for id in ["B1", "B2", "B3"] {
+                engine.feed_observation(Observation::rssi(ObservationSource::Replay, id, -65, Some(now)));
+            }
+            engine.poll();
+            clock.advance(Duration::from_secs(1));
+        }
+        assert_eq!(engine.epoch_strategy(), EpochStrategy::Timer { interval: Duration::from_secs(30) });
+        // 前 5 秒按运动间隔求解，之后每 30 秒一次
+        assert!((6..=9).contains(&engine.stats().solves), "solves = {}", engine.stats().solves);
+
+        engine.reset();
+        assert_eq!(engine.epoch_strategy(), EpochStrategy::Timer { interval: Duration::from_secs(1) });
+    }
+
+    #[test]
+    fn test_adaptive_noise_smooths_jittery_beacon() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("B1".to_string(), "b1".to_string(), 0.0, 0.0, 0.0),
+            Beacon::new("B2".to_string(), "b2".to_string(), 1000.0, 0.0, 0.0),
+            Beacon::new("B3".to_string(), "b3".to_string(), 0.0, 1000.0, 0.0),
+            Beacon::new("B4".to_string(), "b4".to_string(), 1000.0, 1000.0, 0.0),
+        ]);
+        let model = RSSIModel::default();
+        let rssi_at = |x: f64, y: f64| model.distance_to_rssi((300.0 - x).hypot(400.0 - y)).round() as i16;
+        // 标签静止在 (300, 400)，B2 的 RSSI 以 ±3 dB 跳动，其余信标稳定；统计各历元残差误差的波动范围
+        let error_spread = |engine: PositioningEngine, clock: Arc<MockClock>| {
+            let mut engine = engine.with_clock(clock.clone()).with_rssi_aggregation(RssiAggregation::Latest);
+            let mut errors = Vec::new();
+            for i in 0..20 {
+                let now = clock.now_ms();
+                let jitter = rssi_at(1000.0, 0.0) + if i % 2 == 0 { 3 } else { -3 };
+                let readings = [("B1", rssi_at(0.0, 0.0)), ("B2", jitter), ("B3", rssi_at(0.0, 1000.0)), ("B4", rssi_at(1000.0, 1000.0))];
+                for (id, rssi) in readings {
+                    engine.feed_observation(Observation::rssi(ObservationSource::Replay, id, rssi, Some(now)));
+                }
+                errors.push(engine.solve().unwrap().error);
+                clock.advance(Duration::from_millis(200));
+            }
+            let tail = &errors[10..];
+            tail.iter().cloned().fold(f64::MIN, f64::max) - tail.iter().cloned().fold(f64::MAX, f64::min)
+        };
+
+        let raw = error_spread(PositioningEngine::manual(beacons.clone(), model.clone()), Arc::new(MockClock::new(10_000)));
+        let smoothed = error_spread(
+            PositioningEngine::manual(beacons, model.clone())
+                .with_adaptive_noise(AdaptiveNoise::new(Duration::from_secs(2), 1.0), 0.01),
+            Arc::new(MockClock::new(10_000)),
+        );
+        assert!(smoothed < raw / 2.0, "raw = {raw}, smoothed = {smoothed}");
+    }
+
+    #[test]
+    fn test_pause_resume_and_update_interval() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("B1".to_string(), "b1".to_string(), 0.0, 0.0, 0.0),
+            Beacon::new("B2".to_string(), "b2".to_string(), 1000.0, 0.0, 0.0),
+            Beacon::new("B3".to_string(), "b3".to_string(), 0.0, 1000.0, 0.0),
+        ]);
+        let clock = Arc::new(MockClock::new(10_000));
+        let mut engine = PositioningEngine::manual(beacons, RSSIModel::default()).with_clock(clock.clone());
+        let feed = |engine: &mut PositioningEngine| {
+            for id in ["B1", "B2", "B3"] {
+                assert!(engine.feed_observation(Observation::rssi(ObservationSource::Replay, id, -65, Some(clock.now_ms()))));
+            }
+        };
+        feed(&mut engine);
+        assert!(engine.poll().is_some());
+
+        // 暂停期间观测照常记录但不求解
+        engine.pause();
+        clock.advance(Duration::from_secs(2));
+        feed(&mut engine);
+        assert!(engine.is_paused());
+        assert!(engine.poll().is_none());
+        assert_eq!(engine.stats().solves, 1);
+
+        engine.resume();
+        assert!(engine.poll().is_some());
+        assert_eq!(engine.stats().solves, 2);
+
+        engine.set_update_interval(Duration::from_secs(5));
+        assert_eq!(engine.epoch_strategy(), EpochStrategy::Timer { interval: Duration::from_secs(5) });
+        clock.advance(Duration::from_secs(1));
+        feed(&mut engine);
+        assert!(engine.poll().is_none());
+        clock.advance(Duration::from_secs(4));
+        feed(&mut engine);
+        assert!(engine.poll().is_some());
+    }
+}
@@ -0,0 +1,280 @@
+/// 定位精度评估工具
+///
+/// 提供与具体算法无关的理论分析，用于判断定位误差来自算法还是物理限制；
+/// 以及基于已知轨迹的反算工具，用于排查单个信标的模型或安装位置问题
+
+use crate::algorithms::{
+    Beacon, BeaconSet, DistanceEstimator, LocationAlgorithm, Observation, ObservationKind, RSSIModel,
+};
+use std::collections::HashMap;
+use std::f64::consts::LN_10;
+
+/// 克拉美-罗下界（CRLB）
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CrlbBound {
+    /// x 方向最小标准差
+    pub std_x: f64,
+    /// y 方向最小标准差
+    pub std_y: f64,
+    /// 平面位置最小均方根误差 sqrt(var_x + var_y)
+    pub rmse: f64,
+}
+
+/// 计算某点处基于 RSSI 测距的平面定位 CRLB
+///
+/// 在对数正态阴影模型 RSSI = A + B·log10(d) + N(0, σ²) 下，
+/// 第 i 个信标对位置的 Fisher 信息为 (B / (σ·ln10))² · u_i·u_iᵀ / d_i²，
+/// 其中 u_i 为信标指向该点的水平单位投影。结果单位与坐标一致。
+/// 下界由对数距离模型的 B 与 σ 决定，因此只接受 `RSSIModel`
+///
+/// # 返回
+/// - 理论下界，或 None 如果模型 σ 未知 (0) 或几何退化（如信标共线）
+pub fn crlb(beacons: &[Beacon], model: &RSSIModel, point: (f64, f64, f64)) -> Option<CrlbBound> {
+    if model.sigma <= 0.0 {
+        return None;
+    }
+
+    let scale = (model.b / (model.sigma * LN_10)).powi(2);
+    let mut fisher = [[0.0; 2]; 2];
+    for beacon in beacons {
+        let dx = point.0 - beacon.x;
+        let dy = point.1 - beacon.y;
+        let dz = point.2 - beacon.z;
+        let d2 = dx * dx + dy * dy + dz * dz;
+        if d2 < 1e-12 {
+            continue;
+        }
+        // ∂ln(d)/∂x = dx / d²
+        let gx = dx / d2;
+        let gy = dy / d2;
+        fisher[0][0] += scale * gx * gx;
+        fisher[0][1] += scale * gx * gy;
+        fisher[1][1] += scale * gy * gy;
+    }
+    fisher[1][0] = fisher[0][1];
+
+    let det = fisher[0][0] * fisher[1][1] - fisher[0][1] * fisher[1][0];
+    if det.abs() < 1e-18 {
+        return None;
+    }
+
+    let var_x = fisher[1][1] / det;
+    let var_y = fisher[0][0] / det;
+    Some(CrlbBound {
+        std_x: var_x.sqrt(),
+        std_y: var_y.sqrt(),
+        rmse: (var_x + var_y).sqrt(),
+    })
+}
+
+/// 单个信标的残差诊断
+#[derive(Clone, Debug)]
+pub struct BeaconResidualReport {
+    /// 信标 ID
+    pub beacon_id: String,
+    /// 参与统计的观测数
+    pub sample_count: usize,
+    /// 平均残差 (dB)，观测 RSSI - 模型预测 RSSI
+    pub mean_residual: f64,
+    /// 残差标准差 (dB)
+    pub std_residual: f64,
+    /// 残差直方图 [(区间下界, 计数)]，按区间下界升序
+    pub histogram: Vec<(f64, usize)>,
+    /// 建议的模型截距修正量 (dB)，即 A' = A + 修正量
+    pub suggested_a_offset: f64,
+    /// 按观测反推的信标水平位置，轨迹几何退化时为 None
+    pub suggested_position: Option<(f64, f64)>,
+    /// 建议位置与配置位置的水平偏差
+    pub position_shift: Option<f64>,
+}
+
+/// 轨迹上的一次观测：(真实位置, 观测 RSSI)
+type TrackSample = ((f64, f64, f64), f64);
+
+/// 在已知轨迹上按时间线性插值位置
+///
+/// `track` 为按时间排序的 (时间戳毫秒, x, y, z)，超出轨迹时间范围时返回 None
+fn interpolate_track(track: &[(u64, f64, f64, f64)], timestamp_ms: u64) -> Option<(f64, f64, f64)> {
+    let idx = track.partition_point(|p| p.0 < timestamp_ms);
+    let after = track.get(idx)?;
+    if after.0 == timestamp_ms {
+        return Some((after.1, after.2, after.3));
+    }
+    let before = track.get(idx.checked_sub(1)?)?;
+    let t = (timestamp_ms - before.0) as f64 / (after.0 - before.0) as f64;
+    Some((
+        before.1 + (after.1 - before.1) * t,
+        before.2 + (after.2 - before.2) * t,
+        before.3 + (after.3 - before.3) * t,
+    ))
+}
+
+/// 反算模式：根据已知轨迹计算每个信标的 RSSI 残差并给出修正建议
+///
+/// 对每条带时间戳的 RSSI 观测，按轨迹插值出真实位置，
+/// 用模型预测该距离下的 RSSI 并与观测值比较。
+/// 平均残差即截距 A 的修正建议；同时用各观测反解的距离对信标做多边定位，
+/// 反推其实际安装位置，便于定位单个摆放错误的信标
+///
+/// # 参数
+/// - `track`: 已知轨迹 (时间戳毫秒, x, y, z)，按时间排序
+/// - `observations`: 观测列表，只使用带时间戳的 RSSI 观测
+/// - `beacons`: 信标配置
+/// - `model`: 测距模型，需要实现 [`DistanceEstimator::expected_rssi`]
+/// - `bin_width`: 直方图区间宽度 (dB)
+///
+/// # 返回
+/// - 每个有观测的已配置信标一份报告，按平均残差绝对值降序（最可疑的在前）
+pub fn beacon_residuals(
+    track: &[(u64, f64, f64, f64)],
+    observations: &[Observation],
+    beacons: &BeaconSet,
+    model: &(impl DistanceEstimator + ?Sized),
+    bin_width: f64,
+) -> Vec<BeaconResidualReport> {
+    let bin_width = if bin_width > 0.0 { bin_width } else { 1.0 };
+
+    // 信标 ID -> [(真实位置, 观测 RSSI)]
+    let mut grouped: HashMap<&str, Vec<TrackSample>> = HashMap::new();
+    for obs in observations.iter().filter(|o| o.kind == ObservationKind::Rssi) {
+        let (Some(ts), Some(_)) = (obs.timestamp_ms, beacons.get(&obs.target)) else {
+            continue;
+        };
+        if let Some(position) = interpolate_track(track, ts) {
+            grouped.entry(obs.target.as_str()).or_default().push((position, obs.value));
+        }
+    }
+
+    let mut reports: Vec<BeaconResidualReport> = grouped
+        .into_iter()
+        .filter_map(|(id, samples)| {
+            let beacon = beacons.get(id)?;
+            let residuals: Vec<f64> = samples
+                .iter()
+                .map(|&((x, y, z), rssi)| {
+                    let d = ((x - beacon.x).powi(2) + (y - beacon.y).powi(2) + (z - beacon.z).powi(2)).sqrt();
+                    model.expected_rssi(id, d).map_or(f64::NAN, |expected| rssi - expected)
+                })
+                .filter(|r| r.is_finite())
+                .collect();
+            if residuals.is_empty() {
+                return None;
+            }
+
+            let n = residuals.len() as f64;
+            let mean = residuals.iter().sum::<f64>() / n;
+            let variance = residuals.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n;
+
+            let mut bins: HashMap<i64, usize> = HashMap::new();
+            for r in &residuals {
+                *bins.entry((r / bin_width).floor() as i64).or_insert(0) += 1;
+            }
+            let mut histogram: Vec<(f64, usize)> =
+                bins.into_iter().map(|(b, c)| (b as f64 * bin_width, c)).collect();
+            histogram.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            // 反解距离并投影到水平面，按轨迹点多边定位信标
+            let ranges: Vec<(f64, f64, f64)> = samples
+                .iter()
+                .filter_map(|&((x, y, z), rssi)| {
+                    let d = model.distance(id, rssi.round() as i16)?;
+                    let dz = z - beacon.z;
+                    Some((x, y, (d * d - dz * dz).max(0.0).sqrt()))
+                })
+                .collect();
+            let suggested_position = LocationAlgorithm::_least_squares_xy(&ranges);
+            let position_shift = suggested_position
+                .map(|(x, y)| ((x - beacon.x).powi(2) + (y - beacon.y).powi(2)).sqrt());
+
+            Some(BeaconResidualReport {
+                beacon_id: id.to_string(),
+                sample_count: residuals.len(),
+                mean_residual: mean,
+                std_residual: variance.sqrt(),
+                histogram,
+                suggested_a_offset: mean,
+                suggested_position,
+                position_shift,
+            })
+        })
+        .collect();
+
+    reports.sort_by(|a, b| b.mean_residual.abs().total_cmp(&a.mean_residual.abs()));
+    reports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::DistanceUnit;
+
+    fn square_layout() -> Vec<Beacon> {
+        [(0.0, 0.0), (1000.0, 0.0), (0.0, 1000.0), (1000.0, 1000.0)]
+            .iter()
+            .enumerate()
+            .map(|(i, (x, y))| Beacon::new(format!("B{}", i), format!("B{}", i), *x, *y, 0.0))
+            .collect()
+    }
+
+    #[test]
+    fn test_crlb_center_is_symmetric_and_scales_with_sigma() {
+        let beacons = square_layout();
+        let model = RSSIModel::log_normal_shadow(-50.0, 2.0, DistanceUnit::Centimeter).with_sigma(4.0);
+        let bound = crlb(&beacons, &model, (500.0, 500.0, 0.0)).unwrap();
+        assert!((bound.std_x - bound.std_y).abs() < 1e-9);
+
+        let noisier = model.clone().with_sigma(8.0);
+        let bound2 = crlb(&beacons, &noisier, (500.0, 500.0, 0.0)).unwrap();
+        assert!((bound2.rmse / bound.rmse - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_crlb_requires_sigma_and_geometry() {
+        let model = RSSIModel::log_normal_shadow(-50.0, 2.0, DistanceUnit::Centimeter);
+        assert!(crlb(&square_layout(), &model, (500.0, 500.0, 0.0)).is_none());
+
+        let collinear: Vec<Beacon> = (0..3)
+            .map(|i| Beacon::new(format!("B{}", i), String::new(), i as f64 * 100.0, 0.0, 0.0))
+            .collect();
+        let model = model.with_sigma(4.0);
+        assert!(crlb(&collinear, &model, (500.0, 0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_beacon_residuals_finds_misplaced_beacon() {
+        let model = RSSIModel::log_normal_shadow(-50.0, 2.0, DistanceUnit::Centimeter);
+        let configured = BeaconSet::from_vec(square_layout());
+        // B3 实际安装在 (700, 600)，而非配置中的 (1000, 1000)
+        let mut actual = configured.clone();
+        actual.get_mut("B3").unwrap().x = 700.0;
+        actual.get_mut("B3").unwrap().y = 600.0;
+
+        let track: Vec<(u64, f64, f64, f64)> = (0..=20)
+            .map(|i| {
+                let t = i as f64 / 20.0;
+                (i * 1000, 200.0 + 600.0 * t, 200.0 + 400.0 * (t * 6.0).sin(), 0.0)
+            })
+            .collect();
+        let mut observations = Vec::new();
+        for &(ts, x, y, z) in &track {
+            for (id, beacon) in actual.iter() {
+                let d = ((x - beacon.x).powi(2) + (y - beacon.y).powi(2) + (z - beacon.z).powi(2)).sqrt();
+                observations.push(Observation::new(
+                    crate::algorithms::ObservationSource::Replay,
+                    id.clone(),
+                    ObservationKind::Rssi,
+                    model.distance_to_rssi(d),
+                    Some(ts),
+                ));
+            }
+        }
+
+        let reports = beacon_residuals(&track, &observations, &configured, &model, 2.0);
+        assert_eq!(reports.len(), 4);
+        assert_eq!(reports[0].beacon_id, "B3");
+        assert_eq!(reports[0].sample_count, 21);
+        let (x, y) = reports[0].suggested_position.unwrap();
+        assert!((x - 700.0).abs() < 30.0 && (y - 600.0).abs() < 30.0);
+        assert!(reports[3].mean_residual.abs() < 0.5);
+    }
+}
@@ -0,0 +1,191 @@
+/// 统一事件通道
+///
+/// 扫描、定位、区域等各环节的事件统一为 `BlunavEvent`，
+/// 通过 `EventBus`（tokio broadcast）分发，一个订阅者即可驱动日志、界面和告警
+
+use crate::algorithms::{Alert, LocationResult, RejectReason};
+use std::fmt;
+use tokio::sync::broadcast;
+
+/// 默认通道容量
+pub const DEFAULT_EVENT_CAPACITY: usize = 256;
+
+/// 流水线事件
+#[derive(Clone, Debug)]
+pub enum BlunavEvent {
+    /// 开始扫描
+    ScanStarted,
+    /// 发现设备
+    DeviceDiscovered {
+        /// 设备标识
+        device_id: String,
+        /// 广播名称
+        name: Option<String>,
+        /// 首次观测到的 RSSI
+        rssi: i16,
+    },
+    /// 信标超时未收到
+    BeaconLost {
+        /// 信标 ID
+        beacon_id: String,
+    },
+    /// 得到定位结果
+    FixComputed(LocationResult),
+    /// 求解器的原始结果（未经后处理，仅在双路输出模式下发送）
+    RawFixComputed(LocationResult),
+    /// 定位结果被拒绝
+    FixRejected {
+        /// 被拒绝的结果
+        result: LocationResult,
+        /// 拒绝原因
+        reason: RejectReason,
+    },
+    /// 设备进入区域
+    ZoneEntered {
+        /// 区域名称
+        zone: String,
+        /// 设备 ID（规范化形式）
+        device_id: String,
+    },
+    /// 设备离开区域（包括设备被移除）
+    ZoneLeft {
+        /// 区域名称
+        zone: String,
+        /// 设备 ID（规范化形式）
+        device_id: String,
+    },
+    /// 位置稳定（在一定范围内停留足够久）
+    PositionSettled(LocationResult),
+    /// 怀疑两个信标被对调或标注错误
+    BeaconSwapSuspected {
+        /// 信标 A
+        beacon_a: String,
+        /// 信标 B
+        beacon_b: String,
+    },
+    /// 信标信号长期漂移，建议重新标定
+    BeaconDrifting {
+        /// 信标 ID
+        beacon_id: String,
+        /// 漂移速率 (dB/天)
+        drift_db_per_day: f64,
+    },
+    /// 告警规则触发
+    Alert(Alert),
+    /// 错误
+    Error(String),
+}
+
+impl BlunavEvent {
+    /// 事件类型名称（用于日志和过滤）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BlunavEvent::ScanStarted => "scan_started",
+            BlunavEvent::DeviceDiscovered { .. } => "device_discovered",
+            BlunavEvent::BeaconLost { .. } => "beacon_lost",
+            BlunavEvent::FixComputed(_) => "fix_computed",
+            BlunavEvent::RawFixComputed(_) => "raw_fix_computed",
+            BlunavEvent::FixRejected { .. } => "fix_rejected",
+            BlunavEvent::ZoneEntered { .. } => "zone_entered",
+            BlunavEvent::ZoneLeft { .. } => "zone_left",
+            BlunavEvent::PositionSettled(_) => "position_settled",
+            BlunavEvent::BeaconSwapSuspected { .. } => "beacon_swap_suspected",
+            BlunavEvent::BeaconDrifting { .. } => "beacon_drifting",
+            BlunavEvent::Alert(_) => "alert",
+            BlunavEvent::Error(_) => "error",
+        }
+    }
+}
+
+impl fmt::Display for BlunavEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlunavEvent::ScanStarted => write!(f, "开始扫描"),
+            BlunavEvent::DeviceDiscovered { device_id, name, rssi } => write!(
+                f,
+                "发现设备 {} ({}) RSSI {} dBm",
+                device_id,
+                name.as_deref().unwrap_or("-"),
+                rssi
+            ),
+            BlunavEvent::BeaconLost { beacon_id } => write!(f, "信标丢失 {}", beacon_id),
+            BlunavEvent::FixComputed(result) => write!(f, "定位 {}", result),
+            BlunavEvent::RawFixComputed(result) => write!(f, "原始定位 {}", result),
+            BlunavEvent::FixRejected { reason, .. } => write!(f, "定位被拒绝: {}", reason),
+            BlunavEvent::ZoneEntered { zone, device_id } => write!(f, "{} 进入区域 {}", device_id, zone),
+            BlunavEvent::ZoneLeft { zone, device_id } => write!(f, "{} 离开区域 {}", device_id, zone),
+            BlunavEvent::PositionSettled(result) => write!(f, "位置稳定 {}", result),
+            BlunavEvent::BeaconSwapSuspected { beacon_a, beacon_b } => {
+                write!(f, "信标 {} 与 {} 可能被对调", beacon_a, beacon_b)
+            }
+            BlunavEvent::BeaconDrifting { beacon_id, drift_db_per_day } => {
+                write!(f, "信标 {} 信号漂移 {:.2} dB/天，建议重新标定", beacon_id, drift_db_per_day)
+            }
+            BlunavEvent::Alert(alert) => write!(f, "告警 {}", alert),
+            BlunavEvent::Error(message) => write!(f, "错误: {}", message),
+        }
+    }
+}
+
+/// 事件总线 - 多生产者、多订阅者
+///
+/// 克隆得到的总线共享同一通道。订阅者处理过慢时会丢失最旧的事件
+/// （`RecvError::Lagged`），不会阻塞发送方
+#[derive(Clone, Debug)]
+pub struct EventBus {
+    sender: broadcast::Sender<BlunavEvent>,
+}
+
+impl EventBus {
+    /// 创建事件总线
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        EventBus { sender }
+    }
+
+    /// 订阅事件
+    pub fn subscribe(&self) -> broadcast::Receiver<BlunavEvent> {
+        self.sender.subscribe()
+    }
+
+    /// 发送事件，返回接收到事件的订阅者数量（无订阅者时为 0）
+    pub fn emit(&self, event: BlunavEvent) -> usize {
+        self.sender.send(event).unwrap_or(0)
+    }
+
+    /// 当前订阅者数量
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_event_bus_broadcasts_to_all_subscribers() {
+        let bus = EventBus::default();
+        assert_eq!(bus.emit(BlunavEvent::ScanStarted), 0);
+
+        let mut log = bus.subscribe();
+        let mut ui = bus.clone().subscribe();
+        assert_eq!(bus.subscriber_count(), 2);
+
+        bus.emit(BlunavEvent::BeaconLost {
+            beacon_id: "B1".to_string(),
+        });
+        let fix = LocationResult::new(1.0, 2.0, 0.0, 0.9, 5.0, "m".to_string(), 3);
+        assert_eq!(bus.emit(BlunavEvent::FixComputed(fix)), 2);
+
+        assert_eq!(log.recv().await.unwrap().kind(), "beacon_lost");
+        assert_eq!(log.recv().await.unwrap().kind(), "fix_computed");
+        assert_eq!(ui.recv().await.unwrap().to_string(), "信标丢失 B1");
+    }
+}
@@ -0,0 +1,438 @@
+/// 指纹定位数据库
+///
+/// 保存参考点处各信标的平均 RSSI，并通过 k 近邻（kNN）匹配实时信号进行定位。
+/// 参考点还可保存现场 Wi-Fi 接入点（按 BSSID）的 RSSI 和地磁场强度，与蓝牙信号一起匹配。
+/// 运行期间可用高置信度的测距定位结果在线更新附近参考点，使数据库跟上环境变化
+
+use crate::algorithms::{LocationResult, SignalReadings};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 未收到某信标时用于匹配的 RSSI 替代值 (dBm)
+pub const MISSING_RSSI: f64 = -100.0;
+
+/// 指纹参考点
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReferencePoint {
+    /// 参考点标签（如房间名 + 编号）
+    pub label: String,
+    /// X 坐标
+    pub x: f64,
+    /// Y 坐标
+    pub y: f64,
+    /// Z 坐标
+    pub z: f64,
+    /// beacon_id -> 平均 RSSI
+    pub rssi: HashMap<String, f64>,
+    /// beacon_id -> 采集样本数
+    #[serde(default)]
+    pub sample_counts: HashMap<String, usize>,
+    /// BSSID -> Wi-Fi 平均 RSSI
+    #[serde(default)]
+    pub wifi_rssi: HashMap<String, f64>,
+    /// 地磁场强度 (μT)，钢结构建筑内随位置变化明显
+    #[serde(default)]
+    pub magnetic: Option<f64>,
+}
+
+impl ReferencePoint {
+    /// 创建没有指纹的参考点
+    pub fn new(label: impl Into<String>, x: f64, y: f64, z: f64) -> Self {
+        ReferencePoint {
+            label: label.into(),
+            x,
+            y,
+            z,
+            rssi: HashMap::new(),
+            sample_counts: HashMap::new(),
+            wifi_rssi: HashMap::new(),
+            magnetic: None,
+        }
+    }
+
+    /// 从原始样本创建参考点（每个信标取均值）
+    pub fn from_samples(
+        label: impl Into<String>,
+        x: f64,
+        y: f64,
+        z: f64,
+        samples: &HashMap<String, Vec<i16>>,
+    ) -> Self {
+        let mut point = ReferencePoint::new(label, x, y, z);
+        for (beacon_id, values) in samples.iter().filter(|(_, v)| !v.is_empty()) {
+            let mean = values.iter().map(|r| *r as f64).sum::<f64>() / values.len() as f64;
+            point.rssi.insert(beacon_id.clone(), mean);
+            point.sample_counts.insert(beacon_id.clone(), values.len());
+        }
+        point
+    }
+
+    /// 添加 Wi-Fi 接入点的平均 RSSI（构建器风格）
+    pub fn with_wifi(mut self, bssid: impl Into<String>, rssi: f64) -> Self {
+        self.wifi_rssi.insert(bssid.into(), rssi);
+        self
+    }
+
+    /// 设置地磁场强度 (μT)（构建器风格）
+    pub fn with_magnetic(mut self, magnitude_ut: f64) -> Self {
+        self.magnetic = Some(magnitude_ut);
+        self
+    }
+
+    /// 与实时信号之间的信号空间欧几里得距离
+    pub fn signal_distance(&self, signals: &SignalReadings) -> f64 {
+        Self::squared_distance(&self.rssi, signals).sqrt()
+    }
+
+    /// 同时考虑蓝牙与 Wi-Fi 的信号空间距离
+    ///
+    /// sqrt(d_ble² + wifi_weight · d_wifi²)；Wi-Fi 读数为空时与 `signal_distance` 相同
+    pub fn fused_signal_distance(&self, ble: &SignalReadings, wifi: &SignalReadings, wifi_weight: f64) -> f64 {
+        self.query_distance(&FingerprintQuery::new(ble.clone()).with_wifi(wifi.clone(), wifi_weight))
+    }
+
+    /// 按查询计算的综合距离
+    ///
+    /// sqrt(d_ble² + wifi_weight · d_wifi² + magnetic_weight · (B - B_ref)²)，
+    /// 查询或参考点缺少某类数据时该项不参与
+    pub fn query_distance(&self, query: &FingerprintQuery) -> f64 {
+        let mut sum = Self::squared_distance(&self.rssi, &query.ble);
+        if query.wifi.count() > 0 {
+            sum += query.wifi_weight.max(0.0) * Self::squared_distance(&self.wifi_rssi, &query.wifi);
+        }
+        if let (Some(observed), Some(reference)) = (query.magnetic, self.magnetic) {
+            sum += query.magnetic_weight.max(0.0) * (observed - reference).powi(2);
+        }
+        sum.sqrt()
+    }
+
+    fn squared_distance(fingerprint: &HashMap<String, f64>, signals: &SignalReadings) -> f64 {
+        let mut sum = 0.0;
+        for (id, rssi) in fingerprint {
+            let observed = signals.get(id).map(|r| r as f64).unwrap_or(MISSING_RSSI);
+            sum += (observed - rssi).powi(2);
+        }
+        for (id, rssi) in signals.all() {
+            if !fingerprint.contains_key(id) {
+                sum += (*rssi as f64 - MISSING_RSSI).powi(2);
+            }
+        }
+        sum
+    }
+}
+
+/// 指纹匹配查询 - 蓝牙读数及可选的 Wi-Fi、地磁数据
+#[derive(Clone, Debug, Default)]
+pub struct FingerprintQuery {
+    /// 蓝牙信标读数
+    pub ble: SignalReadings,
+    /// Wi-Fi 读数（BSSID -> RSSI）
+    pub wifi: SignalReadings,
+    /// Wi-Fi 距离权重
+    pub wifi_weight: f64,
+    /// 实测地磁场强度 (μT)
+    pub magnetic: Option<f64>,
+    /// 地磁差值权重（每 μT² 相当于多少 dB²）
+    pub magnetic_weight: f64,
+}
+
+impl FingerprintQuery {
+    /// 只有蓝牙读数的查询
+    pub fn new(ble: SignalReadings) -> Self {
+        FingerprintQuery {
+            ble,
+            ..Default::default()
+        }
+    }
+
+    /// 加入 Wi-Fi 读数
+    pub fn with_wifi(mut self, wifi: SignalReadings, weight: f64) -> Self {
+        self.wifi = wifi;
+        self.wifi_weight = weight;
+        self
+    }
+
+    /// 加入地磁场强度
+    ///
+    /// `weight` 把 μT 差值换算到 RSSI 空间，如 4.0 表示 1 μT 的差异相当于 2 dB
+    pub fn with_magnetic(mut self, magnitude_ut: f64, weight: f64) -> Self {
+        self.magnetic = Some(magnitude_ut);
+        self.magnetic_weight = weight;
+        self
+    }
+
+    /// 参与匹配的测量数
+    fn measurement_count(&self) -> usize {
+        self.ble.count() + self.wifi.count() + usize::from(self.magnetic.is_some())
+    }
+}
+
+/// 在线更新参数
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OnlineUpdate {
+    /// 只有置信度不低于该值的定位结果才用于更新
+    pub min_confidence: f64,
+    /// 更新半径（坐标单位），只更新此范围内的参考点
+    pub radius: f64,
+    /// 学习率：位于定位结果处的参考点按该比例向实测值靠拢，随距离线性减小到 0
+    pub learning_rate: f64,
+}
+
+impl Default for OnlineUpdate {
+    fn default() -> Self {
+        OnlineUpdate {
+            min_confidence: 0.8,
+            radius: 200.0,
+            learning_rate: 0.1,
+        }
+    }
+}
+
+/// 指纹数据库
+#[derive(Clone, Debug, Default)]
+pub struct FingerprintDatabase {
+    points: Vec<ReferencePoint>,
+}
+
+impl FingerprintDatabase {
+    /// 创建空数据库
+    pub fn new() -> Self {
+        FingerprintDatabase { points: Vec::new() }
+    }
+
+    /// 添加参考点
+    pub fn add_point(&mut self, point: ReferencePoint) {
+        self.points.push(point);
+    }
+
+    /// 获取所有参考点
+    pub fn points(&self) -> &[ReferencePoint] {
+        &self.points
+    }
+
+    /// 参考点数量
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// 用高置信度的定位结果（通常来自测距算法）更新附近参考点
+    ///
+    /// 半径内每个参考点的每个信标 RSSI 按指数滑动平均向实测值靠拢；
+    /// 参考点尚无该信标时从 [`MISSING_RSSI`] 开始同样按学习率混合，
+    /// 学习率衰减到 0 的参考点（位于半径边缘）不更新也不计入样本数
+    ///
+    /// # 返回
+    /// - 被更新的参考点数
+    pub fn update_from_fix(&mut self, fix: &LocationResult, signals: &SignalReadings, params: &OnlineUpdate) -> usize {
+        if fix.confidence < params.min_confidence || signals.count() == 0 || params.radius <= 0.0 {
+            return 0;
+        }
+
+        let mut updated = 0;
+        for point in &mut self.points {
+            let distance = ((point.x - fix.x).powi(2) + (point.y - fix.y).powi(2)).sqrt();
+            if distance > params.radius {
+                continue;
+            }
+            let alpha = (params.learning_rate * (1.0 - distance / params.radius)).clamp(0.0, 1.0);
+            if alpha <= f64::EPSILON {
+                continue;
+            }
+            for (beacon_id, rssi) in signals.all() {
+                let observed = *rssi as f64;
+                let r = point.rssi.entry(beacon_id.clone()).or_insert(MISSING_RSSI);
+                *r += alpha * (observed - *r);
+                *point.sample_counts.entry(beacon_id.clone()).or_insert(0) += 1;
+            }
+            updated += 1;
+        }
+        updated
+    }
+
+    /// kNN 指纹定位 - 按信号距离倒数对最近的 k 个参考点加权平均
+    pub fn locate_knn(&self, signals: &SignalReadings, k: usize) -> Option<LocationResult> {
+        if signals.count() == 0 {
+            return None;
+        }
+        self._knn(k, signals.count(), "fingerprint_knn", |p| p.signal_distance(signals))
+    }
+
+    /// 蓝牙与 Wi-Fi 融合的 kNN 指纹定位
+    ///
+    /// # 参数
+    /// - `ble`: 蓝牙信标读数
+    /// - `wifi`: Wi-Fi 读数（BSSID -> RSSI）
+    /// - `k`: 近邻数
+    /// - `wifi_weight`: Wi-Fi 距离的权重，现场 AP 稳定时可取 0.5 ~ 1.0
+    pub fn locate_knn_fused(
+        &self,
+        ble: &SignalReadings,
+        wifi: &SignalReadings,
+        k: usize,
+        wifi_weight: f64,
+    ) -> Option<LocationResult> {
+        self.locate_knn_query(&FingerprintQuery::new(ble.clone()).with_wifi(wifi.clone(), wifi_weight), k)
+    }
+
+    /// 按综合查询（蓝牙、Wi-Fi、地磁）的 kNN 指纹定位
+    pub fn locate_knn_query(&self, query: &FingerprintQuery, k: usize) -> Option<LocationResult> {
+        let count = query.measurement_count();
+        if count == 0 {
+            return None;
+        }
+        self._knn(k, count, "fingerprint_knn_fused", |p| p.query_distance(query))
+    }
+
+    fn _knn(
+        &self,
+        k: usize,
+        signal_count: usize,
+        method: &str,
+        distance: impl Fn(&ReferencePoint) -> f64,
+    ) -> Option<LocationResult> {
+        if self.points.is_empty() || k == 0 {
+            return None;
+        }
+
+        let mut ranked: Vec<(f64, &ReferencePoint)> = self
+            .points
+            .iter()
+            .map(|p| (distance(p), p))
+            .collect();
+        ranked.sort_by(|a, b| a.0.total_cmp(&b.0));
+        ranked.truncate(k);
+
+        let weights: Vec<f64> = ranked.iter().map(|(d, _)| 1.0 / (d + 1e-6)).collect();
+        let total_weight: f64 = weights.iter().sum();
+
+        let mut x = 0.0;
+        let mut y = 0.0;
+        let mut z = 0.0;
+        for ((_, p), w) in ranked.iter().zip(&weights) {
+            x += p.x * w;
+            y += p.y * w;
+            z += p.z * w;
+        }
+        x /= total_weight;
+        y /= total_weight;
+        z /= total_weight;
+
+        // 误差取参考点到估计位置的平均距离，置信度由信号距离决定
+        let error = ranked
+            .iter()
+            .map(|(_, p)| ((p.x - x).powi(2) + (p.y - y).powi(2)).sqrt())
+            .sum::<f64>()
+            / ranked.len() as f64;
+        let mean_signal_distance =
+            ranked.iter().map(|(d, _)| d).sum::<f64>() / ranked.len() as f64;
+        let confidence = 1.0 / (1.0 + mean_signal_distance / 10.0);
+
+        Some(LocationResult::new(
+            x,
+            y,
+            z,
+            confidence,
+            error,
+            method.to_string(),
+            signal_count,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(label: &str, x: f64, rssi: &[(&str, f64)]) -> ReferencePoint {
+        let mut p = ReferencePoint::new(label, x, 0.0, 0.0);
+        for (id, r) in rssi {
+            p.rssi.insert(id.to_string(), *r);
+        }
+        p
+    }
+
+    #[test]
+    fn test_knn_nearest_point() {
+        let mut db = FingerprintDatabase::new();
+        db.add_point(point("P1", 0.0, &[("B1", -50.0), ("B2", -80.0)]));
+        db.add_point(point("P2", 500.0, &[("B1", -80.0), ("B2", -50.0)]));
+
+        let signals = SignalReadings::from_pairs(vec![("B1", -51), ("B2", -79)]);
+        let result = db.locate_knn(&signals, 1).unwrap();
+        assert_eq!(result.x, 0.0);
+        assert_eq!(result.method, "fingerprint_knn");
+    }
+
+    #[test]
+    fn test_knn_weighted_between_points() {
+        let mut db = FingerprintDatabase::new();
+        db.add_point(point("P1", 0.0, &[("B1", -50.0), ("B2", -80.0)]));
+        db.add_point(point("P2", 500.0, &[("B1", -80.0), ("B2", -50.0)]));
+
+        let signals = SignalReadings::from_pairs(vec![("B1", -65), ("B2", -65)]);
+        let result = db.locate_knn(&signals, 2).unwrap();
+        assert!((result.x - 250.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_knn_fused_wifi_breaks_tie() {
+        // 两个参考点的蓝牙指纹相同，只能靠 Wi-Fi 区分
+        let mut db = FingerprintDatabase::new();
+        db.add_point(point("P1", 0.0, &[("B1", -60.0)]).with_wifi("aa:bb:cc:00:00:01", -40.0));
+        db.add_point(point("P2", 500.0, &[("B1", -60.0)]).with_wifi("aa:bb:cc:00:00:01", -75.0));
+
+        let ble = SignalReadings::from_pairs(vec![("B1", -60)]);
+        let wifi = SignalReadings::from_pairs(vec![("aa:bb:cc:00:00:01", -73)]);
+        let result = db.locate_knn_fused(&ble, &wifi, 1, 1.0).unwrap();
+        assert!((result.x - 500.0).abs() < 1e-9);
+        assert_eq!(result.method, "fingerprint_knn_fused");
+        assert_eq!(result.beacon_count, 2);
+    }
+
+    #[test]
+    fn test_online_update_moves_nearby_points() {
+        let mut db = FingerprintDatabase::new();
+        db.add_point(point("P1", 0.0, &[("B1", -60.0)]));
+        db.add_point(point("P2", 100.0, &[("B1", -60.0)]));
+        db.add_point(point("P3", 1000.0, &[("B1", -60.0)]));
+        db.add_point(point("P4", 200.0, &[("B1", -60.0)]));
+
+        let signals = SignalReadings::from_pairs(vec![("B1", -70), ("B2", -80)]);
+        let params = OnlineUpdate::default();
+        let low = LocationResult::new(0.0, 0.0, 0.0, 0.5, 50.0, "m".to_string(), 3);
+        assert_eq!(db.update_from_fix(&low, &signals, &params), 0);
+
+        let fix = LocationResult::new(0.0, 0.0, 0.0, 0.9, 50.0, "m".to_string(), 3);
+        assert_eq!(db.update_from_fix(&fix, &signals, &params), 2);
+        let points = db.points();
+        assert!((points[0].rssi["B1"] - -61.0).abs() < 1e-9);
+        assert!((points[1].rssi["B1"] - -60.5).abs() < 1e-9);
+        // 新信标从 MISSING_RSSI 开始混合
+        assert!((points[0].rssi["B2"] - -98.0).abs() < 1e-9);
+        assert!((points[1].rssi["B2"] - -99.0).abs() < 1e-9);
+        assert_eq!(points[2].rssi["B1"], -60.0);
+        // 半径边缘的学习率为 0：不插入新信标，也不计样本
+        assert!(!points[3].rssi.contains_key("B2"));
+        assert!(points[3].sample_counts.is_empty());
+        assert_eq!(points[0].sample_counts["B2"], 1);
+    }
+
+    #[test]
+    fn test_knn_query_with_magnetic() {
+        let mut db = FingerprintDatabase::new();
+        db.add_point(point("P1", 0.0, &[("B1", -60.0)]).with_magnetic(42.0));
+        db.add_point(point("P2", 500.0, &[("B1", -61.0)]).with_magnetic(65.0));
+
+        let ble = SignalReadings::from_pairs(vec![("B1", -60)]);
+        // 仅蓝牙时 P1 更近；地磁读数接近 P2
+        assert!(db.locate_knn(&ble, 1).unwrap().x.abs() < 1e-9);
+        let query = FingerprintQuery::new(ble).with_magnetic(63.0, 1.0);
+        let result = db.locate_knn_query(&query, 1).unwrap();
+        assert!((result.x - 500.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,123 @@
+/// 指纹采集质量报告
+///
+/// 汇总覆盖空白、各参考点的样本数以及各信标在参考点上的可见性，
+/// 便于安装人员在怀疑算法之前先确定需要补采的位置
+
+use crate::algorithms::FingerprintDatabase;
+use std::collections::BTreeMap;
+
+/// 单个参考点的采集质量
+#[derive(Clone, Debug, PartialEq)]
+pub struct PointQuality {
+    /// 参考点标签
+    pub label: String,
+    /// X 坐标
+    pub x: f64,
+    /// Y 坐标
+    pub y: f64,
+    /// 有指纹数据的信标数
+    pub visible_beacons: usize,
+    /// 各信标样本数之和
+    pub total_samples: usize,
+    /// 样本最少的信标的样本数，没有样本记录时为 None
+    pub min_samples: Option<usize>,
+}
+
+/// 采集质量报告
+#[derive(Clone, Debug, PartialEq)]
+pub struct SurveyQualityReport {
+    /// 各参考点质量，按标签排序
+    pub points: Vec<PointQuality>,
+    /// 信标 ID -> 能收到该信标的参考点数
+    pub beacon_visibility: BTreeMap<String, usize>,
+    /// 覆盖空白：网格中附近没有参考点的单元中心 (x, y)
+    pub coverage_gaps: Vec<(f64, f64)>,
+    /// 样本数不足（任一信标少于阈值）或信标数不足 3 个的参考点标签
+    pub needs_resurvey: Vec<String>,
+}
+
+impl SurveyQualityReport {
+    /// 只被少数参考点收到的信标（可见参考点数不超过 `max_points`）
+    ///
+    /// 从未被任何参考点收到的信标不在 `beacon_visibility` 中，也不会返回
+    pub fn rarely_visible_beacons(&self, max_points: usize) -> Vec<&str> {
+        self.beacon_visibility
+            .iter()
+            .filter(|(_, count)| **count <= max_points)
+            .map(|(id, _)| id.as_str())
+            .collect()
+    }
+}
+
+impl FingerprintDatabase {
+    /// 生成采集质量报告
+    ///
+    /// # 参数
+    /// - `cell_size`: 检查覆盖空白的网格尺寸（坐标单位）
+    /// - `min_samples`: 每个信标的最少样本数
+    pub fn quality_report(&self, cell_size: f64, min_samples: usize) -> SurveyQualityReport {
+        let mut points = Vec::new();
+        let mut beacon_visibility = BTreeMap::new();
+        let mut needs_resurvey = Vec::new();
+
+        for point in self.points() {
+            for beacon_id in point.rssi.keys() {
+                *beacon_visibility.entry(beacon_id.clone()).or_insert(0) += 1;
+            }
+            let quality = PointQuality {
+                label: point.label.clone(),
+                x: point.x,
+                y: point.y,
+                visible_beacons: point.rssi.len(),
+                total_samples: point.sample_counts.values().sum(),
+                min_samples: point.rssi.keys().map(|id| point.sample_counts.get(id).copied().unwrap_or(0)).min(),
+            };
+            if quality.visible_beacons < 3 || quality.min_samples.is_some_and(|n| n < min_samples) {
+                needs_resurvey.push(quality.label.clone());
+            }
+            points.push(quality);
+        }
+        points.sort_by(|a, b| a.label.cmp(&b.label));
+        needs_resurvey.sort();
+
+        let coverage_gaps = self
+            .uncovered_cells(cell_size)
+            .into_iter()
+            .map(|(_, _, x, y)| (x, y))
+            .collect();
+
+        SurveyQualityReport {
+            points,
+            beacon_visibility,
+            coverage_gaps,
+            needs_resurvey,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::ReferencePoint;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_quality_report() {
+        let samples = |pairs: &[(&str, usize)]| -> HashMap<String, Vec<i16>> {
+            pairs.iter().map(|(id, n)| (id.to_string(), vec![-60; *n])).collect()
+        };
+        let mut db = FingerprintDatabase::new();
+        db.add_point(ReferencePoint::from_samples("A", 0.0, 0.0, 0.0, &samples(&[("B1", 30), ("B2", 30), ("B3", 30)])));
+        db.add_point(ReferencePoint::from_samples("B", 300.0, 0.0, 0.0, &samples(&[("B1", 30), ("B2", 4), ("B3", 30)])));
+        db.add_point(ReferencePoint::from_samples("C", 300.0, 300.0, 0.0, &samples(&[("B1", 30), ("B4", 30)])));
+
+        let report = db.quality_report(100.0, 10);
+        assert_eq!(report.points.len(), 3);
+        assert_eq!(report.points[1].min_samples, Some(4));
+        assert_eq!(report.needs_resurvey, vec!["B".to_string(), "C".to_string()]);
+        assert_eq!(report.beacon_visibility["B1"], 3);
+        assert_eq!(report.rarely_visible_beacons(1), vec!["B4"]);
+        // 4 x 4 网格中只有 3 个单元有参考点
+        assert_eq!(report.coverage_gaps.len(), 13);
+    }
+}
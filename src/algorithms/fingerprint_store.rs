@@ -0,0 +1,396 @@
+/// 指纹数据库的持久化与维护
+///
+/// - JSON 与紧凑二进制两种带版本号的存储格式，重新部署后无需重新采集
+/// - 合并位置重复的参考点（压缩）
+/// - 按网格对未采集的位置插值补点
+
+use crate::algorithms::{FingerprintDatabase, ReferencePoint};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 当前存储格式版本
+pub const FINGERPRINT_FORMAT_VERSION: u16 = 1;
+
+/// 二进制格式的文件头
+const BINARY_MAGIC: &[u8; 4] = b"BLFP";
+
+/// JSON 存储格式
+#[derive(Serialize, Deserialize)]
+struct FingerprintFile {
+    version: u16,
+    points: Vec<ReferencePoint>,
+}
+
+impl FingerprintDatabase {
+    /// 序列化为 JSON（带格式版本号）
+    pub fn to_json(&self) -> Result<String, String> {
+        let file = FingerprintFile {
+            version: FINGERPRINT_FORMAT_VERSION,
+            points: self.points().to_vec(),
+        };
+        serde_json::to_string_pretty(&file).map_err(|e| format!("指纹数据库序列化失败: {}", e))
+    }
+
+    /// 从 JSON 解析
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let file: FingerprintFile =
+            serde_json::from_str(json).map_err(|e| format!("指纹数据库解析失败: {}", e))?;
+        check_version(file.version)?;
+        Ok(Self::from_points(file.points))
+    }
+
+    /// 序列化为紧凑的二进制格式
+    ///
+    /// 布局（小端）：`BLFP`、u16 版本、u32 参考点数，随后逐个参考点写入
+    /// 标签、坐标、地磁、蓝牙 (ID, RSSI, 样本数) 列表和 Wi-Fi (BSSID, RSSI) 列表
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(BINARY_MAGIC);
+        out.extend_from_slice(&FINGERPRINT_FORMAT_VERSION.to_le_bytes());
+        out.extend_from_slice(&(self.len() as u32).to_le_bytes());
+
+        for point in self.points() {
+            write_str(&mut out, &point.label);
+            for v in [point.x, point.y, point.z] {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            match point.magnetic {
+                Some(m) => {
+                    out.push(1);
+                    out.extend_from_slice(&m.to_le_bytes());
+                }
+                None => out.push(0),
+            }
+
+            let mut beacons: Vec<_> = point.rssi.iter().collect();
+            beacons.sort_by(|a, b| a.0.cmp(b.0));
+            out.extend_from_slice(&(beacons.len() as u32).to_le_bytes());
+            for (id, rssi) in beacons {
+                write_str(&mut out, id);
+                out.extend_from_slice(&rssi.to_le_bytes());
+                let count = point.sample_counts.get(id).copied().unwrap_or(0) as u32;
+                out.extend_from_slice(&count.to_le_bytes());
+            }
+
+            let mut wifi: Vec<_> = point.wifi_rssi.iter().collect();
+            wifi.sort_by(|a, b| a.0.cmp(b.0));
+            out.extend_from_slice(&(wifi.len() as u32).to_le_bytes());
+            for (bssid, rssi) in wifi {
+                write_str(&mut out, bssid);
+                out.extend_from_slice(&rssi.to_le_bytes());
+            }
+        }
+        out
+    }
+
+    /// 从二进制格式解析
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes, pos: 0 };
+        if reader.take(4)? != BINARY_MAGIC {
+            return Err("不是指纹数据库文件（文件头不匹配）".to_string());
+        }
+        check_version(reader.u16()?)?;
+
+        let count = reader.u32()?;
+        let mut points = Vec::new();
+        for _ in 0..count {
+            let label = reader.string()?;
+            let mut point = ReferencePoint::new(label, reader.f64()?, reader.f64()?, reader.f64()?);
+            if reader.take(1)?[0] == 1 {
+                point.magnetic = Some(reader.f64()?);
+            }
+            for _ in 0..reader.u32()? {
+                let id = reader.string()?;
+                point.rssi.insert(id.clone(), reader.f64()?);
+                let samples = reader.u32()? as usize;
+                if samples > 0 {
+                    point.sample_counts.insert(id, samples);
+                }
+            }
+            for _ in 0..reader.u32()? {
+                let bssid = reader.string()?;
+                point.wifi_rssi.insert(bssid, reader.f64()?);
+            }
+            points.push(point);
+        }
+        if reader.pos != bytes.len() {
+            return Err(format!("指纹数据库文件末尾有 {} 字节多余数据", bytes.len() - reader.pos));
+        }
+        Ok(Self::from_points(points))
+    }
+
+    /// 保存到文件：扩展名为 `.json` 时使用 JSON，否则使用二进制格式
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let bytes = if is_json_path(path) {
+            self.to_json()?.into_bytes()
+        } else {
+            self.to_bytes()
+        };
+        std::fs::write(path, bytes).map_err(|e| format!("无法写入指纹数据库 {}: {}", path.display(), e))
+    }
+
+    /// 从文件读取，格式规则与 [`FingerprintDatabase::save`] 相同
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| format!("无法读取指纹数据库 {}: {}", path.display(), e))?;
+        if is_json_path(path) {
+            let json = String::from_utf8(bytes).map_err(|e| format!("指纹数据库不是有效的 UTF-8: {}", e))?;
+            Self::from_json(&json)
+        } else {
+            Self::from_bytes(&bytes)
+        }
+    }
+
+    /// 压缩数据库：合并距离不超过 `tolerance` 的参考点，删除没有任何指纹数据的参考点
+    ///
+    /// 合并时各信标 RSSI 按样本数加权平均（无样本数记为 1），坐标取平均，标签保留第一个
+    ///
+    /// # 返回
+    /// - 减少的参考点数
+    pub fn compact(&mut self, tolerance: f64) -> usize {
+        let before = self.len();
+        let mut merged: Vec<(ReferencePoint, usize)> = Vec::new();
+
+        for point in self.points().iter().filter(|p| !p.rssi.is_empty() || !p.wifi_rssi.is_empty()) {
+            let target = merged.iter_mut().find(|(m, _)| {
+                ((m.x - point.x).powi(2) + (m.y - point.y).powi(2) + (m.z - point.z).powi(2)).sqrt() <= tolerance
+            });
+            let Some((existing, merges)) = target else {
+                merged.push((point.clone(), 1));
+                continue;
+            };
+
+            let n = *merges as f64;
+            existing.x = (existing.x * n + point.x) / (n + 1.0);
+            existing.y = (existing.y * n + point.y) / (n + 1.0);
+            existing.z = (existing.z * n + point.z) / (n + 1.0);
+            *merges += 1;
+
+            for (id, rssi) in &point.rssi {
+                let incoming = point.sample_counts.get(id).copied().unwrap_or(1).max(1);
+                let current = existing.sample_counts.get(id).copied().unwrap_or(1).max(1);
+                existing
+                    .rssi
+                    .entry(id.clone())
+                    .and_modify(|r| *r = (*r * current as f64 + rssi * incoming as f64) / (current + incoming) as f64)
+                    .or_insert(*rssi);
+                let total = if existing.sample_counts.contains_key(id) { current + incoming } else { incoming };
+                existing.sample_counts.insert(id.clone(), total);
+            }
+            for (bssid, rssi) in &point.wifi_rssi {
+                existing
+                    .wifi_rssi
+                    .entry(bssid.clone())
+                    .and_modify(|r| *r = (*r + rssi) / 2.0)
+                    .or_insert(*rssi);
+            }
+            if existing.magnetic.is_none() {
+                existing.magnetic = point.magnetic;
+            }
+        }
+
+        *self = Self::from_points(merged.into_iter().map(|(p, _)| p).collect());
+        before - self.len()
+    }
+
+    /// 对采集点包围盒内未采集的网格单元插值补点
+    ///
+    /// 单元中心 `cell_size / 2` 范围内没有参考点时，用最近的 `k` 个已采集参考点按距离倒数加权
+    /// 插值蓝牙与 Wi-Fi RSSI，新点标签为 `interp(列,行)`，不记录样本数以便与实测点区分
+    ///
+    /// # 返回
+    /// - 新增的参考点数
+    pub fn interpolate_grid(&mut self, cell_size: f64, k: usize) -> usize {
+        if k == 0 {
+            return 0;
+        }
+        let cells = self.uncovered_cells(cell_size);
+        let surveyed: Vec<ReferencePoint> = self.points().to_vec();
+
+        for &(column, row, cx, cy) in &cells {
+            let mut ranked: Vec<(f64, &ReferencePoint)> = surveyed
+                .iter()
+                .map(|p| (((p.x - cx).powi(2) + (p.y - cy).powi(2)).sqrt(), p))
+                .collect();
+            ranked.sort_by(|a, b| a.0.total_cmp(&b.0));
+            ranked.truncate(k);
+
+            let mut point = ReferencePoint::new(format!("interp({},{})", column, row), cx, cy, 0.0);
+            let total_weight: f64 = ranked.iter().map(|(d, _)| 1.0 / d).sum();
+            point.z = ranked.iter().map(|(d, p)| p.z / d).sum::<f64>() / total_weight;
+            point.rssi = weighted_average(&ranked, |p| &p.rssi);
+            point.wifi_rssi = weighted_average(&ranked, |p| &p.wifi_rssi);
+            self.add_point(point);
+        }
+        cells.len()
+    }
+
+    /// 参考点包围盒内按 `cell_size` 划分的网格中，中心 `cell_size / 2` 范围内没有参考点的单元
+    ///
+    /// # 返回
+    /// - (列, 行, 中心 x, 中心 y) 列表
+    pub(crate) fn uncovered_cells(&self, cell_size: f64) -> Vec<(usize, usize, f64, f64)> {
+        if self.is_empty() || cell_size <= 0.0 {
+            return Vec::new();
+        }
+        let points = self.points();
+        let min_x = points.iter().map(|p| p.x).fold(f64::INFINITY, f64::min);
+        let max_x = points.iter().map(|p| p.x).fold(f64::NEG_INFINITY, f64::max);
+        let min_y = points.iter().map(|p| p.y).fold(f64::INFINITY, f64::min);
+        let max_y = points.iter().map(|p| p.y).fold(f64::NEG_INFINITY, f64::max);
+        let columns = ((max_x - min_x) / cell_size).floor() as usize + 1;
+        let rows = ((max_y - min_y) / cell_size).floor() as usize + 1;
+
+        let mut cells = Vec::new();
+        for row in 0..rows {
+            for column in 0..columns {
+                let cx = min_x + column as f64 * cell_size;
+                let cy = min_y + row as f64 * cell_size;
+                let covered = points
+                    .iter()
+                    .any(|p| ((p.x - cx).powi(2) + (p.y - cy).powi(2)).sqrt() <= cell_size / 2.0);
+                if !covered {
+                    cells.push((column, row, cx, cy));
+                }
+            }
+        }
+        cells
+    }
+
+    fn from_points(points: Vec<ReferencePoint>) -> Self {
+        let mut db = FingerprintDatabase::new();
+        for point in points {
+            db.add_point(point);
+        }
+        db
+    }
+}
+
+/// 按距离倒数对各参考点的同名信号加权平均（只对含该信号的参考点归一化）
+fn weighted_average(
+    ranked: &[(f64, &ReferencePoint)],
+    field: impl Fn(&ReferencePoint) -> &HashMap<String, f64>,
+) -> HashMap<String, f64> {
+    let mut sums: HashMap<String, (f64, f64)> = HashMap::new();
+    for (distance, point) in ranked {
+        for (id, rssi) in field(point) {
+            let entry = sums.entry(id.clone()).or_insert((0.0, 0.0));
+            entry.0 += rssi / distance;
+            entry.1 += 1.0 / distance;
+        }
+    }
+    sums.into_iter().map(|(id, (sum, weight))| (id, sum / weight)).collect()
+}
+
+fn check_version(version: u16) -> Result<(), String> {
+    if version == 0 || version > FINGERPRINT_FORMAT_VERSION {
+        return Err(format!(
+            "不支持的指纹数据库版本 {}（当前支持 1 ~ {}）",
+            version, FINGERPRINT_FORMAT_VERSION
+        ));
+    }
+    Ok(())
+}
+
+fn is_json_path(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(&(s.len() as u32).to_le_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
+
+/// 二进制读取游标
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(n).filter(|end| *end <= self.bytes.len());
+        let Some(end) = end else {
+            return Err(format!("指纹数据库文件在偏移 {} 处被截断", self.pos));
+        };
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap_or_default()))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap_or_default()))
+    }
+
+    fn f64(&mut self) -> Result<f64, String> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap_or_default()))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|e| format!("指纹数据库字符串无效: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_db() -> FingerprintDatabase {
+        let mut db = FingerprintDatabase::new();
+        let mut p1 = ReferencePoint::new("P1", 0.0, 0.0, 0.0).with_wifi("aa:bb:cc:00:00:01", -45.0).with_magnetic(48.5);
+        p1.rssi.insert("B1".to_string(), -60.0);
+        p1.sample_counts.insert("B1".to_string(), 20);
+        let mut p2 = ReferencePoint::new("P2", 400.0, 0.0, 0.0);
+        p2.rssi.insert("B1".to_string(), -80.0);
+        db.add_point(p1);
+        db.add_point(p2);
+        db
+    }
+
+    #[test]
+    fn test_round_trip_json_and_binary() {
+        let db = sample_db();
+
+        let from_json = FingerprintDatabase::from_json(&db.to_json().unwrap()).unwrap();
+        let bytes = db.to_bytes();
+        let from_bytes = FingerprintDatabase::from_bytes(&bytes).unwrap();
+        for restored in [&from_json, &from_bytes] {
+            assert_eq!(restored.len(), 2);
+            let p1 = &restored.points()[0];
+            assert_eq!(p1.rssi["B1"], -60.0);
+            assert_eq!(p1.sample_counts["B1"], 20);
+            assert_eq!(p1.wifi_rssi["aa:bb:cc:00:00:01"], -45.0);
+            assert_eq!(p1.magnetic, Some(48.5));
+        }
+
+        assert!(FingerprintDatabase::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(FingerprintDatabase::from_json(r#"{"version": 9, "points": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_compact_and_interpolate() {
+        let mut db = sample_db();
+        let mut duplicate = ReferencePoint::new("P1b", 1.0, 0.0, 0.0);
+        duplicate.rssi.insert("B1".to_string(), -70.0);
+        duplicate.sample_counts.insert("B1".to_string(), 20);
+        db.add_point(duplicate);
+        db.add_point(ReferencePoint::new("empty", 900.0, 0.0, 0.0));
+
+        assert_eq!(db.compact(5.0), 2);
+        assert_eq!(db.points()[0].rssi["B1"], -65.0);
+        assert_eq!(db.points()[0].sample_counts["B1"], 40);
+
+        // P1 (x≈0) 与 P2 (x=400) 之间按 100 网格补 3 个点
+        assert_eq!(db.interpolate_grid(100.0, 2), 3);
+        let middle = db.points().iter().find(|p| p.label == "interp(2,0)").unwrap();
+        assert!((middle.rssi["B1"] - -72.5).abs() < 0.5);
+        assert!(middle.sample_counts.is_empty());
+    }
+}
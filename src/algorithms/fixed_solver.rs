@@ -0,0 +1,103 @@
+/// 固定信标数的三边定位
+///
+/// 测量以定长数组传入，求解过程只使用栈上数据、不分配堆内存，
+/// 适合多目标跟踪的热循环以及无分配器的嵌入式环境
+
+use crate::algorithms::{LocationAlgorithm, LocationResult};
+
+/// 固定信标数求解的结果（`Copy`，不含堆数据）
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FixedSolution {
+    /// X 坐标
+    pub x: f64,
+    /// Y 坐标
+    pub y: f64,
+    /// Z 坐标（信标平均高度）
+    pub z: f64,
+    /// 测距残差的均方根
+    pub error: f64,
+}
+
+impl FixedSolution {
+    /// 转换为 `LocationResult`（置信度计算与其他三边定位算法一致）
+    pub fn to_location_result(&self, beacon_count: usize) -> LocationResult {
+        let confidence = (1.0 / (1.0 + self.error / 100.0)).min(1.0);
+        LocationResult::new(
+            self.x,
+            self.y,
+            self.z,
+            confidence,
+            self.error,
+            "trilateration_fixed".to_string(),
+            beacon_count,
+        )
+    }
+}
+
+impl LocationAlgorithm {
+    /// 固定信标数的最小二乘三边定位，如 `trilateration_fixed::<3>` / `::<4>`
+    ///
+    /// 测距先按信标与平均高度的高差投影到水平面，再线性最小二乘求 (x, y)；
+    /// `N < 3` 在编译期报错
+    ///
+    /// # 参数
+    /// - `measurements`: (信标 x, 信标 y, 信标 z, 距离)
+    ///
+    /// # 返回
+    /// - 定位结果，或 None 如果信标共线
+    pub fn trilateration_fixed<const N: usize>(measurements: &[(f64, f64, f64, f64); N]) -> Option<FixedSolution> {
+        const { assert!(N >= 3, "三边定位至少需要 3 个信标") };
+
+        let z = measurements.iter().map(|m| m.2).sum::<f64>() / N as f64;
+        let horizontal: [(f64, f64, f64); N] = std::array::from_fn(|i| {
+            let (bx, by, bz, r) = measurements[i];
+            let dz = bz - z;
+            (bx, by, (r * r - dz * dz).max(0.0).sqrt())
+        });
+        let (x, y) = Self::_least_squares_xy(&horizontal)?;
+
+        let sum_sq = measurements
+            .iter()
+            .map(|(bx, by, bz, r)| {
+                let d = ((x - bx).powi(2) + (y - by).powi(2) + (z - bz).powi(2)).sqrt();
+                (d - r).powi(2)
+            })
+            .sum::<f64>();
+
+        Some(FixedSolution {
+            x,
+            y,
+            z,
+            error: (sum_sq / N as f64).sqrt(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_three_and_four_beacons() {
+        let target = (300.0_f64, 400.0_f64);
+        let range = |bx: f64, by: f64| ((target.0 - bx).powi(2) + (target.1 - by).powi(2)).sqrt();
+
+        let three = [
+            (0.0, 0.0, 0.0, range(0.0, 0.0)),
+            (1000.0, 0.0, 0.0, range(1000.0, 0.0)),
+            (0.0, 1000.0, 0.0, range(0.0, 1000.0)),
+        ];
+        let solution = LocationAlgorithm::trilateration_fixed::<3>(&three).unwrap();
+        assert!((solution.x - 300.0).abs() < 1e-6 && (solution.y - 400.0).abs() < 1e-6);
+        assert!(solution.error < 1e-6);
+
+        let four = [three[0], three[1], three[2], (1000.0, 1000.0, 0.0, range(1000.0, 1000.0))];
+        let solution = LocationAlgorithm::trilateration_fixed(&four).unwrap();
+        assert!((solution.x - 300.0).abs() < 1e-6);
+        assert_eq!(solution.to_location_result(4).method, "trilateration_fixed");
+
+        // 共线信标无解
+        let collinear = [(0.0, 0.0, 0.0, 1.0), (1.0, 0.0, 0.0, 1.0), (2.0, 0.0, 0.0, 1.0)];
+        assert!(LocationAlgorithm::trilateration_fixed(&collinear).is_none());
+    }
+}
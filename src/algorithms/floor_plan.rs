@@ -0,0 +1,149 @@
+/// 平面图标注导入
+///
+/// 从平面图标注工具导出的 JSON 文件中读取信标像素坐标，
+/// 结合图像比例尺和原点自动换算为信标坐标。
+///
+/// 文件格式示例：
+/// ```json
+/// {
+///   "image": "floor1.png",
+///   "scale": 2.5,
+///   "origin": [120.0, 800.0],
+///   "default_height": 250.0,
+///   "beacons": [
+///     { "id": "20:A7:16:5E:C5:D6", "name": "RFstar_C5D6", "px": 425.0, "py": 713.0 }
+///   ]
+/// }
+/// ```
+/// - `scale`: 每像素对应的距离（与定位使用的单位一致，如厘米）
+/// - `origin`: 坐标原点在图像中的像素位置
+/// - `y_down`: 是否保留图像坐标的 y 轴向下方向（默认 false，即翻转为向上）
+
+use crate::algorithms::{Beacon, BeaconSet};
+use serde::{Deserialize, Serialize};
+
+/// 单个标注点
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AnnotatedBeacon {
+    /// 信标 ID
+    pub id: String,
+    /// 信标名称（可选，缺省使用 ID）
+    #[serde(default)]
+    pub name: Option<String>,
+    /// 像素 x 坐标
+    pub px: f64,
+    /// 像素 y 坐标
+    pub py: f64,
+    /// 安装高度（可选，缺省使用 `default_height`）
+    #[serde(default)]
+    pub z: Option<f64>,
+}
+
+/// 平面图标注文件
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FloorPlanAnnotation {
+    /// 图像文件名（仅作记录）
+    #[serde(default)]
+    pub image: Option<String>,
+    /// 每像素对应的距离
+    pub scale: f64,
+    /// 原点像素坐标
+    #[serde(default)]
+    pub origin: (f64, f64),
+    /// 图像 y 轴是否保持向下
+    #[serde(default)]
+    pub y_down: bool,
+    /// 未标注高度时使用的默认高度
+    #[serde(default)]
+    pub default_height: f64,
+    /// 标注的信标
+    pub beacons: Vec<AnnotatedBeacon>,
+}
+
+impl FloorPlanAnnotation {
+    /// 从 JSON 字符串解析标注
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let annotation: FloorPlanAnnotation =
+            serde_json::from_str(json).map_err(|e| format!("标注文件解析失败: {}", e))?;
+        annotation.validate()?;
+        Ok(annotation)
+    }
+
+    /// 从文件读取标注
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| format!("无法读取标注文件 {}: {}", path.as_ref().display(), e))?;
+        Self::from_json(&content)
+    }
+
+    /// 验证标注参数
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.scale.is_finite() || self.scale <= 0.0 {
+            return Err("比例尺 scale 必须为正数".to_string());
+        }
+        if let Some(b) = self.beacons.iter().find(|b| b.id.is_empty()) {
+            return Err(format!("标注点 ({}, {}) 缺少信标 ID", b.px, b.py));
+        }
+        Ok(())
+    }
+
+    /// 像素坐标转换为平面坐标
+    pub fn pixel_to_world(&self, px: f64, py: f64) -> (f64, f64) {
+        let x = (px - self.origin.0) * self.scale;
+        let dy = (py - self.origin.1) * self.scale;
+        let y = if self.y_down { dy } else { -dy };
+        (x, y)
+    }
+
+    /// 转换为信标列表
+    pub fn to_beacons(&self) -> Vec<Beacon> {
+        self.beacons
+            .iter()
+            .map(|b| {
+                let (x, y) = self.pixel_to_world(b.px, b.py);
+                Beacon::new(
+                    b.id.clone(),
+                    b.name.clone().unwrap_or_else(|| b.id.clone()),
+                    x,
+                    y,
+                    b.z.unwrap_or(self.default_height),
+                )
+            })
+            .collect()
+    }
+
+    /// 转换为信标集合
+    pub fn to_beacon_set(&self) -> BeaconSet {
+        BeaconSet::from_vec(self.to_beacons())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotation_to_beacons() {
+        let json = r#"{
+            "scale": 2.0,
+            "origin": [100.0, 500.0],
+            "default_height": 250.0,
+            "beacons": [
+                { "id": "B1", "name": "Beacon1", "px": 100.0, "py": 500.0 },
+                { "id": "B2", "px": 150.0, "py": 400.0, "z": 63.0 }
+            ]
+        }"#;
+        let set = FloorPlanAnnotation::from_json(json).unwrap().to_beacon_set();
+
+        assert_eq!(set.get("B1").unwrap().coordinates(), (0.0, 0.0, 250.0));
+        let b2 = set.get("B2").unwrap();
+        assert_eq!(b2.coordinates(), (100.0, 200.0, 63.0));
+        assert_eq!(b2.name, "B2");
+    }
+
+    #[test]
+    fn test_annotation_rejects_bad_scale() {
+        let json = r#"{ "scale": 0.0, "beacons": [] }"#;
+        assert!(FloorPlanAnnotation::from_json(json).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+/// 地理围栏与区域占用
+///
+/// 按设备的定位结果增量维护“区域 -> 当前在内的设备”和“设备 -> 当前所在区域”，
+/// 应用可以直接查询当前占用情况，无需从进出事件流中自行推导。
+/// 设备 ID 按 [`DeviceId`] 规范化，同一设备的不同写法视为同一设备
+
+use crate::algorithms::{BlunavEvent, DeviceId, EventBus, LocationResult, ZoneMap};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// 一次更新引起的区域变化
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ZoneTransitions {
+    /// 新进入的区域
+    pub entered: Vec<String>,
+    /// 离开的区域
+    pub left: Vec<String>,
+}
+
+impl ZoneTransitions {
+    /// 是否没有变化
+    pub fn is_empty(&self) -> bool {
+        self.entered.is_empty() && self.left.is_empty()
+    }
+}
+
+/// 地理围栏
+#[derive(Clone, Debug)]
+pub struct Geofence {
+    zones: ZoneMap,
+    /// 区域名称 -> 当前在内的设备（规范化 ID）
+    occupants: BTreeMap<String, BTreeSet<String>>,
+    /// 设备（规范化 ID） -> 当前所在区域
+    memberships: BTreeMap<String, BTreeSet<String>>,
+    /// 事件总线
+    events: Option<EventBus>,
+}
+
+impl Geofence {
+    /// 创建地理围栏，区域重叠时设备可同时属于多个区域
+    pub fn new(zones: ZoneMap) -> Self {
+        Geofence {
+            zones,
+            occupants: BTreeMap::new(),
+            memberships: BTreeMap::new(),
+            events: None,
+        }
+    }
+
+    /// 设置事件总线，设备进入区域时发布 `ZoneEntered`，离开或被移除时发布 `ZoneLeft`
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// 区域定义
+    pub fn zones(&self) -> &ZoneMap {
+        &self.zones
+    }
+
+    /// 用设备的最新定位结果更新区域占用
+    pub fn update(&mut self, device_id: &str, result: &LocationResult) -> ZoneTransitions {
+        let device_id = DeviceId::normalized(device_id);
+        let current: BTreeSet<String> = self.zones.zones_containing(result).into_iter().map(|z| z.name.clone()).collect();
+        let previous = self.memberships.remove(&device_id).unwrap_or_default();
+
+        let transitions = ZoneTransitions {
+            entered: current.difference(&previous).cloned().collect(),
+            left: previous.difference(&current).cloned().collect(),
+        };
+        for zone in &transitions.left {
+            self.leave(zone, &device_id);
+        }
+        for zone in &transitions.entered {
+            self.occupants.entry(zone.clone()).or_default().insert(device_id.clone());
+            if let Some(events) = &self.events {
+                events.emit(BlunavEvent::ZoneEntered { zone: zone.clone(), device_id: device_id.clone() });
+            }
+        }
+        if !current.is_empty() {
+            self.memberships.insert(device_id, current);
+        }
+        transitions
+    }
+
+    /// 移除设备（如设备丢失），返回它离开的区域
+    pub fn remove_device(&mut self, device_id: &str) -> Vec<String> {
+        let device_id = DeviceId::normalized(device_id);
+        let zones: Vec<String> = self.memberships.remove(&device_id).unwrap_or_default().into_iter().collect();
+        for zone in &zones {
+            self.leave(zone, &device_id);
+        }
+        zones
+    }
+
+    /// 区域内当前的设备，按规范化 ID 排序
+    pub fn occupancy(&self, zone: &str) -> Vec<DeviceId> {
+        self.occupants
+            .get(zone)
+            .map(|devices| devices.iter().map(|id| DeviceId::parse(id)).collect())
+            .unwrap_or_default()
+    }
+
+    /// 区域内当前的设备数
+    pub fn occupancy_count(&self, zone: &str) -> usize {
+        self.occupants.get(zone).map_or(0, |devices| devices.len())
+    }
+
+    /// 设备当前所在的区域，按区域名称排序
+    pub fn zones_of(&self, device_id: &str) -> Vec<&str> {
+        self.memberships
+            .get(&DeviceId::normalized(device_id))
+            .map(|zones| zones.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    fn leave(&mut self, zone: &str, device_id: &str) {
+        if let Some(devices) = self.occupants.get_mut(zone) {
+            devices.remove(device_id);
+            if devices.is_empty() {
+                self.occupants.remove(zone);
+            }
+        }
+        if let Some(events) = &self.events {
+            events.emit(BlunavEvent::ZoneLeft { zone: zone.to_string(), device_id: device_id.to_string() });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::Zone;
+
+    fn at(x: f64) -> LocationResult {
+        LocationResult::new(x, 50.0, 0.0, 0.9, 10.0, "test".to_string(), 3)
+    }
+
+    #[test]
+    fn test_occupancy_tracks_entries_and_exits() {
+        let zones = ZoneMap::from_vec(vec![
+            Zone::rectangle("dock", 0.0, 0.0, 100.0, 100.0),
+            Zone::rectangle("aisle", 50.0, 0.0, 300.0, 100.0),
+        ]);
+        let bus = EventBus::new(16);
+        let mut receiver = bus.subscribe();
+        let mut geofence = Geofence::new(zones).with_events(bus);
+
+        let t = geofence.update("tag-1", &at(75.0));
+        assert_eq!(t.entered, vec!["aisle", "dock"]);
+        geofence.update("aa-bb-cc-dd-ee-02", &at(20.0));
+        let tag_2 = DeviceId::parse("AA:BB:CC:DD:EE:02");
+        assert_eq!(geofence.occupancy("dock"), vec![tag_2.clone(), DeviceId::parse("tag-1")]);
+        assert_eq!(geofence.zones_of("tag-1"), vec!["aisle", "dock"]);
+
+        let t = geofence.update("tag-1", &at(200.0));
+        assert_eq!(t.left, vec!["dock"]);
+        assert!(t.entered.is_empty());
+        assert_eq!(geofence.occupancy("dock"), vec![tag_2.clone()]);
+        assert!(geofence.update("tag-1", &at(250.0)).is_empty());
+
+        assert_eq!(geofence.remove_device("AABBCCDDEE02"), vec!["dock"]);
+        assert_eq!(geofence.occupancy_count("dock"), 0);
+        assert!(geofence.zones_of("aa:bb:cc:dd:ee:02").is_empty());
+
+        // 事件带有设备 ID，离开与移除都会发布 ZoneLeft
+        let events: Vec<BlunavEvent> = std::iter::from_fn(|| receiver.try_recv().ok()).collect();
+        let kinds: Vec<&str> = events.iter().map(|e| e.kind()).collect();
+        assert_eq!(kinds, vec!["zone_entered", "zone_entered", "zone_entered", "zone_left", "zone_left"]);
+        assert!(matches!(&events[3], BlunavEvent::ZoneLeft { zone, device_id } if zone == "dock" && device_id == "tag-1"));
+        assert!(matches!(&events[4], BlunavEvent::ZoneLeft { zone, device_id } if zone == "dock" && *device_id == tag_2.to_string()));
+    }
+}
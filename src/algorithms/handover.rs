@@ -0,0 +1,222 @@
+/// 定位小区（信标组）软切换
+///
+/// 目标在房间/楼层之间移动时，活动信标组的切换带有滞回：
+/// - 新信标组的信号需比当前组强出 `hysteresis_db` 才触发切换
+/// - 切换后的若干个周期内，对新旧两组的定位结果做渐进加权融合，
+///   避免活动 `BeaconSet` 变化时位置跳变
+
+use crate::algorithms::{
+    BeaconSet, DistanceEstimator, LocationAlgorithm, LocationResult, SignalReadings,
+    SolverConstraints,
+};
+
+/// 定位小区 - 一组协同定位的信标（如一个房间）
+#[derive(Clone, Debug)]
+pub struct PositioningCell {
+    /// 小区 ID
+    pub id: String,
+    /// 小区内的信标
+    pub beacons: BeaconSet,
+}
+
+impl PositioningCell {
+    /// 创建定位小区
+    pub fn new(id: impl Into<String>, beacons: BeaconSet) -> Self {
+        PositioningCell {
+            id: id.into(),
+            beacons,
+        }
+    }
+
+    /// 小区信号评分 - 最强 3 个信标的平均 RSSI
+    pub fn score(&self, signals: &SignalReadings) -> Option<f64> {
+        let mut heard: Vec<i16> = self
+            .beacons
+            .iter()
+            .filter_map(|(id, _)| signals.get(id))
+            .collect();
+        if heard.is_empty() {
+            return None;
+        }
+        heard.sort_unstable_by(|a, b| b.cmp(a));
+        heard.truncate(3);
+        Some(heard.iter().map(|r| *r as f64).sum::<f64>() / heard.len() as f64)
+    }
+
+    /// 使用本小区信标定位
+    pub fn locate(
+        &self,
+        signals: &SignalReadings,
+        rssi_model: &(impl DistanceEstimator + ?Sized),
+    ) -> Option<LocationResult> {
+        LocationAlgorithm::trilateration_constrained(
+            &self.beacons.all_cloned(),
+            signals,
+            rssi_model,
+            &SolverConstraints::new(),
+        )
+    }
+}
+
+/// 带滞回的小区切换控制器
+#[derive(Clone, Debug)]
+pub struct CellHandover {
+    cells: Vec<PositioningCell>,
+    /// 触发切换所需的信号优势 (dB)
+    hysteresis_db: f64,
+    /// 切换后融合新旧结果的周期数
+    blend_epochs: usize,
+    /// 当前活动小区下标
+    active: Option<usize>,
+    /// 正在切出的小区下标
+    previous: Option<usize>,
+    /// 已完成的融合周期数
+    blend_progress: usize,
+}
+
+impl CellHandover {
+    /// 创建切换控制器
+    ///
+    /// # 参数
+    /// - `cells`: 所有定位小区
+    /// - `hysteresis_db`: 切换滞回 (dB)
+    /// - `blend_epochs`: 切换过渡周期数，0 表示立即切换
+    pub fn new(cells: Vec<PositioningCell>, hysteresis_db: f64, blend_epochs: usize) -> Self {
+        CellHandover {
+            cells,
+            hysteresis_db,
+            blend_epochs,
+            active: None,
+            previous: None,
+            blend_progress: 0,
+        }
+    }
+
+    /// 当前活动小区 ID
+    pub fn active_cell(&self) -> Option<&str> {
+        self.active.map(|i| self.cells[i].id.as_str())
+    }
+
+    /// 是否处于切换过渡期
+    pub fn in_transition(&self) -> bool {
+        self.previous.is_some()
+    }
+
+    /// 处理一个周期的信号，必要时切换小区，返回（可能融合后的）定位结果
+    pub fn update(
+        &mut self,
+        signals: &SignalReadings,
+        rssi_model: &(impl DistanceEstimator + ?Sized),
+    ) -> Option<LocationResult> {
+        self.select_cell(signals);
+        let active = self.active?;
+        let new_fix = self.cells[active].locate(signals, rssi_model);
+
+        let Some(previous) = self.previous else {
+            return new_fix;
+        };
+
+        let old_fix = self.cells[previous].locate(signals, rssi_model);
+        self.blend_progress += 1;
+        let alpha = self.blend_progress as f64 / (self.blend_epochs + 1) as f64;
+        if self.blend_progress >= self.blend_epochs {
+            self.previous = None;
+        }
+
+        match (old_fix, new_fix) {
+            (Some(old), Some(new)) => {
+                let mut blended =
+                    LocationAlgorithm::fuse_results(&[(old, 1.0 - alpha), (new, alpha)])?;
+                blended.method = "cell_handover".to_string();
+                Some(blended)
+            }
+            (old, new) => new.or(old),
+        }
+    }
+
+    fn select_cell(&mut self, signals: &SignalReadings) {
+        let scores: Vec<Option<f64>> = self.cells.iter().map(|c| c.score(signals)).collect();
+        let best = scores
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| s.map(|s| (i, s)))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        let Some((best, best_score)) = best else {
+            return;
+        };
+
+        match self.active {
+            None => self.active = Some(best),
+            Some(active) if active != best => {
+                let active_score = scores[active].unwrap_or(f64::NEG_INFINITY);
+                if best_score > active_score + self.hysteresis_db {
+                    self.previous = if self.blend_epochs > 0 { Some(active) } else { None };
+                    self.active = Some(best);
+                    self.blend_progress = 0;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{Beacon, DistanceUnit, RSSIModel};
+
+    fn cell(id: &str, offset: f64) -> PositioningCell {
+        let beacons = (0..3)
+            .map(|i| {
+                let (x, y) = [(0.0, 0.0), (500.0, 0.0), (0.0, 500.0)][i];
+                Beacon::new(format!("{}-{}", id, i), format!("{}-{}", id, i), x + offset, y, 0.0)
+            })
+            .collect();
+        PositioningCell::new(id, BeaconSet::from_vec(beacons))
+    }
+
+    #[test]
+    fn test_handover_hysteresis_and_blend() {
+        let model = RSSIModel::log_distance(-50.0, -30.0, DistanceUnit::Centimeter);
+        let mut handover = CellHandover::new(vec![cell("A", 0.0), cell("B", 1000.0)], 6.0, 2);
+
+        let room_a = SignalReadings::from_pairs(vec![
+            ("A-0", -60),
+            ("A-1", -62),
+            ("A-2", -62),
+            ("B-0", -80),
+        ]);
+        handover.update(&room_a, &model);
+        assert_eq!(handover.active_cell(), Some("A"));
+
+        // B 仅略强，不切换
+        let close = SignalReadings::from_pairs(vec![
+            ("A-0", -70),
+            ("A-1", -70),
+            ("A-2", -70),
+            ("B-0", -67),
+            ("B-1", -67),
+            ("B-2", -67),
+        ]);
+        handover.update(&close, &model);
+        assert_eq!(handover.active_cell(), Some("A"));
+
+        // B 明显更强，切换并进入过渡期
+        let room_b = SignalReadings::from_pairs(vec![
+            ("A-0", -75),
+            ("A-1", -75),
+            ("A-2", -75),
+            ("B-0", -60),
+            ("B-1", -62),
+            ("B-2", -62),
+        ]);
+        let fix = handover.update(&room_b, &model).unwrap();
+        assert_eq!(handover.active_cell(), Some("B"));
+        assert_eq!(fix.method, "cell_handover");
+        assert!(handover.in_transition());
+
+        handover.update(&room_b, &model);
+        assert!(!handover.in_transition());
+    }
+}
@@ -0,0 +1,102 @@
+/// 延迟直方图
+///
+/// 以固定的毫秒分桶记录从观测时间到输出定位结果的端到端延迟，
+/// 用于确认实时性要求并发现输入积压
+
+use std::time::Duration;
+
+/// 各分桶的上界（毫秒），最后一个分桶为超出所有上界的延迟
+pub const LATENCY_BUCKETS_MS: [u64; 10] = [5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000];
+
+/// 延迟直方图
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LatencyHistogram {
+    /// 各分桶计数，长度为 `LATENCY_BUCKETS_MS.len() + 1`
+    counts: Vec<u64>,
+    /// 样本数
+    count: u64,
+    /// 延迟总和（毫秒）
+    sum_ms: u64,
+    /// 最大延迟（毫秒）
+    max_ms: u64,
+}
+
+impl LatencyHistogram {
+    /// 创建空直方图
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次延迟
+    pub fn record(&mut self, latency: Duration) {
+        let ms = latency.as_millis() as u64;
+        if self.counts.is_empty() {
+            self.counts = vec![0; LATENCY_BUCKETS_MS.len() + 1];
+        }
+        let bucket = LATENCY_BUCKETS_MS.partition_point(|&bound| bound < ms);
+        self.counts[bucket] += 1;
+        self.count += 1;
+        self.sum_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+    }
+
+    /// 样本数
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// 平均延迟
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_millis(self.sum_ms / self.count))
+    }
+
+    /// 最大延迟
+    pub fn max(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_millis(self.max_ms))
+    }
+
+    /// 百分位延迟（`p` 取 0~1），返回所在分桶的上界；落在最后一个分桶时返回最大延迟
+    pub fn percentile(&self, p: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((p.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let bound = LATENCY_BUCKETS_MS.get(bucket).copied().unwrap_or(self.max_ms);
+                return Some(Duration::from_millis(bound.min(self.max_ms)));
+            }
+        }
+        self.max()
+    }
+
+    /// (分桶上界毫秒, 计数) 列表，最后一项上界为 None
+    pub fn buckets(&self) -> Vec<(Option<u64>, u64)> {
+        (0..=LATENCY_BUCKETS_MS.len())
+            .map(|i| (LATENCY_BUCKETS_MS.get(i).copied(), self.counts.get(i).copied().unwrap_or(0)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_histogram_percentiles() {
+        let mut histogram = LatencyHistogram::new();
+        assert!(histogram.percentile(0.5).is_none());
+
+        for ms in [3, 8, 20, 40, 80, 90, 95, 120, 400, 7000] {
+            histogram.record(Duration::from_millis(ms));
+        }
+        assert_eq!(histogram.count(), 10);
+        assert_eq!(histogram.percentile(0.5), Some(Duration::from_millis(100)));
+        assert_eq!(histogram.percentile(0.9), Some(Duration::from_millis(500)));
+        assert_eq!(histogram.percentile(1.0), Some(Duration::from_millis(7000)));
+        assert_eq!(histogram.max(), Some(Duration::from_millis(7000)));
+        assert_eq!(histogram.buckets().last(), Some(&(None, 1)));
+    }
+}
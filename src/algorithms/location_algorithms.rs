@@ -2,12 +2,18 @@
 /// 
 /// 支持：
 /// - 三边定位（基础、加权、最小二乘）
+/// - 外部提供的距离（UWB、超声波等）直接定位
 /// - 多信标融合
 /// - 卡尔曼滤波
 /// - 可配置的参数输入
 
-use crate::algorithms::{Beacon, LocationResult, RSSIModel};
+use crate::algorithms::{
+    Beacon, BeaconSet, DistanceEstimator, LocationResult, RSSIModel, Rssi, SignalStats, MISSING_RSSI,
+};
+use crate::algorithms::cooperative::gauss_newton_step;
 use std::collections::HashMap;
+use std::f64::consts::LN_10;
+use std::time::Duration;
 
 // ============================================================================
 // 信号测量数据结构
@@ -25,18 +31,18 @@ pub struct SignalMeasurement {
 }
 
 impl SignalMeasurement {
-    pub fn new(beacon_id: String, rssi: i16) -> Self {
+    pub fn new(beacon_id: String, rssi: impl Into<Rssi>) -> Self {
         SignalMeasurement {
             beacon_id,
-            rssi,
+            rssi: rssi.into().dbm(),
             timestamp_ms: None,
         }
     }
 
-    pub fn with_timestamp(beacon_id: String, rssi: i16, timestamp_ms: u64) -> Self {
+    pub fn with_timestamp(beacon_id: String, rssi: impl Into<Rssi>, timestamp_ms: u64) -> Self {
         SignalMeasurement {
             beacon_id,
-            rssi,
+            rssi: rssi.into().dbm(),
             timestamp_ms: Some(timestamp_ms),
         }
     }
@@ -67,7 +73,7 @@ impl SignalReadings {
     }
 
     /// 从 (beacon_id, rssi) 对的向量创建
-    pub fn from_pairs(pairs: Vec<(&str, i16)>) -> Self {
+    pub fn from_pairs(pairs: Vec<(&str, impl Into<Rssi>)>) -> Self {
         let mut readings = SignalReadings::new();
         for (id, rssi) in pairs {
             readings.add(id.to_string(), rssi);
@@ -83,12 +89,12 @@ impl SignalReadings {
     }
 
     /// 添加测量
-    pub fn add(&mut self, beacon_id: String, rssi: i16) {
-        self.measurements.insert(beacon_id, rssi);
+    pub fn add(&mut self, beacon_id: String, rssi: impl Into<Rssi>) {
+        self.measurements.insert(beacon_id, rssi.into().dbm());
     }
 
     /// 批量添加
-    pub fn add_multiple(&mut self, pairs: Vec<(String, i16)>) {
+    pub fn add_multiple(&mut self, pairs: Vec<(String, impl Into<Rssi>)>) {
         for (id, rssi) in pairs {
             self.add(id, rssi);
         }
@@ -126,6 +132,30 @@ impl Default for SignalReadings {
     }
 }
 
+// ============================================================================
+// 求解约束
+// ============================================================================
+
+/// 求解约束条件
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SolverConstraints {
+    /// 目标高度范围 (最小, 最大)，如胸卡佩戴高度 80 ~ 150 厘米
+    pub z_range: Option<(f64, f64)>,
+}
+
+impl SolverConstraints {
+    /// 无约束
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置目标高度范围
+    pub fn with_z_range(mut self, min: f64, max: f64) -> Self {
+        self.z_range = Some((min.min(max), min.max(max)));
+        self
+    }
+}
+
 // ============================================================================
 // 定位算法集合
 // ============================================================================
@@ -134,19 +164,50 @@ impl Default for SignalReadings {
 pub struct LocationAlgorithm;
 
 impl LocationAlgorithm {
+    /// 最近信标快速定位 - 直接返回信号最强信标的坐标
+    ///
+    /// 置信度由最强信标相对第二强信标的功率优势决定：
+    /// confidence = 1 - 10^(-margin/10)，优势 3 dB 约 0.5，10 dB 约 0.9。
+    /// 误差估计为最强与第二强信标间距的一半
+    pub fn quick_locate(signals: &SignalReadings, beacons: &BeaconSet) -> Option<LocationResult> {
+        let mut heard: Vec<(&Beacon, i16)> = signals
+            .all()
+            .iter()
+            .filter_map(|(id, rssi)| beacons.get(id).map(|b| (b, *rssi)))
+            .collect();
+        heard.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.id.cmp(&b.0.id)));
+
+        let (nearest, strongest) = *heard.first()?;
+        let (margin_db, error) = match heard.get(1) {
+            Some((second, rssi)) => ((strongest - rssi) as f64, nearest.distance_to(second) / 2.0),
+            None => (strongest as f64 - MISSING_RSSI, 0.0),
+        };
+        let confidence = 1.0 - 10_f64.powf(-margin_db / 10.0);
+
+        Some(LocationResult::new(
+            nearest.x,
+            nearest.y,
+            nearest.z,
+            confidence,
+            error,
+            "quick_locate".to_string(),
+            heard.len(),
+        ))
+    }
+
     /// 三边定位（基础版）- 仅使用 3 个信标
     ///
     /// # 参数
     /// - `beacons`: 信标集合
     /// - `signals`: 信号测量
-    /// - `rssi_model`: RSSI 转距离模型
+    /// - `rssi_model`: 测距模型（`RSSIModel` 或任意 `DistanceEstimator`）
     ///
     /// # 返回
     /// - 定位结果，或 None 如果信标不足
     pub fn trilateration_basic(
         beacons: &[Beacon],
         signals: &SignalReadings,
-        rssi_model: &RSSIModel,
+        rssi_model: &(impl DistanceEstimator + ?Sized),
     ) -> Option<LocationResult> {
         if beacons.len() < 3 {
             return None;
@@ -155,8 +216,9 @@ impl LocationAlgorithm {
         // 收集前三个信标的信号
         let mut measurements = Vec::new();
         for beacon in beacons.iter().take(3) {
-            if let Some(rssi) = signals.get(&beacon.id) {
-                let distance = rssi_model.rssi_to_distance(rssi);
+            if let Some(rssi) = signals.get(&beacon.id)
+                && let Some(distance) = rssi_model.distance(&beacon.id, rssi)
+            {
                 measurements.push((beacon.x, beacon.y, beacon.z, distance));
             }
         }
@@ -174,7 +236,7 @@ impl LocationAlgorithm {
     pub fn trilateration_weighted(
         beacons: &[Beacon],
         signals: &SignalReadings,
-        rssi_model: &RSSIModel,
+        rssi_model: &(impl DistanceEstimator + ?Sized),
     ) -> Option<LocationResult> {
         if beacons.len() < 3 {
             return None;
@@ -183,8 +245,9 @@ impl LocationAlgorithm {
         // 收集信号并计算权重
         let mut weighted_measurements = Vec::new();
         for beacon in beacons.iter().take(3) {
-            if let Some(rssi) = signals.get(&beacon.id) {
-                let distance = rssi_model.rssi_to_distance(rssi);
+            if let Some(rssi) = signals.get(&beacon.id)
+                && let Some(distance) = rssi_model.distance(&beacon.id, rssi)
+            {
                 // 权重：信号强度（绝对值越小权重越大）
                 let weight = 1.0 / ((-rssi as f64).abs() / 100.0 + 0.1);
                 weighted_measurements.push((beacon.x, beacon.y, beacon.z, distance, weight));
@@ -204,7 +267,7 @@ impl LocationAlgorithm {
     pub fn trilateration_least_squares(
         beacons: &[Beacon],
         signals: &SignalReadings,
-        rssi_model: &RSSIModel,
+        rssi_model: &(impl DistanceEstimator + ?Sized),
     ) -> Option<LocationResult> {
         if beacons.len() < 3 {
             return None;
@@ -213,8 +276,9 @@ impl LocationAlgorithm {
         // 收集所有可用的信号测量
         let mut measurements = Vec::new();
         for beacon in beacons {
-            if let Some(rssi) = signals.get(&beacon.id) {
-                let distance = rssi_model.rssi_to_distance(rssi);
+            if let Some(rssi) = signals.get(&beacon.id)
+                && let Some(distance) = rssi_model.distance(&beacon.id, rssi)
+            {
                 measurements.push((beacon.x, beacon.y, beacon.z, distance));
             }
         }
@@ -226,6 +290,150 @@ impl LocationAlgorithm {
         Self::_trilateration_least_squares_impl(&measurements)
     }
 
+    /// 带约束的三维最小二乘定位 - 支持 3+ 个信标
+    ///
+    /// 交替求解：固定 z 时将测距投影到水平面，线性最小二乘求 (x, y)；
+    /// 固定 (x, y) 时在 `z_range` 内一维搜索使测距残差最小的 z。
+    /// 未设置 `z_range` 时 z 取信标平均高度（与其他算法一致）
+    pub fn trilateration_constrained(
+        beacons: &[Beacon],
+        signals: &SignalReadings,
+        rssi_model: &(impl DistanceEstimator + ?Sized),
+        constraints: &SolverConstraints,
+    ) -> Option<LocationResult> {
+        let mut measurements = Vec::new();
+        for beacon in beacons {
+            if let Some(rssi) = signals.get(&beacon.id)
+                && let Some(distance) = rssi_model.distance(&beacon.id, rssi)
+            {
+                measurements.push((beacon.x, beacon.y, beacon.z, distance));
+            }
+        }
+
+        if measurements.len() < 3 {
+            return None;
+        }
+
+        Self::_trilateration_constrained_impl(&measurements, constraints)
+    }
+
+    /// 按样本窗口测距并以测距标准差加权定位 - 支持 3+ 个信标
+    ///
+    /// 每个信标的 RSSI 窗口交给 [`DistanceEstimator::estimate`]，得到的距离以 1/σ² 加权求解
+    /// （见 [`LocationAlgorithm::from_ranges`]）；模型不给出标准差的信标按等权处理
+    ///
+    /// # 参数
+    /// - `beacons`: 信标列表
+    /// - `rssi_windows`: 信标 ID -> 最近的 RSSI 样本（如 [`SignalStats::rssi_windows`]）
+    /// - `estimator`: 测距模型
+    pub fn trilateration_estimated(
+        beacons: &[Beacon],
+        rssi_windows: &HashMap<String, Vec<i16>>,
+        estimator: &(impl DistanceEstimator + ?Sized),
+    ) -> Option<LocationResult> {
+        let ranges: Vec<(Beacon, f64, f64)> = beacons
+            .iter()
+            .filter_map(|beacon| {
+                let window = rssi_windows.get(&beacon.id).filter(|w| !w.is_empty())?;
+                let range = estimator.estimate(&beacon.id, window)?;
+                let sigma = range.std_dev.filter(|s| *s > 0.0).unwrap_or(1.0);
+                Some((beacon.clone(), range.distance, sigma))
+            })
+            .collect();
+        let mut result = Self::from_ranges(&ranges)?;
+        result.method = "trilateration_estimated".to_string();
+        Some(result)
+    }
+
+    /// 使用外部提供的距离定位 - 支持 3+ 个信标
+    ///
+    /// 适合自带测距的场景（UWB、超声波、厂商 SDK 给出的距离），无需伪造 RSSI。
+    /// 以 1/σ² 为权重做加权 Gauss-Newton 迭代求 (x, y)；结果可直接交给后处理流水线。
+    ///
+    /// 只求解水平位置：标签高度假定为信标按权重的平均高度，测距按此高度投影到水平面。
+    /// 标签高度与信标高度相差较大时（如信标装在天花板、标签在地面）水平位置会有偏差，
+    /// 已知标签高度时应使用 [`LocationAlgorithm::from_ranges_at_height`]。
+    ///
+    /// # 参数
+    /// - `ranges`: (信标, 距离, 距离标准差)，距离与坐标单位一致；距离为负或标准差不为正的条目被忽略
+    ///
+    /// # 返回
+    /// - 定位结果（误差为加权距离残差的均方根），或 None 如果有效距离不足 3 个或几何退化
+    pub fn from_ranges(ranges: &[(Beacon, f64, f64)]) -> Option<LocationResult> {
+        let valid = Self::_valid_ranges(ranges);
+        let weight_sum: f64 = valid.iter().map(|m| m.4).sum();
+        let z = valid.iter().map(|m| m.2 * m.4).sum::<f64>() / weight_sum;
+        Self::_from_ranges_impl(&valid, z)
+    }
+
+    /// 使用外部提供的距离和已知的标签高度定位
+    ///
+    /// 与 [`LocationAlgorithm::from_ranges`] 相同，但测距按给定高度 `z` 投影到水平面，
+    /// 结果的 z 即为 `z`
+    pub fn from_ranges_at_height(ranges: &[(Beacon, f64, f64)], z: f64) -> Option<LocationResult> {
+        Self::_from_ranges_impl(&Self::_valid_ranges(ranges), z)
+    }
+
+    /// 过滤有效测距并转换为 (x, y, z, 距离, 1/σ²)
+    fn _valid_ranges(ranges: &[(Beacon, f64, f64)]) -> Vec<(f64, f64, f64, f64, f64)> {
+        ranges
+            .iter()
+            .filter(|(_, distance, sigma)| distance.is_finite() && *distance >= 0.0 && sigma.is_finite() && *sigma > 0.0)
+            .map(|(beacon, distance, sigma)| (beacon.x, beacon.y, beacon.z, *distance, 1.0 / (sigma * sigma)))
+            .collect()
+    }
+
+    fn _from_ranges_impl(measurements: &[(f64, f64, f64, f64, f64)], z: f64) -> Option<LocationResult> {
+        if measurements.len() < 3 || !z.is_finite() {
+            return None;
+        }
+
+        let weight_sum: f64 = measurements.iter().map(|m| m.4).sum();
+        let mut x = measurements.iter().map(|m| m.0 * m.4).sum::<f64>() / weight_sum;
+        let mut y = measurements.iter().map(|m| m.1 * m.4).sum::<f64>() / weight_sum;
+
+        // 信标共线等退化几何下，信标分布的协方差行列式相对迹接近 0
+        let (sxx, sxy, syy) = measurements.iter().fold((0.0, 0.0, 0.0), |(sxx, sxy, syy), (bx, by, _, _, w)| {
+            let (dx, dy) = (bx - x, by - y);
+            (sxx + w * dx * dx, sxy + w * dx * dy, syy + w * dy * dy)
+        });
+        let trace = sxx + syy;
+        if trace <= 0.0 || sxx * syy - sxy * sxy <= 1e-9 * trace * trace {
+            return None;
+        }
+
+        // 测距投影到水平面后做加权 Gauss-Newton
+        let horizontal: Vec<(f64, f64, f64, f64)> = measurements
+            .iter()
+            .map(|(bx, by, bz, r, w)| (*bx, *by, (r * r - (z - bz).powi(2)).max(0.0).sqrt(), *w))
+            .collect();
+        for _ in 0..20 {
+            let (dx, dy) = gauss_newton_step((x, y), &horizontal)?;
+            x += dx;
+            y += dy;
+            if dx.hypot(dy) < 1e-6 {
+                break;
+            }
+        }
+
+        let sum_sq = horizontal
+            .iter()
+            .map(|(bx, by, r, w)| w * ((x - bx).hypot(y - by) - r).powi(2))
+            .sum::<f64>();
+        let error = (sum_sq / weight_sum).sqrt();
+        let confidence = (1.0 / (1.0 + error / 100.0)).min(1.0);
+
+        Some(LocationResult::new(
+            x,
+            y,
+            z,
+            confidence,
+            error,
+            "from_ranges".to_string(),
+            measurements.len(),
+        ))
+    }
+
     /// 融合多个定位结果
     ///
     /// 对多个算法的结果进行加权平均
@@ -410,6 +618,120 @@ impl LocationAlgorithm {
         ))
     }
 
+    fn _trilateration_constrained_impl(
+        measurements: &[(f64, f64, f64, f64)],
+        constraints: &SolverConstraints,
+    ) -> Option<LocationResult> {
+        let n = measurements.len() as f64;
+        let mean_z = measurements.iter().map(|(_, _, z, _)| z).sum::<f64>() / n;
+        let mut z = match constraints.z_range {
+            Some((min, max)) => (min + max) / 2.0,
+            None => mean_z,
+        };
+
+        let mut xy = None;
+        for _ in 0..10 {
+            // 固定 z，测距投影到水平面
+            let horizontal: Vec<(f64, f64, f64)> = measurements
+                .iter()
+                .map(|(bx, by, bz, r)| (*bx, *by, (r * r - (z - bz).powi(2)).max(0.0).sqrt()))
+                .collect();
+            let (x, y) = Self::_least_squares_xy(&horizontal)?;
+            xy = Some((x, y));
+
+            // 固定 (x, y)，在范围内搜索 z
+            let Some((min, max)) = constraints.z_range else {
+                break;
+            };
+            let residual = |z: f64| {
+                measurements
+                    .iter()
+                    .map(|(bx, by, bz, r)| {
+                        let d = ((x - bx).powi(2) + (y - by).powi(2) + (z - bz).powi(2)).sqrt();
+                        (d - r).powi(2)
+                    })
+                    .sum::<f64>()
+            };
+            let new_z = Self::_golden_section_min(residual, min, max);
+            if (new_z - z).abs() < 1e-3 {
+                z = new_z;
+                break;
+            }
+            z = new_z;
+        }
+
+        let (x, y) = xy?;
+        let error = (measurements
+            .iter()
+            .map(|(bx, by, bz, r)| {
+                let d = ((x - bx).powi(2) + (y - by).powi(2) + (z - bz).powi(2)).sqrt();
+                (d - r).powi(2)
+            })
+            .sum::<f64>()
+            / n)
+            .sqrt();
+        let confidence = (1.0 / (1.0 + error / 100.0)).min(1.0);
+
+        Some(LocationResult::new(
+            x,
+            y,
+            z,
+            confidence,
+            error,
+            "trilateration_constrained".to_string(),
+            measurements.len(),
+        ))
+    }
+
+    /// 线性化最小二乘求解水平位置，输入 (x, y, 水平距离)
+    pub(crate) fn _least_squares_xy(measurements: &[(f64, f64, f64)]) -> Option<(f64, f64)> {
+        if measurements.len() < 3 {
+            return None;
+        }
+
+        let (x1, y1, r1) = measurements[0];
+        let mut ata = [[0.0; 2]; 2];
+        let mut atb = [0.0; 2];
+        for &(xi, yi, ri) in &measurements[1..] {
+            let a0 = 2.0 * (xi - x1);
+            let a1 = 2.0 * (yi - y1);
+            let b = r1 * r1 - ri * ri - x1 * x1 + xi * xi - y1 * y1 + yi * yi;
+            ata[0][0] += a0 * a0;
+            ata[0][1] += a0 * a1;
+            ata[1][1] += a1 * a1;
+            atb[0] += a0 * b;
+            atb[1] += a1 * b;
+        }
+        ata[1][0] = ata[0][1];
+
+        let det = ata[0][0] * ata[1][1] - ata[0][1] * ata[1][0];
+        if det.abs() < 1e-10 {
+            return None;
+        }
+
+        let x = (atb[0] * ata[1][1] - atb[1] * ata[0][1]) / det;
+        let y = (ata[0][0] * atb[1] - ata[1][0] * atb[0]) / det;
+        Some((x, y))
+    }
+
+    /// 黄金分割搜索一维函数在 [min, max] 上的最小值点
+    fn _golden_section_min(f: impl Fn(f64) -> f64, min: f64, max: f64) -> f64 {
+        let ratio = (5_f64.sqrt() - 1.0) / 2.0;
+        let (mut lo, mut hi) = (min, max);
+        let mut c = hi - ratio * (hi - lo);
+        let mut d = lo + ratio * (hi - lo);
+        while hi - lo > 1e-4 * (1.0 + max.abs() + min.abs()) {
+            if f(c) < f(d) {
+                hi = d;
+            } else {
+                lo = c;
+            }
+            c = hi - ratio * (hi - lo);
+            d = lo + ratio * (hi - lo);
+        }
+        (lo + hi) / 2.0
+    }
+
     fn _calculate_error(measurements: &[(f64, f64, f64, f64)], x: f64, y: f64) -> f64 {
         if measurements.is_empty() {
             return 0.0;
@@ -455,6 +777,17 @@ impl KalmanFilter1D {
         }
     }
 
+    /// 设置测量噪声协方差
+    pub fn set_measurement_noise(&mut self, r: f64) {
+        self.r = r.max(f64::EPSILON);
+    }
+
+    /// 使用本次测量的噪声协方差更新滤波器
+    pub fn update_with_noise(&mut self, measurement: f64, r: f64) -> f64 {
+        self.set_measurement_noise(r);
+        self.update(measurement)
+    }
+
     /// 更新滤波器
     pub fn update(&mut self, measurement: f64) -> f64 {
         // 预测
@@ -497,12 +830,71 @@ impl KalmanFilter3D {
         )
     }
 
+    /// 使用本次测量的噪声协方差更新滤波器（三个轴相同）
+    pub fn update_with_noise(&mut self, x: f64, y: f64, z: f64, r: f64) -> (f64, f64, f64) {
+        (
+            self.x_filter.update_with_noise(x, r),
+            self.y_filter.update_with_noise(y, r),
+            self.z_filter.update_with_noise(z, r),
+        )
+    }
+
     /// 获取当前状态
     pub fn state(&self) -> (f64, f64, f64) {
         (self.x_filter.value, self.y_filter.value, self.z_filter.value)
     }
 }
 
+/// 自适应测量噪声 - 由各信标近期 RSSI 方差推导卡尔曼滤波的 R
+///
+/// 对数距离模型下，RSSI 标准差 σ 对应的距离标准差约为 d·ln10·σ/|B|，
+/// 噪声大的周期得到更大的 R，从而自动降低对该周期测量的信任。
+/// `PositioningEngine::with_adaptive_noise` 用它平滑求解前的 RSSI
+#[derive(Clone, Debug)]
+pub struct AdaptiveNoise {
+    /// 统计窗口
+    pub window: Duration,
+    /// R 的下限（方差不足或样本太少时使用）
+    pub min_r: f64,
+}
+
+impl AdaptiveNoise {
+    /// 创建自适应噪声估计
+    pub fn new(window: Duration, min_r: f64) -> Self {
+        AdaptiveNoise { window, min_r }
+    }
+
+    /// 单个信标的 RSSI 测量噪声（方差，dBm²），用于 RSSI 平滑滤波
+    pub fn rssi_noise(&self, stats: &SignalStats, beacon_id: &str) -> f64 {
+        stats
+            .for_window(beacon_id, self.window)
+            .map(|w| w.variance)
+            .unwrap_or(0.0)
+            .max(self.min_r)
+    }
+
+    /// 位置测量噪声（距离方差，坐标单位²），取参与定位信标的平均值
+    ///
+    /// 误差传播依赖对数距离模型的路径损耗指数 B，因此只接受 `RSSIModel`
+    pub fn position_noise(&self, stats: &SignalStats, beacon_ids: &[&str], rssi_model: &RSSIModel) -> f64 {
+        let variances: Vec<f64> = beacon_ids
+            .iter()
+            .filter_map(|id| stats.for_window(id, self.window))
+            .filter(|w| w.sample_count >= 2)
+            .map(|w| {
+                let distance = rssi_model.rssi_to_distance_f64(w.mean);
+                let std = distance * LN_10 * w.std_dev() / rssi_model.b.abs();
+                std * std
+            })
+            .collect();
+
+        if variances.is_empty() {
+            return self.min_r;
+        }
+        (variances.iter().sum::<f64>() / variances.len() as f64).max(self.min_r)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -516,6 +908,120 @@ mod tests {
         assert_eq!(readings.get("B1"), Some(-50));
     }
 
+    #[test]
+    fn test_trilateration_constrained_z_range() {
+        // 信标安装在 250 高度，目标位于 (300, 200, 120)
+        let target = (300.0, 200.0, 120.0);
+        let beacons: Vec<(f64, f64, f64)> = vec![
+            (0.0, 0.0, 250.0),
+            (800.0, 0.0, 250.0),
+            (0.0, 600.0, 250.0),
+            (800.0, 600.0, 250.0),
+        ];
+        let measurements: Vec<(f64, f64, f64, f64)> = beacons
+            .iter()
+            .map(|&(x, y, z)| {
+                let d = ((x - target.0).powi(2) + (y - target.1).powi(2) + (z - target.2).powi(2))
+                    .sqrt();
+                (x, y, z, d)
+            })
+            .collect();
+
+        let constraints = SolverConstraints::new().with_z_range(80.0, 150.0);
+        let result =
+            LocationAlgorithm::_trilateration_constrained_impl(&measurements, &constraints).unwrap();
+        assert!((result.x - target.0).abs() < 1.0);
+        assert!((result.y - target.1).abs() < 1.0);
+        assert!(result.z >= 80.0 && result.z <= 150.0);
+        assert!((result.z - target.2).abs() < 5.0);
+    }
+
+    #[test]
+    fn test_from_ranges_weights_by_sigma() {
+        let beacon = |id: &str, x: f64, y: f64| Beacon::new(id.to_string(), String::new(), x, y, 0.0);
+        let distance = |x: f64, y: f64| (x - 300.0_f64).hypot(y - 200.0);
+        // 第四个距离偏差 200，但标准差很大，几乎不影响结果
+        let ranges = vec![
+            (beacon("U1", 0.0, 0.0), distance(0.0, 0.0), 10.0),
+            (beacon("U2", 800.0, 0.0), distance(800.0, 0.0), 10.0),
+            (beacon("U3", 0.0, 600.0), distance(0.0, 600.0), 10.0),
+            (beacon("U4", 800.0, 600.0), distance(800.0, 600.0) + 200.0, 1000.0),
+        ];
+        let result = LocationAlgorithm::from_ranges(&ranges).unwrap();
+        assert!((result.x - 300.0).abs() < 1.0);
+        assert!((result.y - 200.0).abs() < 1.0);
+        assert_eq!(result.method, "from_ranges");
+        assert_eq!(result.beacon_count, 4);
+
+        // 无效条目被忽略；共线信标无法求解
+        let mut invalid = ranges[..2].to_vec();
+        invalid.push((beacon("U3", 0.0, 600.0), 100.0, 0.0));
+        assert!(LocationAlgorithm::from_ranges(&invalid).is_none());
+        let collinear: Vec<(Beacon, f64, f64)> =
+            [0.0, 400.0, 800.0].iter().map(|x| (beacon("L", *x, 0.0), distance(*x, 0.0), 10.0)).collect();
+        assert!(LocationAlgorithm::from_ranges(&collinear).is_none());
+
+        // 信标在天花板 (z = 300)、标签在地面：给定高度后水平位置不受高度差影响
+        let ceiling = |id: &str, x: f64, y: f64| Beacon::new(id.to_string(), String::new(), x, y, 300.0);
+        let slant = |x: f64, y: f64| distance(x, y).hypot(300.0);
+        let ranges: Vec<(Beacon, f64, f64)> = [(0.0, 0.0), (800.0, 0.0), (0.0, 600.0), (800.0, 600.0)]
+            .iter()
+            .map(|(x, y)| (ceiling("C", *x, *y), slant(*x, *y), 10.0))
+            .collect();
+        let result = LocationAlgorithm::from_ranges_at_height(&ranges, 0.0).unwrap();
+        assert!((result.x - 300.0).abs() < 1.0 && (result.y - 200.0).abs() < 1.0);
+        assert_eq!(result.z, 0.0);
+        assert!(result.error < 1.0);
+    }
+
+    #[test]
+    fn test_quick_locate_margin_confidence() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("B1".to_string(), "B1".to_string(), 0.0, 0.0, 100.0),
+            Beacon::new("B2".to_string(), "B2".to_string(), 400.0, 0.0, 100.0),
+        ]);
+
+        let clear = SignalReadings::from_pairs(vec![("B1", -55), ("B2", -65), ("X", -40)]);
+        let result = LocationAlgorithm::quick_locate(&clear, &beacons).unwrap();
+        assert_eq!(result.xyz(), (0.0, 0.0, 100.0));
+        assert!((result.confidence - 0.9).abs() < 1e-9);
+        assert_eq!(result.error, 200.0);
+
+        let ambiguous = SignalReadings::from_pairs(vec![("B1", -60), ("B2", -61)]);
+        let result = LocationAlgorithm::quick_locate(&ambiguous, &beacons).unwrap();
+        assert!(result.confidence < 0.3);
+
+        assert!(LocationAlgorithm::quick_locate(&SignalReadings::new(), &beacons).is_none());
+    }
+
+    #[test]
+    fn test_adaptive_noise_tracks_variance() {
+        use crate::algorithms::{DistanceUnit, MockClock};
+        use std::sync::Arc;
+
+        let model = RSSIModel::log_distance(-50.0, -20.0, DistanceUnit::Meter);
+        let clock = MockClock::new(10_000);
+        let mut stats = SignalStats::default().with_clock(Arc::new(clock));
+        for (i, rssi) in [-50, -50, -50, -50].iter().enumerate() {
+            stats.record_at("QUIET", *rssi, 9_000 + i as u64 * 100);
+        }
+        for (i, rssi) in [-40, -60, -40, -60].iter().enumerate() {
+            stats.record_at("NOISY", *rssi, 9_000 + i as u64 * 100);
+        }
+
+        let adaptive = AdaptiveNoise::new(Duration::from_secs(5), 0.01);
+        assert_eq!(adaptive.position_noise(&stats, &["QUIET"], &model), 0.01);
+        assert!(adaptive.position_noise(&stats, &["NOISY"], &model) > 1.0);
+        assert_eq!(adaptive.rssi_noise(&stats, "NOISY"), 100.0);
+
+        // 噪声越大，滤波器对新测量的响应越慢
+        let mut trusting = KalmanFilter1D::new(0.001, 0.1, 0.0);
+        let mut cautious = KalmanFilter1D::new(0.001, 0.1, 0.0);
+        let fast = trusting.update_with_noise(10.0, 0.01);
+        let slow = cautious.update_with_noise(10.0, 100.0);
+        assert!(fast > slow);
+    }
+
     #[test]
     fn test_kalman_filter_1d() {
         let mut filter = KalmanFilter1D::new(0.001, 0.1, 0.0);
@@ -524,4 +1030,34 @@ mod tests {
         assert!(v1 > 0.0 && v1 < 10.0);
         assert!(v2 > v1 && v2 < 10.1);
     }
+
+    #[test]
+    fn test_trilateration_estimated_weights_by_window_sigma() {
+        use crate::algorithms::DistanceUnit;
+
+        let model = RSSIModel::log_distance(-50.0, -20.0, DistanceUnit::Meter).with_sigma(4.0);
+        let beacons = vec![
+            Beacon::new("B1".to_string(), String::new(), 0.0, 0.0, 0.0),
+            Beacon::new("B2".to_string(), String::new(), 20.0, 0.0, 0.0),
+            Beacon::new("B3".to_string(), String::new(), 0.0, 20.0, 0.0),
+            Beacon::new("B4".to_string(), String::new(), 20.0, 20.0, 0.0),
+        ];
+        let rssi_at = |d: f64| model.distance_to_rssi(d).round() as i16;
+        let d = 200f64.sqrt();
+        // B4 只有一个偏差很大的样本，其余信标窗口样本多、标准差小
+        let windows: HashMap<String, Vec<i16>> = [
+            ("B1", vec![rssi_at(d); 16]),
+            ("B2", vec![rssi_at(d); 16]),
+            ("B3", vec![rssi_at(d); 16]),
+            ("B4", vec![rssi_at(d) - 10]),
+        ]
+        .into_iter()
+        .map(|(id, w)| (id.to_string(), w))
+        .collect();
+
+        let result = LocationAlgorithm::trilateration_estimated(&beacons, &windows, &model).unwrap();
+        assert_eq!(result.method, "trilateration_estimated");
+        assert_eq!(result.beacon_count, 4);
+        assert!((result.x - 10.0).abs() < 1.5 && (result.y - 10.0).abs() < 1.5, "{:?}", result.xy());
+    }
 }
@@ -10,8 +10,94 @@ pub mod location_algorithms;
 pub mod rssi_model;
 pub mod beacon;
 pub mod results;
+pub mod signal_stats;
+pub mod clock;
+pub mod reorder;
+pub mod floor_plan;
+pub mod fingerprint;
+pub mod calibration;
+pub mod handover;
+pub mod privacy;
+pub mod postprocess;
+pub mod evaluation;
+pub mod analytics;
+pub mod observation;
+pub mod device_id;
+pub mod events;
+pub mod body_shadowing;
+pub mod distance_estimator;
+pub mod altitude;
+pub mod rssi;
+pub mod zones;
+pub mod density;
+pub mod alerts;
+pub mod engine;
+pub mod fixed_solver;
+pub mod latency;
+pub mod fingerprint_store;
+pub mod fingerprint_quality;
+pub mod visibility;
+pub mod units;
+pub mod refilter;
+pub mod source_stats;
+pub mod uncertainty;
+pub mod geofence;
+pub mod registration;
+pub mod commissioning;
+pub mod swap_detection;
+pub mod debug_bundle;
+pub mod wire;
+pub mod antenna;
+pub mod cooperative;
+pub mod motion;
+pub mod profiles;
+pub mod adaptive_rate;
+pub mod drift;
 
 pub use location_algorithms::*;
 pub use rssi_model::*;
 pub use beacon::*;
 pub use results::*;
+pub use signal_stats::*;
+pub use clock::*;
+pub use reorder::*;
+pub use floor_plan::*;
+pub use fingerprint::*;
+pub use calibration::*;
+pub use handover::*;
+pub use privacy::*;
+pub use postprocess::*;
+pub use evaluation::*;
+pub use analytics::*;
+pub use observation::*;
+pub use device_id::*;
+pub use events::*;
+pub use body_shadowing::*;
+pub use distance_estimator::*;
+pub use altitude::*;
+pub use rssi::*;
+pub use zones::*;
+pub use density::*;
+pub use alerts::*;
+pub use engine::*;
+pub use fixed_solver::*;
+pub use latency::*;
+pub use fingerprint_store::*;
+pub use fingerprint_quality::*;
+pub use visibility::*;
+pub use units::*;
+pub use refilter::*;
+pub use source_stats::*;
+pub use uncertainty::*;
+pub use geofence::*;
+pub use registration::*;
+pub use commissioning::*;
+pub use swap_detection::*;
+pub use debug_bundle::*;
+pub use wire::*;
+pub use antenna::*;
+pub use cooperative::*;
+pub use motion::*;
+pub use profiles::*;
+pub use adaptive_rate::*;
+pub use drift::*;
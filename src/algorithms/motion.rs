@@ -0,0 +1,385 @@
+/// 可替换的运动模型与跟踪滤波
+///
+/// 叉车、行人和静止资产的运动规律与过程噪声差别很大，用同一组参数滤波要么太迟钝、要么太抖。
+/// `MotionModel` 描述状态 (x, y, vx, vy) 的转移与过程噪声，`MotionTracker` 是按所选模型
+/// 运行的卡尔曼滤波器，可作为后处理阶段加入流水线，每个目标使用各自的模型
+
+use crate::algorithms::postprocess::stage_time;
+use crate::algorithms::{Clock, LocationResult, PostProcessor};
+use std::fmt;
+use std::sync::Arc;
+
+/// 4x4 矩阵
+pub type Matrix4 = [[f64; 4]; 4];
+
+/// 运动模型
+pub trait MotionModel: Send + fmt::Debug {
+    /// 模型名称
+    fn name(&self) -> &str;
+
+    /// 时间间隔 `dt`（秒）内的状态转移矩阵，状态为 (x, y, vx, vy)
+    fn transition(&self, dt: f64) -> Matrix4;
+
+    /// 时间间隔 `dt`（秒）内的过程噪声协方差
+    fn process_noise(&self, dt: f64) -> Matrix4;
+
+    /// 对更新后的状态施加约束（如速度上限）
+    fn constrain(&self, _state: &mut [f64; 4]) {}
+}
+
+/// 白噪声加速度对应的过程噪声
+fn white_acceleration_noise(accel_noise: f64, dt: f64) -> Matrix4 {
+    let q = accel_noise * accel_noise;
+    let (dt2, dt3, dt4) = (dt * dt, dt * dt * dt, dt * dt * dt * dt);
+    [
+        [q * dt4 / 4.0, 0.0, q * dt3 / 2.0, 0.0],
+        [0.0, q * dt4 / 4.0, 0.0, q * dt3 / 2.0],
+        [q * dt3 / 2.0, 0.0, q * dt2, 0.0],
+        [0.0, q * dt3 / 2.0, 0.0, q * dt2],
+    ]
+}
+
+/// 匀速模型的转移矩阵，速度分量乘以 `damping`
+fn constant_velocity_transition(dt: f64, damping: f64) -> Matrix4 {
+    [
+        [1.0, 0.0, dt, 0.0],
+        [0.0, 1.0, 0.0, dt],
+        [0.0, 0.0, damping, 0.0],
+        [0.0, 0.0, 0.0, damping],
+    ]
+}
+
+/// 静止模型：位置随机游走，速度恒为 0（货架上的资产）
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConstantPosition {
+    /// 位置漂移标准差（坐标单位/√秒）
+    pub drift: f64,
+}
+
+impl MotionModel for ConstantPosition {
+    fn name(&self) -> &str {
+        "constant_position"
+    }
+
+    fn transition(&self, _dt: f64) -> Matrix4 {
+        let mut f = [[0.0; 4]; 4];
+        f[0][0] = 1.0;
+        f[1][1] = 1.0;
+        f
+    }
+
+    fn process_noise(&self, dt: f64) -> Matrix4 {
+        let mut q = [[0.0; 4]; 4];
+        q[0][0] = self.drift * self.drift * dt;
+        q[1][1] = self.drift * self.drift * dt;
+        q
+    }
+}
+
+/// 匀速模型（叉车、机器人等）
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConstantVelocity {
+    /// 加速度噪声标准差（坐标单位/秒²）
+    pub accel_noise: f64,
+}
+
+impl MotionModel for ConstantVelocity {
+    fn name(&self) -> &str {
+        "constant_velocity"
+    }
+
+    fn transition(&self, dt: f64) -> Matrix4 {
+        constant_velocity_transition(dt, 1.0)
+    }
+
+    fn process_noise(&self, dt: f64) -> Matrix4 {
+        white_acceleration_noise(self.accel_noise, dt)
+    }
+}
+
+/// 匀速转弯模型（沿固定转弯率行驶，如环形通道上的 AGV）
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConstantTurn {
+    /// 转弯率（弧度/秒，逆时针为正）
+    pub turn_rate: f64,
+    /// 加速度噪声标准差（坐标单位/秒²）
+    pub accel_noise: f64,
+}
+
+impl MotionModel for ConstantTurn {
+    fn name(&self) -> &str {
+        "constant_turn"
+    }
+
+    fn transition(&self, dt: f64) -> Matrix4 {
+        let w = self.turn_rate;
+        if w.abs() < 1e-9 {
+            return constant_velocity_transition(dt, 1.0);
+        }
+        let (s, c) = (w * dt).sin_cos();
+        [
+            [1.0, 0.0, s / w, -(1.0 - c) / w],
+            [0.0, 1.0, (1.0 - c) / w, s / w],
+            [0.0, 0.0, c, -s],
+            [0.0, 0.0, s, c],
+        ]
+    }
+
+    fn process_noise(&self, dt: f64) -> Matrix4 {
+        white_acceleration_noise(self.accel_noise, dt)
+    }
+}
+
+/// 行人模型：速度随时间衰减（走走停停），速度不超过步行上限
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pedestrian {
+    /// 加速度噪声标准差（坐标单位/秒²）
+    pub accel_noise: f64,
+    /// 速度上限（坐标单位/秒）
+    pub max_speed: f64,
+    /// 速度衰减时间常数（秒）
+    pub velocity_decay_s: f64,
+}
+
+impl Pedestrian {
+    /// 厘米坐标下的典型行人参数：加速度噪声 50 cm/s²、上限 250 cm/s、衰减 2 秒
+    pub fn walking_cm() -> Self {
+        Pedestrian {
+            accel_noise: 50.0,
+            max_speed: 250.0,
+            velocity_decay_s: 2.0,
+        }
+    }
+}
+
+impl MotionModel for Pedestrian {
+    fn name(&self) -> &str {
+        "pedestrian"
+    }
+
+    fn transition(&self, dt: f64) -> Matrix4 {
+        constant_velocity_transition(dt, (-dt / self.velocity_decay_s.max(f64::EPSILON)).exp())
+    }
+
+    fn process_noise(&self, dt: f64) -> Matrix4 {
+        white_acceleration_noise(self.accel_noise, dt)
+    }
+
+    fn constrain(&self, state: &mut [f64; 4]) {
+        let speed = state[2].hypot(state[3]);
+        if speed > self.max_speed && speed > 0.0 {
+            state[2] *= self.max_speed / speed;
+            state[3] *= self.max_speed / speed;
+        }
+    }
+}
+
+/// 按运动模型运行的二维卡尔曼跟踪器
+///
+/// 测量噪声取定位结果的 `error` 的平方（不小于 `min_measurement_std` 的平方），
+/// z 坐标原样透传
+#[derive(Debug)]
+pub struct MotionTracker {
+    model: Box<dyn MotionModel>,
+    /// 测量标准差下限
+    min_measurement_std: f64,
+    /// 状态 (x, y, vx, vy)
+    state: [f64; 4],
+    /// 状态协方差
+    covariance: Matrix4,
+    /// 上一次更新的时间（毫秒）
+    last_ms: Option<i64>,
+    /// 作为后处理阶段时计时用的时钟，None 时按结果的时间戳
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl MotionTracker {
+    /// 创建跟踪器
+    pub fn new(model: impl MotionModel + 'static) -> Self {
+        Self::with_boxed_model(Box::new(model))
+    }
+
+    /// 使用已装箱的运动模型创建跟踪器（模型在运行时选择时使用）
+    pub fn with_boxed_model(model: Box<dyn MotionModel>) -> Self {
+        MotionTracker {
+            model,
+            min_measurement_std: 1.0,
+            state: [0.0; 4],
+            covariance: [[0.0; 4]; 4],
+            last_ms: None,
+            clock: None,
+        }
+    }
+
+    /// 作为后处理阶段时按指定时钟计算时间间隔
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// 设置测量标准差下限
+    pub fn with_min_measurement_std(mut self, std: f64) -> Self {
+        self.min_measurement_std = std.abs();
+        self
+    }
+
+    /// 运动模型
+    pub fn model(&self) -> &dyn MotionModel {
+        self.model.as_ref()
+    }
+
+    /// 当前速度估计 (vx, vy)，尚未初始化时为 None
+    pub fn velocity(&self) -> Option<(f64, f64)> {
+        self.last_ms.map(|_| (self.state[2], self.state[3]))
+    }
+
+    /// 输入一个测量位置并返回滤波后的 (x, y)
+    pub fn update(&mut self, x: f64, y: f64, measurement_std: f64, timestamp_ms: i64) -> (f64, f64) {
+        let std = measurement_std.abs().max(self.min_measurement_std);
+        let r = std * std;
+        let Some(last_ms) = self.last_ms else {
+            self.state = [x, y, 0.0, 0.0];
+            self.covariance = [[r, 0.0, 0.0, 0.0], [0.0, r, 0.0, 0.0], [0.0, 0.0, r, 0.0], [0.0, 0.0, 0.0, r]];
+            self.last_ms = Some(timestamp_ms);
+            return (x, y);
+        };
+
+        // 预测（时间倒退时不外推）
+        let dt = ((timestamp_ms - last_ms) as f64 / 1000.0).max(0.0);
+        let f = self.model.transition(dt);
+        let q = self.model.process_noise(dt);
+        self.state = mat_vec(&f, &self.state);
+        self.covariance = mat_add(&mat_mul(&mat_mul(&f, &self.covariance), &transpose(&f)), &q);
+
+        // 更新：H = [I2 0]
+        let p = self.covariance;
+        let s = [[p[0][0] + r, p[0][1]], [p[1][0], p[1][1] + r]];
+        let det = s[0][0] * s[1][1] - s[0][1] * s[1][0];
+        if det.abs() > 1e-12 {
+            let s_inv = [[s[1][1] / det, -s[0][1] / det], [-s[1][0] / det, s[0][0] / det]];
+            let innovation = [x - self.state[0], y - self.state[1]];
+            let mut gain = [[0.0; 2]; 4];
+            for (i, row) in gain.iter_mut().enumerate() {
+                for (j, value) in row.iter_mut().enumerate() {
+                    *value = p[i][0] * s_inv[0][j] + p[i][1] * s_inv[1][j];
+                }
+            }
+            for (value, k) in self.state.iter_mut().zip(&gain) {
+                *value += k[0] * innovation[0] + k[1] * innovation[1];
+            }
+            let mut updated = p;
+            for (row, k) in updated.iter_mut().zip(&gain) {
+                for (j, value) in row.iter_mut().enumerate() {
+                    *value -= k[0] * p[0][j] + k[1] * p[1][j];
+                }
+            }
+            self.covariance = updated;
+        }
+        self.model.constrain(&mut self.state);
+        self.last_ms = Some(timestamp_ms);
+        (self.state[0], self.state[1])
+    }
+}
+
+impl PostProcessor for MotionTracker {
+    fn name(&self) -> &str {
+        "motion_tracker"
+    }
+
+    fn process(&mut self, mut result: LocationResult) -> Option<LocationResult> {
+        let timestamp_ms = stage_time(self.clock.as_ref(), &result).timestamp_millis();
+        let (x, y) = self.update(result.x, result.y, result.error, timestamp_ms);
+        result.x = x;
+        result.y = y;
+        Some(result)
+    }
+
+    fn reset(&mut self) {
+        self.state = [0.0; 4];
+        self.covariance = [[0.0; 4]; 4];
+        self.last_ms = None;
+    }
+
+    fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = Some(clock);
+    }
+}
+
+fn mat_mul(a: &Matrix4, b: &Matrix4) -> Matrix4 {
+    let mut out = [[0.0; 4]; 4];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    out
+}
+
+fn mat_add(a: &Matrix4, b: &Matrix4) -> Matrix4 {
+    let mut out = *a;
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value += b[i][j];
+        }
+    }
+    out
+}
+
+fn transpose(a: &Matrix4) -> Matrix4 {
+    let mut out = [[0.0; 4]; 4];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = a[j][i];
+        }
+    }
+    out
+}
+
+fn mat_vec(a: &Matrix4, v: &[f64; 4]) -> [f64; 4] {
+    std::array::from_fn(|i| (0..4).map(|k| a[i][k] * v[k]).sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(tracker: &mut MotionTracker, speed: f64) -> Vec<(f64, f64)> {
+        (0..20)
+            .map(|i| tracker.update(speed * i as f64, 0.0, 20.0, i * 1000))
+            .collect()
+    }
+
+    #[test]
+    fn test_models_differ_in_lag() {
+        // 匀速移动的目标：匀速模型跟得上，静止模型明显滞后
+        let mut moving = MotionTracker::new(ConstantVelocity { accel_noise: 5.0 });
+        let mut static_asset = MotionTracker::new(ConstantPosition { drift: 5.0 });
+        let cv = track(&mut moving, 100.0);
+        let cp = track(&mut static_asset, 100.0);
+        assert!((cv[19].0 - 1900.0).abs() < 20.0);
+        assert!(1900.0 - cp[19].0 > 100.0);
+        assert!((moving.velocity().unwrap().0 - 100.0).abs() < 5.0);
+        assert_eq!(static_asset.velocity(), Some((0.0, 0.0)));
+
+        let mut walker = MotionTracker::new(Pedestrian::walking_cm());
+        track(&mut walker, 1000.0);
+        assert!(walker.velocity().unwrap().0 <= 250.0 + 1e-9);
+    }
+
+    #[test]
+    fn test_constant_turn_transition_rotates_velocity() {
+        let model = ConstantTurn {
+            turn_rate: std::f64::consts::FRAC_PI_2,
+            accel_noise: 1.0,
+        };
+        let next = mat_vec(&model.transition(1.0), &[0.0, 0.0, 100.0, 0.0]);
+        assert!(next[2].abs() < 1e-9 && (next[3] - 100.0).abs() < 1e-9);
+        assert!((next[0] - next[1]).abs() < 1e-9);
+
+        let mut tracker = MotionTracker::with_boxed_model(Box::new(model));
+        let result = LocationResult::new(10.0, 20.0, 30.0, 0.9, 5.0, "test".to_string(), 3);
+        let filtered = tracker.process(result).unwrap();
+        assert_eq!((filtered.x, filtered.y, filtered.z), (10.0, 20.0, 30.0));
+        assert_eq!(tracker.model().name(), "constant_turn");
+    }
+}
@@ -0,0 +1,223 @@
+/// 统一观测数据
+///
+/// 扫描器、网关、回放和模拟器产生的所有输入（包括气压等辅助传感器）统一表示为 `Observation`。
+/// 引擎（`PositioningEngine::feed_observation`）和各采集环节（`SignalStats`、`ReorderBuffer`、
+/// 标定与验收会话）都以 `Observation` 为输入，`SignalMeasurement` 可直接转换后传入。
+/// 求解器使用的 `SignalReadings` 是一个历元内按信标聚合的结果，不是原始输入
+
+use crate::algorithms::{Rssi, SignalMeasurement, SignalReadings};
+use std::collections::HashMap;
+use std::fmt;
+
+/// 附加业务数据（如订单号、患者 ID），键 -> 任意 JSON 值
+///
+/// 在输入观测时附加，随定位结果原样输出，集成方无需另建映射表
+pub type Metadata = HashMap<String, serde_json::Value>;
+
+/// 观测来源
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ObservationSource {
+    /// 本地蓝牙适配器扫描
+    Scanner {
+        /// 适配器标识
+        adapter: String,
+    },
+    /// 远程网关推送
+    Gateway {
+        /// 网关标识
+        gateway: String,
+    },
+    /// 录制数据回放
+    Replay,
+    /// 模拟器
+    Simulator,
+    /// 来源未知（如由 `SignalMeasurement` 转换而来）
+    Unknown,
+}
+
+impl fmt::Display for ObservationSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObservationSource::Scanner { adapter } => write!(f, "scanner:{}", adapter),
+            ObservationSource::Gateway { gateway } => write!(f, "gateway:{}", gateway),
+            ObservationSource::Replay => write!(f, "replay"),
+            ObservationSource::Simulator => write!(f, "simulator"),
+            ObservationSource::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// 观测类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ObservationKind {
+    /// 信号强度 (dBm)
+    Rssi,
+    /// 距离（与坐标单位一致）
+    Range,
+    /// 到达角（弧度）
+    Angle,
+    /// Wi-Fi 接入点信号强度 (dBm)，`target` 为 BSSID
+    WifiRssi,
+    /// 气压 (hPa)，`target` 为气压计标识
+    Pressure,
+}
+
+/// 单条观测
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+    /// 观测来源
+    pub source: ObservationSource,
+    /// 被观测设备 ID（扫描模式下为信标 ID）
+    pub target: String,
+    /// 观测类型
+    pub kind: ObservationKind,
+    /// 观测值，单位由 `kind` 决定
+    pub value: f64,
+    /// 时间戳（毫秒，可选）
+    pub timestamp_ms: Option<u64>,
+    /// 附加业务数据
+    pub metadata: Metadata,
+}
+
+impl Observation {
+    /// 创建观测
+    pub fn new(
+        source: ObservationSource,
+        target: impl Into<String>,
+        kind: ObservationKind,
+        value: f64,
+        timestamp_ms: Option<u64>,
+    ) -> Self {
+        Observation {
+            source,
+            target: target.into(),
+            kind,
+            value,
+            timestamp_ms,
+            metadata: Metadata::new(),
+        }
+    }
+
+    /// 附加一项业务数据
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// 创建 RSSI 观测
+    pub fn rssi(source: ObservationSource, target: impl Into<String>, rssi: impl Into<Rssi>, timestamp_ms: Option<u64>) -> Self {
+        Self::new(source, target, ObservationKind::Rssi, f64::from(rssi.into()), timestamp_ms)
+    }
+
+    /// 创建距离观测
+    pub fn range(source: ObservationSource, target: impl Into<String>, distance: f64, timestamp_ms: Option<u64>) -> Self {
+        Self::new(source, target, ObservationKind::Range, distance, timestamp_ms)
+    }
+
+    /// 创建 Wi-Fi RSSI 观测
+    pub fn wifi(source: ObservationSource, bssid: impl Into<String>, rssi: impl Into<Rssi>, timestamp_ms: Option<u64>) -> Self {
+        Self::new(source, bssid, ObservationKind::WifiRssi, f64::from(rssi.into()), timestamp_ms)
+    }
+
+    /// 创建到达角观测
+    pub fn angle(source: ObservationSource, target: impl Into<String>, angle_rad: f64, timestamp_ms: Option<u64>) -> Self {
+        Self::new(source, target, ObservationKind::Angle, angle_rad, timestamp_ms)
+    }
+
+    /// 转换为信号测量（仅 RSSI 观测）
+    pub fn to_signal_measurement(&self) -> Option<SignalMeasurement> {
+        if self.kind != ObservationKind::Rssi {
+            return None;
+        }
+        Some(SignalMeasurement {
+            beacon_id: self.target.clone(),
+            rssi: self.value.round() as i16,
+            timestamp_ms: self.timestamp_ms,
+        })
+    }
+}
+
+impl From<SignalMeasurement> for Observation {
+    fn from(m: SignalMeasurement) -> Self {
+        Observation::rssi(ObservationSource::Unknown, m.beacon_id, m.rssi, m.timestamp_ms)
+    }
+}
+
+impl From<&SignalMeasurement> for Observation {
+    fn from(m: &SignalMeasurement) -> Self {
+        Observation::rssi(ObservationSource::Unknown, m.beacon_id.clone(), m.rssi, m.timestamp_ms)
+    }
+}
+
+impl SignalReadings {
+    /// 从观测列表创建信号集合
+    ///
+    /// 只使用蓝牙 RSSI 观测；同一信标有多条观测时取时间戳最新的一条
+    pub fn from_observations(observations: &[Observation]) -> Self {
+        Self::latest_of_kind(observations, ObservationKind::Rssi)
+    }
+
+    /// 从观测列表创建 Wi-Fi 信号集合（BSSID -> RSSI），规则同 `from_observations`
+    pub fn from_wifi_observations(observations: &[Observation]) -> Self {
+        Self::latest_of_kind(observations, ObservationKind::WifiRssi)
+    }
+
+    fn latest_of_kind(observations: &[Observation], kind: ObservationKind) -> Self {
+        let mut latest: HashMap<&str, &Observation> = HashMap::new();
+        for obs in observations.iter().filter(|o| o.kind == kind) {
+            let replace = latest
+                .get(obs.target.as_str())
+                .is_none_or(|prev| obs.timestamp_ms.unwrap_or(0) >= prev.timestamp_ms.unwrap_or(0));
+            if replace {
+                latest.insert(obs.target.as_str(), obs);
+            }
+        }
+
+        let mut readings = SignalReadings::new();
+        for (id, obs) in latest {
+            readings.add(id.to_string(), obs.value.round() as i16);
+        }
+        readings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readings_from_observations_uses_latest_rssi() {
+        let scanner = ObservationSource::Scanner {
+            adapter: "hci0".to_string(),
+        };
+        let observations = vec![
+            Observation::rssi(scanner.clone(), "B1", -70, Some(1_000)),
+            Observation::rssi(scanner.clone(), "B1", -60, Some(2_000)),
+            Observation::rssi(ObservationSource::Replay, "B2", -65, Some(1_500)),
+            Observation::range(scanner.clone(), "B3", 250.0, Some(1_500)),
+            Observation::wifi(scanner, "aa:bb:cc:00:00:01", -55, Some(1_500)),
+        ];
+
+        let readings = SignalReadings::from_observations(&observations);
+        assert_eq!(readings.count(), 2);
+        assert_eq!(readings.get("B1"), Some(-60));
+        assert!(!readings.contains("B3"));
+
+        let wifi = SignalReadings::from_wifi_observations(&observations);
+        assert_eq!(wifi.get("aa:bb:cc:00:00:01"), Some(-55));
+        assert_eq!(wifi.count(), 1);
+    }
+
+    #[test]
+    fn test_measurement_round_trip() {
+        let obs: Observation = SignalMeasurement::with_timestamp("B1".to_string(), -55, 42).into();
+        assert_eq!(obs.source, ObservationSource::Unknown);
+        assert_eq!(obs.kind, ObservationKind::Rssi);
+
+        let back = obs.to_signal_measurement().unwrap();
+        assert_eq!((back.beacon_id.as_str(), back.rssi, back.timestamp_ms), ("B1", -55, Some(42)));
+        assert!(Observation::angle(ObservationSource::Simulator, "B1", 0.5, None)
+            .to_signal_measurement()
+            .is_none());
+    }
+}
@@ -0,0 +1,583 @@
+/// 定位结果后处理流水线
+///
+/// 定位结果在输出前依次经过若干处理阶段（如坐标量化），
+/// 每个阶段可以修改结果或将其丢弃
+
+use crate::algorithms::{BlunavEvent, Clock, EventBus, LocationResult};
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 后处理阶段
+pub trait PostProcessor: Send {
+    /// 阶段名称
+    fn name(&self) -> &str;
+
+    /// 处理一个结果，返回 None 表示丢弃
+    fn process(&mut self, result: LocationResult) -> Option<LocationResult>;
+
+    /// 重置内部状态
+    fn reset(&mut self) {}
+
+    /// 使用指定的时钟计时，与时间相关的阶段（限速、停留判定、跟踪滤波）需要实现；
+    /// 未设置时钟时按结果的时间戳计时
+    fn set_clock(&mut self, _clock: Arc<dyn Clock>) {}
+}
+
+/// 阶段计时用的时间：设置了时钟时取时钟时间，否则取结果的时间戳
+pub(crate) fn stage_time(clock: Option<&Arc<dyn Clock>>, result: &LocationResult) -> DateTime<Utc> {
+    clock
+        .and_then(|clock| DateTime::from_timestamp_millis(clock.now_ms() as i64))
+        .unwrap_or(result.timestamp)
+}
+
+/// 后处理流水线 - 按添加顺序执行各阶段
+#[derive(Default)]
+pub struct PostProcessPipeline {
+    stages: Vec<Box<dyn PostProcessor>>,
+    /// 各阶段共用的时钟
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl PostProcessPipeline {
+    /// 创建空流水线
+    pub fn new() -> Self {
+        PostProcessPipeline { stages: Vec::new(), clock: None }
+    }
+
+    /// 使用指定的时钟（构建器风格），之后添加的阶段同样使用该时钟
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.set_clock(clock);
+        self
+    }
+
+    /// 为所有阶段设置时钟
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        for stage in &mut self.stages {
+            stage.set_clock(clock.clone());
+        }
+        self.clock = Some(clock);
+    }
+
+    /// 添加阶段（构建器风格）
+    pub fn with_stage(mut self, stage: impl PostProcessor + 'static) -> Self {
+        self.add_stage(stage);
+        self
+    }
+
+    /// 添加阶段
+    pub fn add_stage(&mut self, mut stage: impl PostProcessor + 'static) {
+        if let Some(clock) = &self.clock {
+            stage.set_clock(clock.clone());
+        }
+        self.stages.push(Box::new(stage));
+    }
+
+    /// 各阶段名称
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.stages.iter().map(|s| s.name()).collect()
+    }
+
+    /// 依次执行所有阶段
+    pub fn process(&mut self, result: LocationResult) -> Option<LocationResult> {
+        self.stages
+            .iter_mut()
+            .try_fold(result, |result, stage| stage.process(result))
+    }
+
+    /// 重置所有阶段
+    pub fn reset(&mut self) {
+        for stage in &mut self.stages {
+            stage.reset();
+        }
+    }
+}
+
+/// 坐标网格量化 - 将坐标吸附到网格上并带有滞回
+///
+/// 只有当原始坐标偏离上次输出的网格点超过 `(0.5 + hysteresis) * cell_size` 时
+/// 才移动到新的网格点，避免在网格边界附近来回跳动
+#[derive(Clone, Debug)]
+pub struct GridQuantizer {
+    /// 网格尺寸（与坐标单位一致，如 10 厘米）
+    cell_size: f64,
+    /// 滞回比例（相对网格尺寸）
+    hysteresis: f64,
+    /// 是否同时量化 z
+    quantize_z: bool,
+    /// 上次输出的坐标
+    last: Option<(f64, f64, f64)>,
+}
+
+impl GridQuantizer {
+    /// 创建量化阶段
+    ///
+    /// # 参数
+    /// - `cell_size`: 网格尺寸
+    /// - `hysteresis`: 滞回比例，0.0 表示普通四舍五入
+    pub fn new(cell_size: f64, hysteresis: f64) -> Self {
+        GridQuantizer {
+            cell_size: cell_size.abs(),
+            hysteresis: hysteresis.max(0.0),
+            quantize_z: false,
+            last: None,
+        }
+    }
+
+    /// 同时量化 z 坐标
+    pub fn with_z(mut self) -> Self {
+        self.quantize_z = true;
+        self
+    }
+
+    fn snap(&self, value: f64, last: Option<f64>) -> f64 {
+        if self.cell_size <= 0.0 {
+            return value;
+        }
+        if let Some(last) = last
+            && (value - last).abs() <= (0.5 + self.hysteresis) * self.cell_size
+        {
+            return last;
+        }
+        (value / self.cell_size).round() * self.cell_size
+    }
+}
+
+impl PostProcessor for GridQuantizer {
+    fn name(&self) -> &str {
+        "grid_quantizer"
+    }
+
+    fn process(&mut self, mut result: LocationResult) -> Option<LocationResult> {
+        let x = self.snap(result.x, self.last.map(|l| l.0));
+        let y = self.snap(result.y, self.last.map(|l| l.1));
+        let z = if self.quantize_z {
+            self.snap(result.z, self.last.map(|l| l.2))
+        } else {
+            result.z
+        };
+        self.last = Some((x, y, z));
+        result.x = x;
+        result.y = y;
+        result.z = z;
+        Some(result)
+    }
+
+    fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+/// 定位结果被拒绝的原因
+#[derive(Clone, Debug, PartialEq)]
+pub enum RejectReason {
+    /// 参与定位的信标数不足
+    TooFewBeacons {
+        /// 实际数量
+        count: usize,
+        /// 要求的最小数量
+        min: usize,
+    },
+    /// 置信度过低
+    LowConfidence {
+        /// 实际置信度
+        confidence: f64,
+        /// 要求的最小置信度
+        min: f64,
+    },
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RejectReason::TooFewBeacons { count, min } => {
+                write!(f, "信标数不足 ({} < {})", count, min)
+            }
+            RejectReason::LowConfidence { confidence, min } => {
+                write!(f, "置信度过低 ({:.2} < {:.2})", confidence, min)
+            }
+        }
+    }
+}
+
+/// 输出门限统计
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GateStats {
+    /// 通过的结果数
+    pub accepted: usize,
+    /// 因信标数不足被拒绝的结果数
+    pub rejected_too_few_beacons: usize,
+    /// 因置信度过低被拒绝的结果数
+    pub rejected_low_confidence: usize,
+}
+
+impl GateStats {
+    /// 被拒绝的结果总数
+    pub fn rejected(&self) -> usize {
+        self.rejected_too_few_beacons + self.rejected_low_confidence
+    }
+
+    /// 记录一次门限检查的结果
+    pub fn record(&mut self, outcome: &Result<(), RejectReason>) {
+        match outcome {
+            Ok(()) => self.accepted += 1,
+            Err(RejectReason::TooFewBeacons { .. }) => self.rejected_too_few_beacons += 1,
+            Err(RejectReason::LowConfidence { .. }) => self.rejected_low_confidence += 1,
+        }
+    }
+}
+
+/// 输出门限 - 丢弃信标数或置信度不足的结果
+///
+/// 被拒绝的结果不进入输出流；若设置了事件总线，则以 `FixRejected` 事件发出
+#[derive(Clone, Debug)]
+pub struct OutputGate {
+    /// 最少信标数
+    min_beacons: usize,
+    /// 最小置信度
+    min_confidence: f64,
+    /// 统计
+    stats: GateStats,
+    /// 事件总线
+    events: Option<EventBus>,
+}
+
+impl OutputGate {
+    /// 创建门限阶段
+    pub fn new(min_beacons: usize, min_confidence: f64) -> Self {
+        OutputGate {
+            min_beacons,
+            min_confidence: min_confidence.clamp(0.0, 1.0),
+            stats: GateStats::default(),
+            events: None,
+        }
+    }
+
+    /// 将被拒绝的结果发送到事件总线
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// 检查结果是否满足门限
+    pub fn check(&self, result: &LocationResult) -> Result<(), RejectReason> {
+        if result.beacon_count < self.min_beacons {
+            return Err(RejectReason::TooFewBeacons {
+                count: result.beacon_count,
+                min: self.min_beacons,
+            });
+        }
+        if result.confidence < self.min_confidence {
+            return Err(RejectReason::LowConfidence {
+                confidence: result.confidence,
+                min: self.min_confidence,
+            });
+        }
+        Ok(())
+    }
+
+    /// 统计信息
+    pub fn stats(&self) -> GateStats {
+        self.stats
+    }
+}
+
+impl PostProcessor for OutputGate {
+    fn name(&self) -> &str {
+        "output_gate"
+    }
+
+    fn process(&mut self, result: LocationResult) -> Option<LocationResult> {
+        let outcome = self.check(&result);
+        self.stats.record(&outcome);
+        match outcome {
+            Ok(()) => Some(result),
+            Err(reason) => {
+                if let Some(events) = &self.events {
+                    events.emit(BlunavEvent::FixRejected { result, reason });
+                }
+                None
+            }
+        }
+    }
+}
+
+/// 候选停留：(开始时间, 坐标和, 结果数)
+type DwellCandidate = (DateTime<Utc>, (f64, f64, f64), usize);
+
+/// 稳定位置 - 估计值在半径内停留足够久后才输出新的稳定位置
+///
+/// 用于自助终端触发、计费区域等不能对短暂偏移做出反应的应用。
+/// 默认原样传递每个结果，稳定位置变化时以 `PositionSettled` 事件发出；
+/// 也可设置为只输出稳定位置
+#[derive(Clone, Debug)]
+pub struct SettledPosition {
+    /// 停留半径
+    radius: f64,
+    /// 最短停留时间
+    dwell: Duration,
+    /// 是否只输出稳定位置
+    settled_only: bool,
+    /// 候选停留
+    candidate: Option<DwellCandidate>,
+    /// 当前稳定位置
+    settled: Option<LocationResult>,
+    /// 事件总线
+    events: Option<EventBus>,
+    /// 计时用的时钟，None 时按结果的时间戳
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl SettledPosition {
+    /// 创建稳定位置阶段
+    ///
+    /// # 参数
+    /// - `radius`: 停留半径（坐标单位）
+    /// - `dwell`: 在半径内停留多久才视为稳定
+    pub fn new(radius: f64, dwell: Duration) -> Self {
+        SettledPosition {
+            radius: radius.abs(),
+            dwell,
+            settled_only: false,
+            candidate: None,
+            settled: None,
+            events: None,
+            clock: None,
+        }
+    }
+
+    /// 只输出稳定位置：稳定位置变化时输出一次，其余结果丢弃
+    pub fn with_settled_only(mut self) -> Self {
+        self.settled_only = true;
+        self
+    }
+
+    /// 稳定位置变化时发送 `PositionSettled` 事件
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// 按指定时钟计算停留时间
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// 当前稳定位置
+    pub fn settled(&self) -> Option<&LocationResult> {
+        self.settled.as_ref()
+    }
+
+    /// 输入一个结果
+    ///
+    /// # 返回
+    /// - 新的稳定位置，或 None 如果稳定位置没有变化
+    pub fn update(&mut self, result: &LocationResult) -> Option<LocationResult> {
+        let now = stage_time(self.clock.as_ref(), result);
+        let (start, sum, count) = match self.candidate {
+            Some((start, sum, count)) => {
+                let n = count as f64;
+                let center = (sum.0 / n, sum.1 / n);
+                let distance = ((result.x - center.0).powi(2) + (result.y - center.1).powi(2)).sqrt();
+                if distance <= self.radius {
+                    (start, (sum.0 + result.x, sum.1 + result.y, sum.2 + result.z), count + 1)
+                } else {
+                    (now, result.xyz(), 1)
+                }
+            }
+            None => (now, result.xyz(), 1),
+        };
+        self.candidate = Some((start, sum, count));
+
+        let dwelled = (now - start).to_std().unwrap_or_default();
+        if dwelled < self.dwell {
+            return None;
+        }
+        let n = count as f64;
+        let mut settled = result.clone();
+        settled.x = sum.0 / n;
+        settled.y = sum.1 / n;
+        settled.z = sum.2 / n;
+        settled.method = format!("{}+settled", result.method);
+
+        // 仍停留在当前稳定位置附近时不重复输出
+        if self.settled.as_ref().is_some_and(|s| s.distance_2d_to(&settled) <= self.radius) {
+            return None;
+        }
+        self.settled = Some(settled.clone());
+        if let Some(events) = &self.events {
+            events.emit(BlunavEvent::PositionSettled(settled.clone()));
+        }
+        Some(settled)
+    }
+}
+
+impl PostProcessor for SettledPosition {
+    fn name(&self) -> &str {
+        "settled_position"
+    }
+
+    fn process(&mut self, result: LocationResult) -> Option<LocationResult> {
+        let settled = self.update(&result);
+        if self.settled_only { settled } else { Some(result) }
+    }
+
+    fn reset(&mut self) {
+        self.candidate = None;
+        self.settled = None;
+    }
+
+    fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = Some(clock);
+    }
+}
+
+/// 输出限速 - 与上次输出间隔不足 `min_interval` 的结果被丢弃
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    /// 最小输出间隔
+    min_interval: Duration,
+    /// 上次输出的时间
+    last: Option<DateTime<Utc>>,
+    /// 计时用的时钟，None 时按结果的时间戳
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl RateLimiter {
+    /// 创建限速阶段
+    pub fn new(min_interval: Duration) -> Self {
+        RateLimiter { min_interval, last: None, clock: None }
+    }
+
+    /// 按指定时钟计算输出间隔
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// 最小输出间隔
+    pub fn min_interval(&self) -> Duration {
+        self.min_interval
+    }
+}
+
+impl PostProcessor for RateLimiter {
+    fn name(&self) -> &str {
+        "rate_limiter"
+    }
+
+    fn process(&mut self, result: LocationResult) -> Option<LocationResult> {
+        let now = stage_time(self.clock.as_ref(), &result);
+        if let Some(last) = self.last
+            && (now - last).to_std().unwrap_or_default() < self.min_interval
+        {
+            return None;
+        }
+        self.last = Some(now);
+        Some(result)
+    }
+
+    fn reset(&mut self) {
+        self.last = None;
+    }
+
+    fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = Some(clock);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(x: f64, y: f64) -> LocationResult {
+        LocationResult::new(x, y, 100.0, 0.8, 10.0, "m".to_string(), 3)
+    }
+
+    #[test]
+    fn test_grid_quantizer_hysteresis() {
+        let mut pipeline = PostProcessPipeline::new().with_stage(GridQuantizer::new(10.0, 0.25));
+        assert_eq!(pipeline.stage_names(), vec!["grid_quantizer"]);
+
+        let first = pipeline.process(fix(123.4, 56.7)).unwrap();
+        assert_eq!(first.xy(), (120.0, 60.0));
+
+        // 在滞回范围内抖动，输出不变
+        let jitter = pipeline.process(fix(126.0, 53.0)).unwrap();
+        assert_eq!(jitter.xy(), (120.0, 60.0));
+
+        // 超出滞回范围，移动到新的网格点
+        let moved = pipeline.process(fix(128.0, 60.0)).unwrap();
+        assert_eq!(moved.xy(), (130.0, 60.0));
+        assert_eq!(moved.z, 100.0);
+    }
+
+    #[test]
+    fn test_settled_position_ignores_excursions() {
+        let at = |x: f64, second: i64| {
+            let mut result = fix(x, 0.0);
+            result.timestamp = DateTime::from_timestamp(1_700_000_000 + second, 0).unwrap();
+            result
+        };
+        let mut stage = SettledPosition::new(50.0, Duration::from_secs(5)).with_settled_only();
+
+        assert!(stage.process(at(0.0, 0)).is_none());
+        assert!(stage.process(at(20.0, 3)).is_none());
+        let settled = stage.process(at(10.0, 6)).unwrap();
+        assert_eq!(settled.x, 10.0);
+        assert!(stage.process(at(5.0, 8)).is_none());
+
+        // 短暂偏移不改变稳定位置
+        assert!(stage.process(at(400.0, 9)).is_none());
+        assert!(stage.process(at(0.0, 10)).is_none());
+        assert_eq!(stage.settled().unwrap().x, 10.0);
+
+        // 在新位置停留足够久后才更新
+        assert!(stage.process(at(800.0, 11)).is_none());
+        assert!(stage.process(at(800.0, 17)).is_some());
+    }
+
+    #[test]
+    fn test_output_gate_rejects_and_counts() {
+        let events = EventBus::default();
+        let mut rejected = events.subscribe();
+        let mut gate = OutputGate::new(3, 0.5).with_events(events);
+
+        assert!(gate.process(fix(0.0, 0.0)).is_some());
+        let mut weak = fix(0.0, 0.0);
+        weak.confidence = 0.2;
+        assert!(gate.process(weak).is_none());
+        let mut sparse = fix(0.0, 0.0);
+        sparse.beacon_count = 2;
+        assert!(gate.process(sparse).is_none());
+
+        let stats = gate.stats();
+        assert_eq!((stats.accepted, stats.rejected()), (1, 2));
+        assert_eq!(stats.rejected_low_confidence, 1);
+
+        let BlunavEvent::FixRejected { reason, .. } = rejected.try_recv().unwrap() else {
+            panic!("应为 FixRejected 事件");
+        };
+        assert!(matches!(reason, RejectReason::LowConfidence { .. }));
+    }
+
+    #[test]
+    fn test_pipeline_clock_drives_time_dependent_stages() {
+        use crate::algorithms::MockClock;
+
+        // 结果的时间戳几乎相同，计时只跟随模拟时钟
+        let clock = MockClock::new(1_700_000_000_000);
+        let mut pipeline = PostProcessPipeline::new()
+            .with_clock(Arc::new(clock.clone()))
+            .with_stage(RateLimiter::new(Duration::from_secs(1)))
+            .with_stage(SettledPosition::new(30.0, Duration::from_secs(5)).with_settled_only());
+        assert!(pipeline.process(fix(100.0, 100.0)).is_none());
+        clock.advance(Duration::from_millis(500));
+        assert!(pipeline.process(fix(100.0, 100.0)).is_none());
+        clock.advance(Duration::from_secs(5));
+        let settled = pipeline.process(fix(100.0, 100.0)).unwrap();
+        assert_eq!(settled.xy(), (100.0, 100.0));
+    }
+}
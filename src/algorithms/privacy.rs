@@ -0,0 +1,191 @@
+/// 隐私模式 - 设备标识脱敏
+///
+/// 在缓存、存储或发布之前对设备标识进行处理：
+/// - 加盐哈希：SHA-256(部署盐值 + MAC)，同一部署内稳定、跨部署不可关联
+/// - 截断：只保留地址前若干字节（厂商前缀）
+/// - 可选隐藏设备名称
+
+use crate::algorithms::{Beacon, DeviceId, Observation, SignalMeasurement};
+use sha2::{Digest, Sha256};
+
+/// 标识处理方式
+#[derive(Clone, Debug, PartialEq)]
+pub enum IdentifierPolicy {
+    /// 不处理
+    Plain,
+    /// 加盐 SHA-256 哈希，保留前 `length` 个十六进制字符
+    ///
+    /// 哈希输入为盐值长度（8 字节小端）、盐值、规范化标识，盐值与标识的边界不会混淆
+    Hash {
+        /// 部署盐值
+        salt: String,
+        /// 输出长度（十六进制字符，最多 64）
+        length: usize,
+    },
+    /// 保留前 `keep` 个字节，其余以 `XX` 替换（至少替换一个字节）；
+    /// 无法识别为 MAC 或 UUID 的标识全部替换为 `X`
+    Truncate {
+        /// 保留的字节数
+        keep: usize,
+    },
+}
+
+/// 隐私模式配置
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrivacyMode {
+    /// 标识处理方式
+    pub identifiers: IdentifierPolicy,
+    /// 是否隐藏设备名称
+    pub suppress_names: bool,
+}
+
+impl PrivacyMode {
+    /// 关闭隐私处理
+    pub fn disabled() -> Self {
+        PrivacyMode {
+            identifiers: IdentifierPolicy::Plain,
+            suppress_names: false,
+        }
+    }
+
+    /// 加盐哈希并隐藏名称
+    pub fn hashed(salt: impl Into<String>) -> Self {
+        PrivacyMode {
+            identifiers: IdentifierPolicy::Hash {
+                salt: salt.into(),
+                length: 16,
+            },
+            suppress_names: true,
+        }
+    }
+
+    /// 截断地址并隐藏名称
+    pub fn truncated(keep: usize) -> Self {
+        PrivacyMode {
+            identifiers: IdentifierPolicy::Truncate { keep },
+            suppress_names: true,
+        }
+    }
+
+    /// 处理设备标识
+    ///
+    /// 标识先按 [`DeviceId`] 规范化，同一设备的不同写法得到相同结果
+    pub fn anonymize_id(&self, id: &str) -> String {
+        match &self.identifiers {
+            IdentifierPolicy::Plain => id.to_string(),
+            IdentifierPolicy::Hash { salt, length } => {
+                let normalized = DeviceId::normalized(id);
+                let mut hasher = Sha256::new();
+                hasher.update((salt.len() as u64).to_le_bytes());
+                hasher.update(salt.as_bytes());
+                hasher.update(normalized.as_bytes());
+                let digest = hasher.finalize();
+                let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+                hex[..(*length).min(hex.len())].to_string()
+            }
+            IdentifierPolicy::Truncate { keep } => match DeviceId::parse(id) {
+                DeviceId::Mac(bytes) => truncate_bytes(&bytes, *keep).join(":"),
+                DeviceId::Uuid(bytes) => {
+                    let hex = truncate_bytes(&bytes, *keep).concat();
+                    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+                }
+                DeviceId::Other(other) => "X".repeat(other.chars().count()),
+            },
+        }
+    }
+
+    /// 处理设备名称
+    pub fn anonymize_name(&self, name: &str) -> String {
+        if self.suppress_names {
+            String::new()
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// 处理信号测量
+    pub fn apply_to_measurement(&self, measurement: &SignalMeasurement) -> SignalMeasurement {
+        SignalMeasurement {
+            beacon_id: self.anonymize_id(&measurement.beacon_id),
+            ..measurement.clone()
+        }
+    }
+
+    /// 处理观测（目标标识脱敏，其余字段不变）
+    pub fn apply_to_observation(&self, observation: &Observation) -> Observation {
+        Observation {
+            target: self.anonymize_id(&observation.target),
+            ..observation.clone()
+        }
+    }
+
+    /// 处理信标定义（保证与处理后的测量仍能匹配）
+    pub fn apply_to_beacon(&self, beacon: &Beacon) -> Beacon {
+        Beacon {
+            id: self.anonymize_id(&beacon.id),
+            name: self.anonymize_name(&beacon.name),
+            aliases: beacon.aliases.iter().map(|a| self.anonymize_id(a)).collect(),
+            name_match: if self.suppress_names { None } else { beacon.name_match.clone() },
+            ..beacon.clone()
+        }
+    }
+}
+
+impl Default for PrivacyMode {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+/// 保留前 `keep` 个字节的十六进制表示，其余替换为 `XX`；至少替换最后一个字节
+fn truncate_bytes(bytes: &[u8], keep: usize) -> Vec<String> {
+    let keep = keep.min(bytes.len().saturating_sub(1));
+    bytes
+        .iter()
+        .enumerate()
+        .map(|(i, b)| if i < keep { format!("{:02X}", b) } else { "XX".to_string() })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_mode_is_salted_and_stable() {
+        let mode = PrivacyMode::hashed("site-a");
+        let id1 = mode.anonymize_id("20:a7:16:5e:c5:d6");
+        let id2 = mode.anonymize_id("20:A7:16:5E:C5:D6");
+        assert_eq!(id1, id2);
+        assert_eq!(id1, mode.anonymize_id("20-a7-16-5e-c5-d6"));
+        assert_eq!(id1, mode.anonymize_id("20A7165EC5D6"));
+        assert_eq!(id1.len(), 16);
+        assert_ne!(id1, PrivacyMode::hashed("site-b").anonymize_id("20:A7:16:5E:C5:D6"));
+        // 盐值与标识的拼接边界不同，结果也不同
+        assert_ne!(PrivacyMode::hashed("a").anonymize_id("bc"), PrivacyMode::hashed("ab").anonymize_id("c"));
+        assert_eq!(mode.anonymize_name("RFstar_C5D6"), "");
+    }
+
+    #[test]
+    fn test_truncate_mode() {
+        let mode = PrivacyMode::truncated(3);
+        assert_eq!(mode.anonymize_id("20:A7:16:5E:C5:D6"), "20:A7:16:XX:XX:XX");
+        assert_eq!(mode.anonymize_id("20a7165ec5d6"), "20:A7:16:XX:XX:XX");
+        assert_eq!(
+            mode.anonymize_id("{12345678-9abc-def0-1234-56789abcdef0}"),
+            "123456XX-XXXX-XXXX-XXXX-XXXXXXXXXXXX"
+        );
+        assert_eq!(PrivacyMode::truncated(10).anonymize_id("20:A7:16:5E:C5:D6"), "20:A7:16:5E:C5:XX");
+        assert_eq!(mode.anonymize_id("tag-7"), "XXXXX");
+    }
+
+    #[test]
+    fn test_beacon_name_pattern_suppressed() {
+        let beacon = Beacon::new("B1".to_string(), "RFstar_C5D6".to_string(), 0.0, 0.0, 0.0).with_name_exact("RFstar_C5D6");
+        let hidden = PrivacyMode::hashed("site-a").apply_to_beacon(&beacon);
+        assert_eq!(hidden.name, "");
+        assert!(hidden.name_match.is_none());
+        let shown = PrivacyMode::disabled().apply_to_beacon(&beacon);
+        assert!(shown.name_match.is_some());
+    }
+}
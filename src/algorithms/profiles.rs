@@ -0,0 +1,346 @@
+/// 按资产类别配置的处理参数
+///
+/// 同一网关下的标签可能分属托盘、人员、机器人等不同类别，各自需要不同的运动模型、
+/// 输出速率、置信度门限和关注区域。资产类别（`AssetProfile`）按标识规则分配给设备，
+/// `ProfiledPipelines` 为每个设备按其类别建立独立的后处理流水线
+///
+/// 配置可从 JSON 加载：
+/// ```json
+/// {
+///   "default": "default",
+///   "profiles": [
+///     {"name": "default"},
+///     {"name": "pallet", "motion": {"type": "constant_position", "drift": 5.0}, "min_output_interval_ms": 10000},
+///     {"name": "person", "motion": {"type": "pedestrian", "accel_noise": 50.0, "max_speed": 250.0, "velocity_decay_s": 2.0}, "zones": ["ward"]}
+///   ],
+///   "assignments": [
+///     {"name_pattern": "^PLT-", "profile": "pallet"},
+///     {"address_prefix": "C0:FF", "profile": "person"}
+///   ]
+/// }
+/// ```
+
+use crate::algorithms::{
+    ConstantPosition, ConstantTurn, ConstantVelocity, LocationResult, MotionModel, MotionTracker, OutputGate, Pedestrian,
+    PostProcessPipeline, RateLimiter, RegistrationRule,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// 运动模型配置
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MotionModelKind {
+    /// 参见 [`ConstantPosition`]
+    ConstantPosition {
+        /// 位置漂移标准差
+        drift: f64,
+    },
+    /// 参见 [`ConstantVelocity`]
+    ConstantVelocity {
+        /// 加速度噪声标准差
+        accel_noise: f64,
+    },
+    /// 参见 [`ConstantTurn`]
+    ConstantTurn {
+        /// 转弯率（弧度/秒）
+        turn_rate: f64,
+        /// 加速度噪声标准差
+        accel_noise: f64,
+    },
+    /// 参见 [`Pedestrian`]
+    Pedestrian {
+        /// 加速度噪声标准差
+        accel_noise: f64,
+        /// 速度上限
+        max_speed: f64,
+        /// 速度衰减时间常数（秒）
+        velocity_decay_s: f64,
+    },
+}
+
+impl MotionModelKind {
+    /// 创建运动模型
+    pub fn build(&self) -> Box<dyn MotionModel> {
+        match *self {
+            MotionModelKind::ConstantPosition { drift } => Box::new(ConstantPosition { drift }),
+            MotionModelKind::ConstantVelocity { accel_noise } => Box::new(ConstantVelocity { accel_noise }),
+            MotionModelKind::ConstantTurn { turn_rate, accel_noise } => Box::new(ConstantTurn { turn_rate, accel_noise }),
+            MotionModelKind::Pedestrian {
+                accel_noise,
+                max_speed,
+                velocity_decay_s,
+            } => Box::new(Pedestrian {
+                accel_noise,
+                max_speed,
+                velocity_decay_s,
+            }),
+        }
+    }
+}
+
+/// 资产类别
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssetProfile {
+    /// 类别名称
+    pub name: String,
+    /// 运动模型，None 表示不做跟踪滤波
+    #[serde(default)]
+    pub motion: Option<MotionModelKind>,
+    /// 最小输出间隔（毫秒），None 表示不限速
+    #[serde(default)]
+    pub min_output_interval_ms: Option<u64>,
+    /// 最小置信度，None 表示不设门限
+    #[serde(default)]
+    pub min_confidence: Option<f64>,
+    /// 关注的区域，为空表示所有区域
+    #[serde(default)]
+    pub zones: Vec<String>,
+}
+
+impl AssetProfile {
+    /// 创建不做任何处理的类别
+    pub fn new(name: impl Into<String>) -> Self {
+        AssetProfile {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// 设置运动模型
+    pub fn with_motion(mut self, motion: MotionModelKind) -> Self {
+        self.motion = Some(motion);
+        self
+    }
+
+    /// 设置最小输出间隔
+    pub fn with_output_interval(mut self, interval: Duration) -> Self {
+        self.min_output_interval_ms = Some(interval.as_millis() as u64);
+        self
+    }
+
+    /// 设置最小置信度
+    pub fn with_min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = Some(min_confidence);
+        self
+    }
+
+    /// 设置关注的区域
+    pub fn with_zones(mut self, zones: Vec<String>) -> Self {
+        self.zones = zones;
+        self
+    }
+
+    /// 区域是否在关注范围内
+    pub fn is_zone_of_interest(&self, zone: &str) -> bool {
+        self.zones.is_empty() || self.zones.iter().any(|z| z == zone)
+    }
+
+    /// 按类别建立后处理流水线：置信度门限 -> 跟踪滤波 -> 限速
+    pub fn build_pipeline(&self) -> PostProcessPipeline {
+        let mut pipeline = PostProcessPipeline::new();
+        if let Some(min_confidence) = self.min_confidence {
+            pipeline.add_stage(OutputGate::new(0, min_confidence));
+        }
+        if let Some(motion) = &self.motion {
+            pipeline.add_stage(MotionTracker::with_boxed_model(motion.build()));
+        }
+        if let Some(interval_ms) = self.min_output_interval_ms {
+            pipeline.add_stage(RateLimiter::new(Duration::from_millis(interval_ms)));
+        }
+        pipeline
+    }
+}
+
+/// 类别分配规则（JSON 配置）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProfileAssignment {
+    /// 广播名称正则
+    #[serde(default)]
+    pub name_pattern: Option<String>,
+    /// 地址前缀
+    #[serde(default)]
+    pub address_prefix: Option<String>,
+    /// 类别名称
+    pub profile: String,
+}
+
+/// 资产类别配置（JSON 配置）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProfileConfig {
+    /// 默认类别名称
+    pub default: String,
+    /// 所有类别
+    pub profiles: Vec<AssetProfile>,
+    /// 分配规则，按顺序匹配
+    #[serde(default)]
+    pub assignments: Vec<ProfileAssignment>,
+}
+
+/// 资产分类器 - 按标识规则为设备选择类别
+#[derive(Clone, Debug)]
+pub struct AssetClassifier {
+    profiles: HashMap<String, AssetProfile>,
+    rules: Vec<(RegistrationRule, String)>,
+    default: String,
+}
+
+impl AssetClassifier {
+    /// 创建分类器，未匹配任何规则的设备使用 `default`
+    pub fn new(default: AssetProfile) -> Self {
+        let name = default.name.clone();
+        AssetClassifier {
+            profiles: HashMap::from([(name.clone(), default)]),
+            rules: Vec::new(),
+            default: name,
+        }
+    }
+
+    /// 添加类别
+    pub fn with_profile(mut self, profile: AssetProfile) -> Self {
+        self.profiles.insert(profile.name.clone(), profile);
+        self
+    }
+
+    /// 添加分配规则，先添加的规则优先
+    pub fn with_rule(mut self, rule: RegistrationRule, profile: &str) -> Result<Self, String> {
+        if !self.profiles.contains_key(profile) {
+            return Err(format!("未定义的资产类别 {}", profile));
+        }
+        self.rules.push((rule, profile.to_string()));
+        Ok(self)
+    }
+
+    /// 从 JSON 配置加载
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let config: ProfileConfig = serde_json::from_str(json).map_err(|e| format!("资产类别配置解析失败: {}", e))?;
+        let default = config
+            .profiles
+            .iter()
+            .find(|p| p.name == config.default)
+            .cloned()
+            .ok_or_else(|| format!("未定义的默认资产类别 {}", config.default))?;
+        let mut classifier = config.profiles.into_iter().fold(Self::new(default), |c, p| c.with_profile(p));
+        for assignment in config.assignments {
+            let rule = match (&assignment.name_pattern, &assignment.address_prefix) {
+                (Some(pattern), None) => RegistrationRule::name_pattern(pattern)?,
+                (None, Some(prefix)) => RegistrationRule::address_prefix(prefix),
+                _ => return Err(format!("类别 {} 的分配规则须且只能指定 name_pattern 或 address_prefix 之一", assignment.profile)),
+            };
+            classifier = classifier.with_rule(rule, &assignment.profile)?;
+        }
+        Ok(classifier)
+    }
+
+    /// 按名称获取类别
+    pub fn profile(&self, name: &str) -> Option<&AssetProfile> {
+        self.profiles.get(name)
+    }
+
+    /// 设备所属的类别
+    pub fn classify(&self, device_id: &str, name: Option<&str>) -> &AssetProfile {
+        self.rules
+            .iter()
+            .find(|(rule, _)| rule.is_match(device_id, name))
+            .and_then(|(_, profile)| self.profiles.get(profile))
+            .unwrap_or(&self.profiles[&self.default])
+    }
+}
+
+/// 按设备类别分别处理的后处理流水线
+pub struct ProfiledPipelines {
+    classifier: AssetClassifier,
+    /// 设备 ID -> (类别名称, 流水线)
+    pipelines: HashMap<String, (String, PostProcessPipeline)>,
+}
+
+impl ProfiledPipelines {
+    /// 创建
+    pub fn new(classifier: AssetClassifier) -> Self {
+        ProfiledPipelines {
+            classifier,
+            pipelines: HashMap::new(),
+        }
+    }
+
+    /// 分类器
+    pub fn classifier(&self) -> &AssetClassifier {
+        &self.classifier
+    }
+
+    /// 设备当前使用的类别名称
+    pub fn profile_of(&self, device_id: &str) -> Option<&str> {
+        self.pipelines.get(device_id).map(|(name, _)| name.as_str())
+    }
+
+    /// 处理设备的定位结果，首次出现的设备按分类结果建立流水线
+    ///
+    /// # 参数
+    /// - `device_id`: 设备 ID
+    /// - `name`: 设备广播名称（用于按名称分类）
+    /// - `result`: 定位结果
+    pub fn process(&mut self, device_id: &str, name: Option<&str>, result: LocationResult) -> Option<LocationResult> {
+        let classifier = &self.classifier;
+        let (_, pipeline) = self.pipelines.entry(device_id.to_string()).or_insert_with(|| {
+            let profile = classifier.classify(device_id, name);
+            (profile.name.clone(), profile.build_pipeline())
+        });
+        pipeline.process(result)
+    }
+
+    /// 移除设备的流水线（设备丢失或重新分类时）
+    pub fn remove(&mut self, device_id: &str) {
+        self.pipelines.remove(device_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{
+        "default": "default",
+        "profiles": [
+            {"name": "default"},
+            {"name": "pallet", "motion": {"type": "constant_position", "drift": 5.0}, "min_output_interval_ms": 10000},
+            {"name": "person", "motion": {"type": "pedestrian", "accel_noise": 50.0, "max_speed": 250.0, "velocity_decay_s": 2.0}, "zones": ["ward"]}
+        ],
+        "assignments": [
+            {"name_pattern": "^PLT-", "profile": "pallet"},
+            {"address_prefix": "C0:FF", "profile": "person"}
+        ]
+    }"#;
+
+    #[test]
+    fn test_classify_from_json() {
+        let classifier = AssetClassifier::from_json(CONFIG).unwrap();
+        assert_eq!(classifier.classify("11:22:33:44:55:66", Some("PLT-0042")).name, "pallet");
+        let person = classifier.classify("c0:ff:00:00:00:01", None);
+        assert_eq!(person.name, "person");
+        assert!(person.is_zone_of_interest("ward") && !person.is_zone_of_interest("dock"));
+        assert_eq!(classifier.classify("AA:00:00:00:00:01", None).name, "default");
+        assert_eq!(
+            classifier.profile("pallet").unwrap().build_pipeline().stage_names(),
+            vec!["motion_tracker", "rate_limiter"]
+        );
+
+        assert!(AssetClassifier::from_json(&CONFIG.replace("\"profile\": \"person\"", "\"profile\": \"robot\"")).is_err());
+    }
+
+    #[test]
+    fn test_profiled_pipelines_rate_limit_per_class() {
+        let mut pipelines = ProfiledPipelines::new(AssetClassifier::from_json(CONFIG).unwrap());
+        let at = |second: i64| {
+            let mut result = LocationResult::new(100.0, 100.0, 0.0, 0.9, 10.0, "m".to_string(), 3);
+            result.timestamp = chrono::DateTime::from_timestamp(1_700_000_000 + second, 0).unwrap();
+            result
+        };
+
+        let pallet: usize = (0..20).filter_map(|s| pipelines.process("P1", Some("PLT-1"), at(s))).count();
+        let other: usize = (0..20).filter_map(|s| pipelines.process("T1", None, at(s))).count();
+        assert_eq!(pallet, 2);
+        assert_eq!(other, 20);
+        assert_eq!(pipelines.profile_of("P1"), Some("pallet"));
+    }
+}
@@ -0,0 +1,193 @@
+/// 按规则自动登记待部署信标
+///
+/// 大批量部署信标时，逐个抄写地址很繁琐。开启自动登记后，未配置但广播地址或名称
+/// 符合规则的设备会进入“待定”列表，记录其标识和信号统计，由运维人员补充坐标后转为正式信标
+
+use crate::algorithms::{Beacon, BeaconSet, DeviceId};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use std::collections::BTreeMap;
+
+/// 自动登记规则
+#[derive(Clone, Debug)]
+pub enum RegistrationRule {
+    /// 广播名称匹配正则表达式
+    NamePattern(Regex),
+    /// 地址（不区分大小写）以指定前缀开头，如厂商 OUI
+    AddressPrefix(String),
+}
+
+impl RegistrationRule {
+    /// 按广播名称匹配
+    pub fn name_pattern(pattern: &str) -> Result<Self, String> {
+        Regex::new(pattern)
+            .map(RegistrationRule::NamePattern)
+            .map_err(|e| format!("无效的名称匹配规则 {}: {}", pattern, e))
+    }
+
+    /// 按地址前缀匹配
+    pub fn address_prefix(prefix: &str) -> Self {
+        RegistrationRule::AddressPrefix(prefix.to_uppercase())
+    }
+
+    /// 广播是否符合规则
+    pub fn is_match(&self, address: &str, name: Option<&str>) -> bool {
+        match self {
+            RegistrationRule::NamePattern(regex) => name.is_some_and(|n| regex.is_match(n)),
+            RegistrationRule::AddressPrefix(prefix) => address.to_uppercase().starts_with(prefix.as_str()),
+        }
+    }
+}
+
+/// 待定信标
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingBeacon {
+    /// 广播地址（规范化）
+    pub address: String,
+    /// 最近一次广播的名称
+    pub name: Option<String>,
+    /// 首次发现时间
+    pub first_seen: DateTime<Utc>,
+    /// 最近发现时间
+    pub last_seen: DateTime<Utc>,
+    /// 发现次数
+    pub sightings: usize,
+    /// 最强 RSSI (dBm)
+    pub max_rssi: i16,
+    /// RSSI 之和，用于计算均值
+    rssi_sum: f64,
+}
+
+impl PendingBeacon {
+    /// 平均 RSSI (dBm)
+    pub fn mean_rssi(&self) -> f64 {
+        self.rssi_sum / self.sightings.max(1) as f64
+    }
+}
+
+/// 自动登记器
+#[derive(Clone, Debug, Default)]
+pub struct AutoRegistration {
+    rules: Vec<RegistrationRule>,
+    /// 地址 -> 待定信标
+    pending: BTreeMap<String, PendingBeacon>,
+}
+
+impl AutoRegistration {
+    /// 创建自动登记器，没有规则时不登记任何设备
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加规则，符合任一规则即登记
+    pub fn with_rule(mut self, rule: RegistrationRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// 处理一次广播
+    ///
+    /// # 参数
+    /// - `beacons`: 已配置的信标，能匹配到的设备不登记
+    /// - `address`: 广播地址
+    /// - `name`: 广播名称
+    /// - `rssi`: 信号强度 (dBm)
+    /// - `timestamp`: 接收时间
+    ///
+    /// # 返回
+    /// - 设备是否在待定列表中
+    pub fn observe(
+        &mut self,
+        beacons: &BeaconSet,
+        address: &str,
+        name: Option<&str>,
+        rssi: i16,
+        timestamp: DateTime<Utc>,
+    ) -> bool {
+        if beacons.match_advertisement(address, name).is_some() {
+            return false;
+        }
+        let address = DeviceId::normalized(address);
+        if !self.pending.contains_key(&address) && !self.rules.iter().any(|r| r.is_match(&address, name)) {
+            return false;
+        }
+
+        let entry = self.pending.entry(address.clone()).or_insert_with(|| PendingBeacon {
+            address,
+            name: None,
+            first_seen: timestamp,
+            last_seen: timestamp,
+            sightings: 0,
+            max_rssi: rssi,
+            rssi_sum: 0.0,
+        });
+        if name.is_some() {
+            entry.name = name.map(str::to_string);
+        }
+        entry.last_seen = entry.last_seen.max(timestamp);
+        entry.sightings += 1;
+        entry.max_rssi = entry.max_rssi.max(rssi);
+        entry.rssi_sum += rssi as f64;
+        true
+    }
+
+    /// 待定信标，按最强 RSSI 从强到弱排序（运维人员就近的设备排在前面）
+    pub fn pending(&self) -> Vec<&PendingBeacon> {
+        let mut pending: Vec<&PendingBeacon> = self.pending.values().collect();
+        pending.sort_by(|a, b| b.max_rssi.cmp(&a.max_rssi).then_with(|| a.address.cmp(&b.address)));
+        pending
+    }
+
+    /// 为待定信标指定坐标，将其从待定列表移除并加入信标集合
+    ///
+    /// # 返回
+    /// - 新信标，或错误如果地址不在待定列表中
+    pub fn assign(&mut self, beacons: &mut BeaconSet, address: &str, x: f64, y: f64, z: f64) -> Result<Beacon, String> {
+        let pending = self
+            .pending
+            .remove(&DeviceId::normalized(address))
+            .ok_or_else(|| format!("待定列表中没有设备 {}", address))?;
+        let name = pending.name.clone().unwrap_or_else(|| pending.address.clone());
+        let beacon = Beacon::new(pending.address, name, x, y, z);
+        beacons.add_beacon(beacon.clone());
+        Ok(beacon)
+    }
+
+    /// 忽略待定信标（如误登记的其他设备）
+    pub fn dismiss(&mut self, address: &str) -> Option<PendingBeacon> {
+        self.pending.remove(&DeviceId::normalized(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_registration_and_assign() {
+        let mut beacons = BeaconSet::from_vec(vec![Beacon::new("AA:00:00:00:00:01".to_string(), "known".to_string(), 0.0, 0.0, 0.0)]);
+        let mut registration = AutoRegistration::new()
+            .with_rule(RegistrationRule::name_pattern("^RFstar_").unwrap())
+            .with_rule(RegistrationRule::address_prefix("cc:dd"));
+        let now = Utc::now();
+
+        assert!(!registration.observe(&beacons, "AA:00:00:00:00:01", Some("RFstar_0001"), -50, now));
+        assert!(registration.observe(&beacons, "11:22:33:44:55:66", Some("RFstar_C5D6"), -70, now));
+        assert!(registration.observe(&beacons, "11:22:33:44:55:66", None, -60, now));
+        assert!(registration.observe(&beacons, "CC:DD:00:00:00:09", None, -80, now));
+        assert!(!registration.observe(&beacons, "EE:00:00:00:00:01", Some("phone"), -40, now));
+
+        let pending = registration.pending();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].name.as_deref(), Some("RFstar_C5D6"));
+        assert_eq!(pending[0].sightings, 2);
+        assert_eq!(pending[0].mean_rssi(), -65.0);
+
+        let beacon = registration.assign(&mut beacons, &pending[0].address.clone(), 100.0, 200.0, 0.0).unwrap();
+        assert_eq!(beacon.name, "RFstar_C5D6");
+        assert_eq!(beacons.len(), 2);
+        assert!(!registration.observe(&beacons, "11:22:33:44:55:66", None, -60, now));
+        assert!(registration.assign(&mut beacons, "11:22:33:44:55:66", 0.0, 0.0, 0.0).is_err());
+        assert!(registration.dismiss("cc:dd:00:00:00:09").is_some());
+    }
+}
@@ -0,0 +1,295 @@
+/// 观测重排序缓冲区
+///
+/// 源端打时间戳的观测可能经 MQTT/网关链路延迟、乱序到达：
+/// - 在允许的最大延迟内缓存并按时间戳重新排序
+/// - 超过最大延迟才到达的观测直接丢弃
+/// - 时间戳超前或落后本地时钟超过跳变阈值时先丢弃，连续多个样本给出一致的偏差后才判定为源端时钟跳变，
+///   此后按该偏差重新换算源时间戳；源时钟恢复正常后自动取消换算
+/// - 时钟换算按观测来源分别进行，一个网关的时钟跳变不影响其他来源
+
+use crate::algorithms::{Clock, Observation, ObservationSource, SystemClock};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 观测入队结果
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PushOutcome {
+    /// 已缓存，等待按序释放
+    Accepted,
+    /// 到达过晚（早于已释放的时间点），已丢弃
+    TooLate,
+    /// 时间戳明显超前或落后，等待同一来源的后续样本确认时钟跳变，已丢弃
+    Unconfirmed {
+        /// 源时间戳相对本地时钟的偏差（毫秒）
+        offset_ms: i64,
+    },
+    /// 已确认源端时钟跳变，按到达时间重新打戳后缓存，该来源后续时间戳按该偏差换算
+    ClockJump {
+        /// 源时间戳相对本地时钟的偏差（毫秒）
+        offset_ms: i64,
+    },
+}
+
+/// 单个来源的时钟换算状态
+#[derive(Clone, Debug, Default)]
+struct SourceClock {
+    /// 待确认的跳变：(偏差毫秒, 连续样本数)
+    jump_candidate: Option<(i64, usize)>,
+    /// 已确认的源时钟偏差（毫秒），源时间戳减去该值得到本地时间
+    offset_ms: i64,
+}
+
+/// 按时间戳重排序观测的缓冲区
+#[derive(Clone, Debug)]
+pub struct ReorderBuffer {
+    /// 待释放的观测，按时间戳升序
+    pending: VecDeque<Observation>,
+    /// 最大允许延迟（毫秒）
+    max_lateness_ms: u64,
+    /// 时钟跳变判定阈值（毫秒）
+    clock_jump_threshold_ms: u64,
+    /// 确认时钟跳变所需的连续样本数
+    jump_confirm_samples: usize,
+    /// 各来源的时钟换算状态
+    sources: HashMap<ObservationSource, SourceClock>,
+    /// 已释放观测的最大时间戳
+    watermark_ms: Option<u64>,
+    /// 因过晚或时间戳不可信被丢弃的观测数
+    discarded: usize,
+    /// 检测到的时钟跳变次数
+    clock_jumps: usize,
+    /// 时间来源
+    clock: Arc<dyn Clock>,
+}
+
+impl ReorderBuffer {
+    /// 创建缓冲区
+    ///
+    /// # 参数
+    /// - `max_lateness`: 观测最多等待多久后按序释放，超过后到达的旧观测被丢弃
+    pub fn new(max_lateness: Duration) -> Self {
+        ReorderBuffer {
+            pending: VecDeque::new(),
+            max_lateness_ms: max_lateness.as_millis() as u64,
+            clock_jump_threshold_ms: 60_000,
+            jump_confirm_samples: 3,
+            sources: HashMap::new(),
+            watermark_ms: None,
+            discarded: 0,
+            clock_jumps: 0,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// 设置时钟跳变判定阈值（默认 60 秒）
+    pub fn with_clock_jump_threshold(mut self, threshold: Duration) -> Self {
+        self.clock_jump_threshold_ms = threshold.as_millis() as u64;
+        self
+    }
+
+    /// 设置确认时钟跳变所需的连续样本数（默认 3）
+    pub fn with_jump_confirmation(mut self, samples: usize) -> Self {
+        self.jump_confirm_samples = samples.max(1);
+        self
+    }
+
+    /// 使用指定的时钟
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 放入一条观测；没有时间戳的观测按到达时间处理
+    pub fn push(&mut self, observation: impl Into<Observation>) -> PushOutcome {
+        let mut observation = observation.into();
+        let now = self.clock.now_ms();
+        let mut outcome = PushOutcome::Accepted;
+
+        let timestamp_ms = match observation.timestamp_ms {
+            Some(ts) => {
+                let threshold = self.clock_jump_threshold_ms;
+                let source = self.sources.entry(observation.source.clone()).or_default();
+                let raw_offset_ms = ts as i64 - now as i64;
+                let offset_ms = raw_offset_ms - source.offset_ms;
+                if raw_offset_ms.unsigned_abs() <= threshold {
+                    // 源时钟正常（或已恢复），取消之前的换算
+                    source.offset_ms = 0;
+                    source.jump_candidate = None;
+                    ts
+                } else if offset_ms.unsigned_abs() <= threshold {
+                    source.jump_candidate = None;
+                    (ts as i64 - source.offset_ms).max(0) as u64
+                } else {
+                    // 向前或向后的跳变都需要连续样本确认
+                    let count = match source.jump_candidate {
+                        Some((candidate, n)) if (offset_ms - candidate).unsigned_abs() <= threshold => n + 1,
+                        _ => 1,
+                    };
+                    if count < self.jump_confirm_samples {
+                        source.jump_candidate = Some((offset_ms, count));
+                        self.discarded += 1;
+                        return PushOutcome::Unconfirmed { offset_ms };
+                    }
+                    source.jump_candidate = None;
+                    source.offset_ms += offset_ms;
+                    self.clock_jumps += 1;
+                    outcome = PushOutcome::ClockJump { offset_ms };
+                    now
+                }
+            }
+            None => now,
+        };
+
+        if self.watermark_ms.is_some_and(|w| timestamp_ms < w) {
+            self.discarded += 1;
+            return PushOutcome::TooLate;
+        }
+
+        observation.timestamp_ms = Some(timestamp_ms);
+        let pos = self
+            .pending
+            .partition_point(|o| o.timestamp_ms.unwrap_or(0) <= timestamp_ms);
+        self.pending.insert(pos, observation);
+        outcome
+    }
+
+    /// 取出已超过最大延迟、可按序处理的观测
+    pub fn drain_ready(&mut self) -> Vec<Observation> {
+        let deadline = self.clock.now_ms().saturating_sub(self.max_lateness_ms);
+        let mut ready = Vec::new();
+        while self
+            .pending
+            .front()
+            .is_some_and(|o| o.timestamp_ms.unwrap_or(0) <= deadline)
+        {
+            if let Some(o) = self.pending.pop_front() {
+                self.watermark_ms = o.timestamp_ms;
+                ready.push(o);
+            }
+        }
+        ready
+    }
+
+    /// 立即取出所有缓存的观测（按时间戳排序）
+    pub fn flush(&mut self) -> Vec<Observation> {
+        if let Some(last) = self.pending.back() {
+            self.watermark_ms = last.timestamp_ms;
+        }
+        self.pending.drain(..).collect()
+    }
+
+    /// 清空缓存及时钟跳变状态
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.watermark_ms = None;
+        self.sources.clear();
+    }
+
+    /// 缓存中的观测数量
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// 缓存是否为空
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// 因过晚或时间戳不可信被丢弃的观测数
+    pub fn discarded_count(&self) -> usize {
+        self.discarded
+    }
+
+    /// 检测到的时钟跳变次数
+    pub fn clock_jump_count(&self) -> usize {
+        self.clock_jumps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{MockClock, SignalMeasurement};
+
+    #[test]
+    fn test_reorders_and_discards_late() {
+        let clock = MockClock::new(10_000);
+        let mut buffer =
+            ReorderBuffer::new(Duration::from_millis(500)).with_clock(Arc::new(clock.clone()));
+
+        buffer.push(SignalMeasurement::with_timestamp("B1".to_string(), -50, 9_900));
+        buffer.push(SignalMeasurement::with_timestamp("B2".to_string(), -60, 9_700));
+        assert!(buffer.drain_ready().is_empty());
+
+        clock.advance(Duration::from_millis(500));
+        let ready = buffer.drain_ready();
+        assert_eq!(ready.len(), 2);
+        assert_eq!(ready[0].target, "B2");
+
+        let outcome =
+            buffer.push(SignalMeasurement::with_timestamp("B3".to_string(), -70, 9_800));
+        assert_eq!(outcome, PushOutcome::TooLate);
+        assert_eq!(buffer.discarded_count(), 1);
+    }
+
+    #[test]
+    fn test_clock_jump_requires_confirmation() {
+        let clock = MockClock::new(1_000_000);
+        let mut buffer = ReorderBuffer::new(Duration::from_millis(200))
+            .with_clock(Arc::new(clock.clone()))
+            .with_clock_jump_threshold(Duration::from_secs(10));
+
+        // 落后很多的旧观测不能当作新数据
+        let outcome = buffer.push(SignalMeasurement::with_timestamp("B1".to_string(), -50, 0));
+        assert_eq!(outcome, PushOutcome::Unconfirmed { offset_ms: -1_000_000 });
+        assert!(buffer.is_empty());
+
+        // 源时钟超前 1 小时：前两个样本丢弃，第三个确认跳变
+        let ahead = 1_000_000 + 3_600_000;
+        for i in 0..2 {
+            let outcome = buffer.push(SignalMeasurement::with_timestamp("B1".to_string(), -50, ahead + i));
+            assert_eq!(outcome, PushOutcome::Unconfirmed { offset_ms: 3_600_000 + i as i64 });
+        }
+        assert!(matches!(
+            buffer.push(SignalMeasurement::with_timestamp("B1".to_string(), -50, ahead + 2)),
+            PushOutcome::ClockJump { .. }
+        ));
+        assert_eq!(buffer.clock_jump_count(), 1);
+        assert_eq!(buffer.discarded_count(), 3);
+
+        // 之后的时间戳按偏差换算
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(buffer.push(SignalMeasurement::with_timestamp("B1".to_string(), -50, ahead + 102)), PushOutcome::Accepted);
+        assert_eq!(buffer.flush().iter().map(|o| o.timestamp_ms).collect::<Vec<_>>(), vec![Some(1_000_000), Some(1_000_100)]);
+    }
+
+    #[test]
+    fn test_backward_jump_per_source() {
+        let clock = MockClock::new(10_000_000);
+        let mut buffer = ReorderBuffer::new(Duration::from_millis(200))
+            .with_clock(Arc::new(clock.clone()))
+            .with_clock_jump_threshold(Duration::from_secs(10));
+        let healthy = ObservationSource::Gateway { gateway: "gw1".to_string() };
+        let jumped = ObservationSource::Gateway { gateway: "gw2".to_string() };
+        let behind = 3_600_000;
+
+        // gw2 的时钟回拨 1 小时，与正常的 gw1 交替到达，确认不受 gw1 干扰
+        let mut outcomes = Vec::new();
+        for i in 0..3 {
+            let now = clock.now_ms();
+            assert_eq!(buffer.push(Observation::rssi(healthy.clone(), "B1", -50, Some(now))), PushOutcome::Accepted);
+            outcomes.push(buffer.push(Observation::rssi(jumped.clone(), "B2", -60, Some(now - behind))));
+            clock.advance(Duration::from_millis(10 * (i + 1)));
+        }
+        assert!(matches!(outcomes[..], [PushOutcome::Unconfirmed { .. }, PushOutcome::Unconfirmed { .. }, PushOutcome::ClockJump { .. }]));
+
+        // 确认后 gw2 的时间戳按偏差换算，gw1 不受影响
+        let now = clock.now_ms();
+        assert_eq!(buffer.push(Observation::rssi(healthy, "B1", -50, Some(now))), PushOutcome::Accepted);
+        assert_eq!(buffer.push(Observation::rssi(jumped, "B2", -60, Some(now - behind))), PushOutcome::Accepted);
+        let flushed = buffer.flush();
+        assert_eq!(flushed.len(), 6);
+        assert!(flushed.iter().all(|o| o.timestamp_ms.unwrap().abs_diff(now) <= 100));
+    }
+}
@@ -2,8 +2,13 @@
 /// 
 /// 包含定位输出的各种信息和元数据
 
+use crate::algorithms::Metadata;
+use std::collections::HashMap;
 use std::fmt;
+use std::f64::consts::PI;
+use std::time::Duration;
 use chrono::{DateTime, Utc};
+use serde::Serialize;
 
 /// 定位结果
 #[derive(Clone, Debug)]
@@ -24,6 +29,8 @@ pub struct LocationResult {
     pub beacon_count: usize,
     /// 时间戳
     pub timestamp: DateTime<Utc>,
+    /// 附加业务数据，来自参与求解的观测
+    pub metadata: Metadata,
 }
 
 impl LocationResult {
@@ -46,6 +53,7 @@ impl LocationResult {
             method,
             beacon_count,
             timestamp: Utc::now(),
+            metadata: Metadata::new(),
         }
     }
 
@@ -69,9 +77,16 @@ impl LocationResult {
             method,
             beacon_count,
             timestamp,
+            metadata: Metadata::new(),
         }
     }
 
+    /// 附加一项业务数据
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
     /// 获取 2D 坐标
     pub fn xy(&self) -> (f64, f64) {
         (self.x, self.y)
@@ -122,6 +137,181 @@ impl LocationResult {
             self.beacon_count
         )
     }
+
+    /// 结果生成后经过的时间（`now` 早于时间戳时为 0）
+    pub fn age(&self, now: DateTime<Utc>) -> Duration {
+        (now - self.timestamp).to_std().unwrap_or_default()
+    }
+
+    /// 按时间衰减的置信度：confidence · 0.5^(age / half_life)
+    ///
+    /// 数值相同的新结果与 30 秒前的旧结果由此可以区分；`half_life` 为 0 时不衰减
+    pub fn decayed_confidence(&self, now: DateTime<Utc>, half_life: Duration) -> f64 {
+        if half_life.is_zero() {
+            return self.confidence;
+        }
+        let halvings = self.age(now).as_secs_f64() / half_life.as_secs_f64();
+        self.confidence * 0.5_f64.powf(halvings)
+    }
+
+    /// 置信度按时间衰减后的副本（时间戳保持不变）
+    pub fn aged(&self, now: DateTime<Utc>, half_life: Duration) -> LocationResult {
+        let mut result = self.clone();
+        result.confidence = self.decayed_confidence(now, half_life);
+        result
+    }
+}
+
+/// 相对参考位姿的极坐标位置
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct RelativePosition {
+    /// 水平距离
+    pub range: f64,
+    /// 方位角（弧度，相对参考朝向，逆时针为正，范围 (-π, π]）
+    pub bearing: f64,
+    /// 参考坐标系下的前向分量
+    pub forward: f64,
+    /// 参考坐标系下的左向分量
+    pub left: f64,
+    /// 高度差
+    pub dz: f64,
+}
+
+/// ROS `builtin_interfaces/Time`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct RosTime {
+    /// 秒
+    pub sec: i32,
+    /// 纳秒
+    pub nanosec: u32,
+}
+
+/// ROS `std_msgs/Header`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RosHeader {
+    /// 时间戳
+    pub stamp: RosTime,
+    /// 坐标系名称
+    pub frame_id: String,
+}
+
+/// ROS `geometry_msgs/Point`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct RosPoint {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+/// ROS `geometry_msgs/Quaternion`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct RosQuaternion {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub w: f64,
+}
+
+/// ROS `geometry_msgs/Pose`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct RosPose {
+    /// 位置（米）
+    pub position: RosPoint,
+    /// 姿态
+    pub orientation: RosQuaternion,
+}
+
+/// ROS `geometry_msgs/PoseWithCovariance`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RosPoseWithCovariance {
+    /// 位姿
+    pub pose: RosPose,
+    /// 6x6 行优先协方差 (x, y, z, rot_x, rot_y, rot_z)
+    pub covariance: Vec<f64>,
+}
+
+/// ROS `geometry_msgs/PoseWithCovarianceStamped`（rosbridge JSON 结构）
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RosPoseWithCovarianceStamped {
+    /// 消息头
+    pub header: RosHeader,
+    /// 带协方差的位姿
+    pub pose: RosPoseWithCovariance,
+}
+
+impl LocationResult {
+    /// 相对参考位姿的距离与方位
+    ///
+    /// # 参数
+    /// - `origin`: 参考点坐标
+    /// - `heading`: 参考朝向（弧度，从 +X 轴逆时针）
+    pub fn relative_to(&self, origin: (f64, f64, f64), heading: f64) -> RelativePosition {
+        let dx = self.x - origin.0;
+        let dy = self.y - origin.1;
+        let (sin, cos) = heading.sin_cos();
+        let forward = dx * cos + dy * sin;
+        let left = -dx * sin + dy * cos;
+
+        let mut bearing = left.atan2(forward);
+        if bearing <= -PI {
+            bearing += 2.0 * PI;
+        }
+
+        RelativePosition {
+            range: (dx * dx + dy * dy).sqrt(),
+            bearing,
+            forward,
+            left,
+            dz: self.z - origin.2,
+        }
+    }
+
+    /// 转换为 ROS `PoseWithCovarianceStamped` 消息
+    ///
+    /// ROS 使用米制，`meters_per_unit` 为结果坐标单位到米的换算系数（厘米为 0.01）。
+    /// 位置协方差取 误差²，姿态未知，旋转协方差设为很大的值
+    pub fn to_ros_pose(&self, frame_id: &str, meters_per_unit: f64) -> RosPoseWithCovarianceStamped {
+        let variance = (self.error * meters_per_unit).powi(2);
+        let mut covariance = vec![0.0; 36];
+        covariance[0] = variance;
+        covariance[7] = variance;
+        covariance[14] = variance;
+        for i in [21, 28, 35] {
+            covariance[i] = 1e6;
+        }
+
+        RosPoseWithCovarianceStamped {
+            header: RosHeader {
+                stamp: RosTime {
+                    sec: self.timestamp.timestamp() as i32,
+                    nanosec: self.timestamp.timestamp_subsec_nanos(),
+                },
+                frame_id: frame_id.to_string(),
+            },
+            pose: RosPoseWithCovariance {
+                pose: RosPose {
+                    position: RosPoint {
+                        x: self.x * meters_per_unit,
+                        y: self.y * meters_per_unit,
+                        z: self.z * meters_per_unit,
+                    },
+                    orientation: RosQuaternion {
+                        x: 0.0,
+                        y: 0.0,
+                        z: 0.0,
+                        w: 1.0,
+                    },
+                },
+                covariance,
+            },
+        }
+    }
+
+    /// 序列化为 ROS 位姿 JSON（可直接用于 rosbridge 发布）
+    pub fn to_ros_pose_json(&self, frame_id: &str, meters_per_unit: f64) -> String {
+        serde_json::to_string(&self.to_ros_pose(frame_id, meters_per_unit))
+            .expect("ROS 位姿消息只包含可序列化的基本类型")
+    }
 }
 
 impl fmt::Display for LocationResult {
@@ -137,6 +327,70 @@ impl fmt::Display for LocationResult {
     }
 }
 
+/// 平面包围盒
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingBox {
+    /// 最小 X
+    pub min_x: f64,
+    /// 最小 Y
+    pub min_y: f64,
+    /// 最大 X
+    pub max_x: f64,
+    /// 最大 Y
+    pub max_y: f64,
+}
+
+impl BoundingBox {
+    /// 宽度
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    /// 高度
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    /// 面积
+    pub fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+}
+
+/// 区域覆盖网格（占用热力图）
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoverageGrid {
+    /// 网格尺寸
+    pub cell_size: f64,
+    /// (列, 行) -> 落入该格的定位点数，格 (i, j) 覆盖 [i·s, (i+1)·s) × [j·s, (j+1)·s)
+    pub cells: HashMap<(i64, i64), usize>,
+}
+
+impl CoverageGrid {
+    /// 坐标所在网格
+    pub fn cell_of(&self, x: f64, y: f64) -> (i64, i64) {
+        (
+            (x / self.cell_size).floor() as i64,
+            (y / self.cell_size).floor() as i64,
+        )
+    }
+
+    /// 坐标所在网格的定位点数
+    pub fn count_at(&self, x: f64, y: f64) -> usize {
+        self.cells.get(&self.cell_of(x, y)).copied().unwrap_or(0)
+    }
+
+    /// 被访问过的网格数
+    pub fn visited_cells(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// 覆盖面积（被访问网格数 × 单格面积）
+    pub fn covered_area(&self) -> f64 {
+        self.cells.len() as f64 * self.cell_size * self.cell_size
+    }
+}
+
 /// 定位结果序列（用于时间序列处理）
 #[derive(Clone, Debug)]
 pub struct LocationSequence {
@@ -201,6 +455,42 @@ impl LocationSequence {
         ))
     }
 
+    /// 获取中位数位置（各坐标分别取中位数）
+    ///
+    /// 少数离群定位点不会影响结果，适合判断“已稳定”的位置
+    pub fn median_position(&self) -> Option<LocationResult> {
+        self.robust_position(|values| trimmed_mean(values, 0.5), "median".to_string())
+    }
+
+    /// 获取截尾均值位置
+    ///
+    /// 各坐标分别去掉最小和最大的 `alpha` 比例（0.0 ~ 0.5）后取平均；
+    /// `alpha` 为 0 时等同于 `average_position`，为 0.5 时等同于 `median_position`
+    pub fn trimmed_mean_position(&self, alpha: f64) -> Option<LocationResult> {
+        let alpha = alpha.clamp(0.0, 0.5);
+        self.robust_position(|values| trimmed_mean(values, alpha), format!("trimmed_mean_{}", alpha))
+    }
+
+    fn robust_position(&self, estimator: impl Fn(&mut [f64]) -> f64, method: String) -> Option<LocationResult> {
+        if self.results.is_empty() {
+            return None;
+        }
+        let column = |f: fn(&LocationResult) -> f64| {
+            let mut values: Vec<f64> = self.results.iter().map(f).collect();
+            estimator(&mut values)
+        };
+
+        Some(LocationResult::new(
+            column(|r| r.x),
+            column(|r| r.y),
+            column(|r| r.z),
+            column(|r| r.confidence),
+            column(|r| r.error),
+            method,
+            0,
+        ))
+    }
+
     /// 获取最近 N 个结果的平均位置
     pub fn average_last_n(&self, n: usize) -> Option<LocationResult> {
         if self.results.is_empty() {
@@ -233,12 +523,106 @@ impl LocationSequence {
         ))
     }
 
+    /// 滚动平面标准差
+    ///
+    /// 对每个长度为 `window` 的连续窗口计算 sqrt(var_x + var_y)，
+    /// 返回值第 i 项对应以第 i + window - 1 个结果结尾的窗口；结果数不足时为空
+    pub fn rolling_std(&self, window: usize) -> Vec<f64> {
+        if window == 0 {
+            return Vec::new();
+        }
+        self.results
+            .windows(window)
+            .map(|w| {
+                let n = w.len() as f64;
+                let mx = w.iter().map(|r| r.x).sum::<f64>() / n;
+                let my = w.iter().map(|r| r.y).sum::<f64>() / n;
+                let var = w.iter().map(|r| (r.x - mx).powi(2) + (r.y - my).powi(2)).sum::<f64>() / n;
+                var.sqrt()
+            })
+            .collect()
+    }
+
+    /// 位置抖动 - 各点偏离前后两点中点距离的均方根
+    ///
+    /// 匀速直线运动的抖动为 0，因此可区分测量噪声与真实移动
+    ///
+    /// # 返回
+    /// - 抖动（坐标单位），或 None 如果结果少于 3 个
+    pub fn jitter(&self) -> Option<f64> {
+        if self.results.len() < 3 {
+            return None;
+        }
+        let sum = self
+            .results
+            .windows(3)
+            .map(|w| {
+                let mx = (w[0].x + w[2].x) / 2.0;
+                let my = (w[0].y + w[2].y) / 2.0;
+                (w[1].x - mx).powi(2) + (w[1].y - my).powi(2)
+            })
+            .sum::<f64>();
+        Some((sum / (self.results.len() - 2) as f64).sqrt())
+    }
+
+    /// 轨迹总长度（平面距离累加）
+    pub fn path_length(&self) -> f64 {
+        self.results
+            .windows(2)
+            .map(|w| w[0].distance_2d_to(&w[1]))
+            .sum()
+    }
+
+    /// 轨迹的平面包围盒
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        let first = self.results.first()?;
+        let init = BoundingBox {
+            min_x: first.x,
+            min_y: first.y,
+            max_x: first.x,
+            max_y: first.y,
+        };
+        Some(self.results.iter().fold(init, |b, r| BoundingBox {
+            min_x: b.min_x.min(r.x),
+            min_y: b.min_y.min(r.y),
+            max_x: b.max_x.max(r.x),
+            max_y: b.max_y.max(r.y),
+        }))
+    }
+
+    /// 按网格统计各区域的定位点数
+    pub fn coverage_grid(&self, cell_size: f64) -> CoverageGrid {
+        let mut grid = CoverageGrid {
+            cell_size,
+            cells: HashMap::new(),
+        };
+        if cell_size <= 0.0 {
+            return grid;
+        }
+        for result in &self.results {
+            let cell = grid.cell_of(result.x, result.y);
+            *grid.cells.entry(cell).or_insert(0) += 1;
+        }
+        grid
+    }
+
     /// 清空序列
     pub fn clear(&mut self) {
         self.results.clear();
     }
 }
 
+/// 截尾均值 - 排序后去掉两端各 `alpha` 比例的值再取平均
+///
+/// `alpha` 为 0.5 时退化为中位数（偶数个值时取中间两个的平均）
+fn trimmed_mean(values: &mut [f64], alpha: f64) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let n = values.len();
+    let cut = ((n as f64 * alpha).floor() as usize).min((n - 1) / 2);
+    let kept = &values[cut..n - cut];
+    kept.iter().sum::<f64>() / kept.len() as f64
+}
+
 impl Default for LocationSequence {
     fn default() -> Self {
         Self::new()
@@ -249,6 +633,39 @@ impl Default for LocationSequence {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_robust_positions_ignore_outlier() {
+        let mut sequence = LocationSequence::new();
+        for x in [100.0, 102.0, 98.0, 101.0, 5000.0] {
+            sequence.push(LocationResult::new(x, 0.0, 0.0, 0.8, 10.0, "m".to_string(), 3));
+        }
+        assert!(sequence.average_position().unwrap().x > 1000.0);
+        assert_eq!(sequence.median_position().unwrap().x, 101.0);
+        // 5 个值去掉两端各 1 个
+        let trimmed = sequence.trimmed_mean_position(0.2).unwrap();
+        assert!((trimmed.x - 101.0).abs() < 1e-9);
+        assert_eq!(sequence.trimmed_mean_position(0.0).unwrap().x, sequence.average_position().unwrap().x);
+    }
+
+    #[test]
+    fn test_rolling_std_and_jitter() {
+        let mut moving = LocationSequence::new();
+        for i in 0..5 {
+            moving.push(LocationResult::new(i as f64 * 10.0, 0.0, 0.0, 0.8, 10.0, "m".to_string(), 3));
+        }
+        assert_eq!(moving.jitter(), Some(0.0));
+        let rolling = moving.rolling_std(2);
+        assert_eq!(rolling.len(), 4);
+        assert!(rolling.iter().all(|s| (s - 5.0).abs() < 1e-9));
+
+        let mut noisy = LocationSequence::new();
+        for y in [0.0, 4.0, 0.0, 4.0] {
+            noisy.push(LocationResult::new(0.0, y, 0.0, 0.8, 10.0, "m".to_string(), 3));
+        }
+        assert!((noisy.jitter().unwrap() - 4.0).abs() < 1e-9);
+        assert!(noisy.rolling_std(5).is_empty());
+    }
+
     #[test]
     fn test_location_result_creation() {
         let result = LocationResult::new(100.0, 200.0, 50.0, 0.85, 10.0, "method".to_string(), 3);
@@ -256,6 +673,16 @@ mod tests {
         assert_eq!(result.confidence, 0.85);
     }
 
+    #[test]
+    fn test_confidence_decay() {
+        let result = LocationResult::new(0.0, 0.0, 0.0, 0.8, 10.0, "m".to_string(), 3);
+        let half_life = Duration::from_secs(10);
+        let later = result.timestamp + chrono::Duration::seconds(20);
+        assert!((result.decayed_confidence(later, half_life) - 0.2).abs() < 1e-9);
+        assert_eq!(result.decayed_confidence(result.timestamp - chrono::Duration::seconds(5), half_life), 0.8);
+        assert_eq!(result.aged(later, Duration::ZERO).confidence, 0.8);
+    }
+
     #[test]
     fn test_distance_calculation() {
         let r1 = LocationResult::new(0.0, 0.0, 0.0, 0.8, 10.0, "m".to_string(), 3);
@@ -263,6 +690,27 @@ mod tests {
         assert_eq!(r1.distance_to(&r2), 5.0);
     }
 
+    #[test]
+    fn test_relative_to_and_ros_pose() {
+        let result = LocationResult::new(100.0, 200.0, 120.0, 0.8, 50.0, "m".to_string(), 3);
+
+        // 参考点在 (100, 100)，朝向 +X：目标在正左方
+        let rel = result.relative_to((100.0, 100.0, 0.0), 0.0);
+        assert!((rel.range - 100.0).abs() < 1e-9);
+        assert!((rel.bearing - PI / 2.0).abs() < 1e-9);
+        assert!((rel.left - 100.0).abs() < 1e-9);
+
+        // 朝向 +Y：目标在正前方
+        let rel = result.relative_to((100.0, 100.0, 0.0), PI / 2.0);
+        assert!(rel.bearing.abs() < 1e-9);
+
+        let pose = result.to_ros_pose("map", 0.01);
+        assert_eq!(pose.header.frame_id, "map");
+        assert!((pose.pose.pose.position.y - 2.0).abs() < 1e-9);
+        assert!((pose.pose.covariance[0] - 0.25).abs() < 1e-9);
+        assert!(result.to_ros_pose_json("map", 0.01).contains("\"frame_id\":\"map\""));
+    }
+
     #[test]
     fn test_location_sequence() {
         let mut seq = LocationSequence::new();
@@ -273,4 +721,24 @@ mod tests {
         let avg = seq.average_position().unwrap();
         assert!((avg.x - 105.0).abs() < 0.1);
     }
+
+    #[test]
+    fn test_path_length_bbox_and_coverage() {
+        let mut seq = LocationSequence::new();
+        assert!(seq.bounding_box().is_none());
+        for (x, y) in [(0.0, 0.0), (30.0, 40.0), (30.0, 140.0), (35.0, 145.0)] {
+            seq.push(LocationResult::new(x, y, 0.0, 0.8, 10.0, "m".to_string(), 3));
+        }
+
+        assert!((seq.path_length() - (50.0 + 100.0 + 50_f64.sqrt())).abs() < 1e-9);
+
+        let bbox = seq.bounding_box().unwrap();
+        assert_eq!((bbox.min_x, bbox.max_y), (0.0, 145.0));
+        assert_eq!(bbox.area(), 35.0 * 145.0);
+
+        let grid = seq.coverage_grid(100.0);
+        assert_eq!(grid.visited_cells(), 2);
+        assert_eq!(grid.count_at(32.0, 141.0), 2);
+        assert_eq!(grid.covered_area(), 20_000.0);
+    }
 }
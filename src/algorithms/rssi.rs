@@ -0,0 +1,150 @@
+/// RSSI 类型
+///
+/// `Rssi` 是带 dBm 语义的 `i16` 包装，构造时校验取值范围，
+/// 避免把 RSSI 与计数、坐标等其他整数混用。
+///
+/// `SignalMeasurement`、`SignalReadings`、`RSSIModel`、`Observation` 和 `SignalStats` 的 RSSI 参数
+/// 均接受 `impl Into<Rssi>`：传入 `Rssi` 时保持校验结果，传入整数时按 dBm 截断到有效范围
+
+use crate::algorithms::SignalReadings;
+use std::fmt;
+
+/// 信号强度 (dBm)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rssi(i16);
+
+impl Rssi {
+    /// 允许的最小值 (dBm)，-127 为蓝牙规范中的“不可用”值
+    pub const MIN: i16 = -127;
+    /// 允许的最大值 (dBm)
+    pub const MAX: i16 = 20;
+
+    /// 创建 RSSI，超出 [-127, 20] dBm 时返回错误
+    pub fn new(dbm: i16) -> Result<Self, String> {
+        if !(Self::MIN..=Self::MAX).contains(&dbm) {
+            return Err(format!("RSSI 超出有效范围 [{}, {}] dBm: {}", Self::MIN, Self::MAX, dbm));
+        }
+        Ok(Rssi(dbm))
+    }
+
+    /// 创建 RSSI，超出范围时截断到边界
+    pub fn saturating(dbm: i16) -> Self {
+        Rssi(dbm.clamp(Self::MIN, Self::MAX))
+    }
+
+    /// dBm 值
+    pub fn dbm(self) -> i16 {
+        self.0
+    }
+
+    /// 功率 (mW)
+    pub fn to_milliwatts(self) -> f64 {
+        10_f64.powf(self.0 as f64 / 10.0)
+    }
+
+    /// 由功率 (mW) 创建，四舍五入到整数 dBm 并截断到有效范围
+    pub fn from_milliwatts(milliwatts: f64) -> Self {
+        if milliwatts <= 0.0 {
+            return Rssi(Self::MIN);
+        }
+        Self::saturating((10.0 * milliwatts.log10()).round() as i16)
+    }
+
+    /// 加上增益（负值为衰减），结果截断到有效范围
+    pub fn offset(self, db: i16) -> Self {
+        Self::saturating(self.0.saturating_add(db))
+    }
+
+    /// 与另一个 RSSI 的差值 (dB)
+    pub fn db_above(self, other: Rssi) -> i16 {
+        self.0 - other.0
+    }
+
+    /// 在功率域求平均（而非直接平均 dBm）
+    ///
+    /// # 返回
+    /// - 平均值，或 None 如果输入为空
+    pub fn power_mean(values: &[Rssi]) -> Option<Rssi> {
+        if values.is_empty() {
+            return None;
+        }
+        let total = values.iter().map(|r| r.to_milliwatts()).sum::<f64>();
+        Some(Self::from_milliwatts(total / values.len() as f64))
+    }
+}
+
+/// 整数按 dBm 截断到有效范围；需要拒绝越界值时使用 [`Rssi::new`]
+impl From<i16> for Rssi {
+    fn from(dbm: i16) -> Self {
+        Self::saturating(dbm)
+    }
+}
+
+/// 整数字面量默认推断为 `i32`，同样按 dBm 截断到有效范围
+impl From<i32> for Rssi {
+    fn from(dbm: i32) -> Self {
+        Self::saturating(dbm.clamp(Self::MIN as i32, Self::MAX as i32) as i16)
+    }
+}
+
+impl From<Rssi> for i16 {
+    fn from(rssi: Rssi) -> Self {
+        rssi.0
+    }
+}
+
+impl From<Rssi> for f64 {
+    fn from(rssi: Rssi) -> Self {
+        rssi.0 as f64
+    }
+}
+
+impl fmt::Display for Rssi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} dBm", self.0)
+    }
+}
+
+impl SignalReadings {
+    /// 获取 RSSI；记录值超出有效范围（如经 `from_hashmap` 直接写入）时返回 None
+    pub fn get_rssi(&self, beacon_id: &str) -> Option<Rssi> {
+        self.get(beacon_id).and_then(|r| Rssi::new(r).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rssi_validation_and_conversions() {
+        assert!(Rssi::new(-60).is_ok());
+        assert!(Rssi::new(21).is_err());
+        assert!(Rssi::new(-128).is_err());
+        assert_eq!(Rssi::saturating(-200).dbm(), -127);
+        assert_eq!(Rssi::from(-200i16).dbm(), -127);
+        assert_eq!(Rssi::from(40_000).dbm(), 20);
+
+        let rssi = Rssi::new(-60).unwrap();
+        assert_eq!(i16::from(rssi), -60);
+        assert_eq!(rssi.to_string(), "-60 dBm");
+        assert_eq!(rssi.offset(-70).dbm(), -127);
+        assert_eq!(Rssi::new(-50).unwrap().db_above(rssi), 10);
+    }
+
+    #[test]
+    fn test_power_mean_and_readings() {
+        let values = [Rssi::new(-50).unwrap(), Rssi::new(-60).unwrap()];
+        // 功率域平均偏向较强的信号
+        assert_eq!(Rssi::power_mean(&values).unwrap().dbm(), -53);
+        assert!(Rssi::power_mean(&[]).is_none());
+
+        let mut readings = SignalReadings::new();
+        readings.add("B1".to_string(), values[0]);
+        readings.add("B2".to_string(), 100);
+        assert_eq!(readings.get_rssi("B1"), Some(values[0]));
+        assert_eq!(readings.get("B2"), Some(Rssi::MAX));
+        let raw = SignalReadings::from_hashmap([("B3".to_string(), 100)].into_iter().collect());
+        assert!(raw.get_rssi("B3").is_none());
+    }
+}
@@ -2,6 +2,8 @@
 /// 
 /// 支持多种 RSSI 模型参数化方式，灵活适配不同数据源
 
+use crate::algorithms::Rssi;
+use std::collections::HashMap;
 use std::fmt;
 
 /// 定位计量单位
@@ -28,6 +30,8 @@ pub struct RSSIModel {
     pub unit: DistanceUnit,
     /// 模型名称/类型
     pub model_type: String,
+    /// 阴影衰落标准差 σ (dB)，0 表示未知
+    pub sigma: f64,
 }
 
 impl RSSIModel {
@@ -46,6 +50,7 @@ impl RSSIModel {
             n: 0.0,
             unit,
             model_type: "log_distance".to_string(),
+            sigma: 0.0,
         }
     }
 
@@ -63,6 +68,7 @@ impl RSSIModel {
             n: 2.0,
             unit,
             model_type: "free_space".to_string(),
+            sigma: 0.0,
         }
     }
 
@@ -79,6 +85,7 @@ impl RSSIModel {
             n,
             unit,
             model_type: "log_normal_shadow".to_string(),
+            sigma: 0.0,
         }
     }
 
@@ -90,6 +97,7 @@ impl RSSIModel {
             n,
             unit,
             model_type: model_type.into(),
+            sigma: 0.0,
         }
     }
 
@@ -102,14 +110,138 @@ impl RSSIModel {
             n,
             unit,
             model_type: "python_fit".to_string(),
+            sigma: 0.0,
         }
     }
 
+    /// 用最小二乘法从标定样本拟合对数距离模型
+    ///
+    /// 拟合 RSSI = A + B * log10(d)，d 以米计
+    ///
+    /// # 参数
+    /// - `samples`: (距离, RSSI) 样本，距离单位为 `unit`
+    /// - `unit`: 距离单位
+    pub fn fit(samples: &[(f64, f64)], unit: DistanceUnit) -> Result<Self, String> {
+        let points: Vec<(f64, f64)> = samples
+            .iter()
+            .filter(|(d, _)| *d > 0.0)
+            .map(|(d, rssi)| (Self::to_meters(*d, unit).log10(), *rssi))
+            .collect();
+
+        if points.len() < 2 {
+            return Err("拟合至少需要 2 个有效样本".to_string());
+        }
+
+        let count = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+        let sxx = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f64>();
+        let sxy = points
+            .iter()
+            .map(|(x, y)| (x - mean_x) * (y - mean_y))
+            .sum::<f64>();
+
+        if sxx < 1e-12 {
+            return Err("样本距离过于集中，无法拟合斜率".to_string());
+        }
+
+        let b = sxy / sxx;
+        let a = mean_y - b * mean_x;
+
+        // 残差标准差即阴影衰落 σ
+        let residual_variance = points
+            .iter()
+            .map(|(x, y)| (y - (a + b * x)).powi(2))
+            .sum::<f64>()
+            / count;
+
+        Ok(RSSIModel::custom(a, b, -b / 10.0, "calibrated", unit).with_sigma(residual_variance.sqrt()))
+    }
+
+    /// 从分组样本拟合共享斜率、按组偏移的模型
+    ///
+    /// 拟合 RSSI = A + o_k + B * log10(d)，各组共享斜率 B，组偏移 o_k 之和为 0。
+    /// A 为各组截距的等权平均，因此某一组样本多不会使模型偏向该组
+    /// （如标定时朝某个方向采集得更多）
+    ///
+    /// # 参数
+    /// - `groups`: 组名 -> (距离, RSSI) 样本
+    /// - `unit`: 距离单位
+    ///
+    /// # 返回
+    /// - (平均模型, 组名 -> 偏移 dB)。平均模型的 σ 为不使用偏移时的残差标准差
+    pub fn fit_grouped(
+        groups: &HashMap<String, Vec<(f64, f64)>>,
+        unit: DistanceUnit,
+    ) -> Result<(Self, HashMap<String, f64>), String> {
+        // 组名 -> [(log10(米), RSSI)]
+        let grouped: Vec<(&String, Vec<(f64, f64)>)> = groups
+            .iter()
+            .map(|(name, samples)| {
+                let points = samples
+                    .iter()
+                    .filter(|(d, _)| *d > 0.0)
+                    .map(|(d, rssi)| (Self::to_meters(*d, unit).log10(), *rssi))
+                    .collect::<Vec<_>>();
+                (name, points)
+            })
+            .filter(|(_, points)| !points.is_empty())
+            .collect();
+
+        if grouped.is_empty() {
+            return Err("拟合至少需要 1 组有效样本".to_string());
+        }
+
+        // 组内中心化后合并求斜率
+        let mut sxx = 0.0;
+        let mut sxy = 0.0;
+        let mut means = Vec::with_capacity(grouped.len());
+        for (_, points) in &grouped {
+            let count = points.len() as f64;
+            let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+            let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+            sxx += points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f64>();
+            sxy += points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f64>();
+            means.push((mean_x, mean_y));
+        }
+        if sxx < 1e-12 {
+            return Err("样本距离过于集中，无法拟合斜率".to_string());
+        }
+
+        let b = sxy / sxx;
+        let intercepts: Vec<f64> = means.iter().map(|(mx, my)| my - b * mx).collect();
+        let a = intercepts.iter().sum::<f64>() / intercepts.len() as f64;
+
+        let total = grouped.iter().map(|(_, p)| p.len()).sum::<usize>() as f64;
+        let residual_variance = grouped
+            .iter()
+            .flat_map(|(_, points)| points.iter())
+            .map(|(x, y)| (y - (a + b * x)).powi(2))
+            .sum::<f64>()
+            / total;
+
+        let offsets = grouped
+            .iter()
+            .zip(&intercepts)
+            .map(|((name, _), intercept)| ((*name).clone(), intercept - a))
+            .collect();
+
+        let model = RSSIModel::custom(a, b, -b / 10.0, "calibrated_grouped", unit)
+            .with_sigma(residual_variance.sqrt());
+        Ok((model, offsets))
+    }
+
+    /// 设置阴影衰落标准差 σ (dB)
+    pub fn with_sigma(mut self, sigma: f64) -> Self {
+        self.sigma = sigma.abs();
+        self
+    }
+
     /// 根据 RSSI 计算距离
     /// 
     /// 反解对数距离模型: d = 10^((RSSI - A) / B)
-    pub fn rssi_to_distance(&self, rssi: i16) -> f64 {
-        let rssi_f64 = rssi as f64;
+    pub fn rssi_to_distance(&self, rssi: impl Into<Rssi>) -> f64 {
+        let rssi_f64 = f64::from(rssi.into());
         let exponent = (rssi_f64 - self.a) / self.b;
         let distance = 10_f64.powf(exponent);
         self.convert_distance(distance, DistanceUnit::Meter)
@@ -122,6 +254,23 @@ impl RSSIModel {
         self.convert_distance(distance, DistanceUnit::Meter)
     }
 
+    /// 根据 RSSI 计算距离的置信区间
+    ///
+    /// 按对数正态阴影模型，RSSI 在真实值附近服从 N(0, σ²) 扰动，
+    /// 取双侧 `confidence_level`（如 0.95）分位数反解得到距离上下界
+    ///
+    /// # 返回
+    /// - (下界, 上界)，单位与模型一致；σ 为 0 时上下界相等
+    pub fn distance_interval(&self, rssi: impl Into<Rssi>, confidence_level: f64) -> (f64, f64) {
+        let rssi = rssi.into().dbm();
+        let level = confidence_level.clamp(0.0, 0.999_999);
+        let z = normal_quantile(0.5 + level / 2.0);
+        let margin = z * self.sigma;
+        let near = self.rssi_to_distance_f64(rssi as f64 + margin);
+        let far = self.rssi_to_distance_f64(rssi as f64 - margin);
+        (near.min(far), near.max(far))
+    }
+
     /// 根据距离计算 RSSI
     pub fn distance_to_rssi(&self, distance: f64) -> f64 {
         let distance_in_meters = self.convert_distance_from(distance);
@@ -149,7 +298,12 @@ impl RSSIModel {
 
     /// 将距离从目标单位转换为米
     fn convert_distance_from(&self, distance: f64) -> f64 {
-        match self.unit {
+        Self::to_meters(distance, self.unit)
+    }
+
+    /// 将指定单位的距离转换为米
+    fn to_meters(distance: f64, unit: DistanceUnit) -> f64 {
+        match unit {
             DistanceUnit::Meter => distance,
             DistanceUnit::Centimeter => distance / 100.0,
             DistanceUnit::Millimeter => distance / 1000.0,
@@ -188,6 +342,60 @@ impl RSSIModel {
     }
 }
 
+/// 标准正态分布分位数（Acklam 有理逼近，相对误差 < 1.2e-9）
+pub(crate) fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.383_577_518_672_69e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+
+    if p <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1.0 {
+        return f64::INFINITY;
+    }
+
+    let p_low = 0.02425;
+    if p < p_low {
+        let q = (-2.0 * p.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    } else if p <= 1.0 - p_low {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        -normal_quantile(1.0 - p)
+    }
+}
+
 impl Default for RSSIModel {
     fn default() -> Self {
         // 默认使用通常的 BLE 参数
@@ -229,4 +437,30 @@ mod tests {
         // 100 cm = 1 m，所以应该转换为 1.0 m
         assert!((distance_m - 1.0).abs() < 0.01);
     }
+
+    #[test]
+    fn test_fit_recovers_parameters() {
+        let truth = RSSIModel::log_distance(-50.0, -30.0, DistanceUnit::Centimeter);
+        let samples: Vec<(f64, f64)> = [50.0, 100.0, 200.0, 400.0, 800.0]
+            .iter()
+            .map(|d| (*d, truth.distance_to_rssi(*d)))
+            .collect();
+
+        let fitted = RSSIModel::fit(&samples, DistanceUnit::Centimeter).unwrap();
+        assert!((fitted.a - (-50.0)).abs() < 1e-6);
+        assert!((fitted.b - (-30.0)).abs() < 1e-6);
+        assert!(RSSIModel::fit(&samples[..1], DistanceUnit::Centimeter).is_err());
+    }
+
+    #[test]
+    fn test_distance_interval() {
+        let model = RSSIModel::log_distance(-50.0, -20.0, DistanceUnit::Meter).with_sigma(4.0);
+        let (low, high) = model.distance_interval(-50, 0.95);
+        // ±1.96σ ≈ ±7.84 dB -> 10^(±0.392)
+        assert!((low - 10_f64.powf(-0.392)).abs() < 1e-3);
+        assert!((high - 10_f64.powf(0.392)).abs() < 1e-3);
+
+        let (low, high) = model.with_sigma(0.0).distance_interval(-50, 0.95);
+        assert_eq!(low, high);
+    }
 }
@@ -0,0 +1,587 @@
+/// 信号统计模块
+///
+/// 按信标保存带时间戳的 RSSI 样本，提供时间窗口内的统计摘要：
+/// - 均值、方差
+/// - 包速率
+/// - 信号中断（dropout）间隔
+/// - 广播间隔估计及按信标自适应的过期窗口
+/// - 按样本新旧指数衰减加权的 RSSI
+/// - 按直方图众数或上分位数聚合的 RSSI
+
+use crate::algorithms::{Clock, Observation, Rssi, SignalReadings, SystemClock};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 单个信标在时间窗口内的统计摘要
+#[derive(Clone, Debug)]
+pub struct WindowStats {
+    /// 信标 ID
+    pub beacon_id: String,
+    /// 窗口内样本数
+    pub sample_count: usize,
+    /// RSSI 均值 (dBm)
+    pub mean: f64,
+    /// RSSI 方差 (dBm²)
+    pub variance: f64,
+    /// 包速率（包/秒）
+    pub packet_rate: f64,
+    /// 中断间隔列表 (起始毫秒, 结束毫秒)，仅包含超过阈值的间隔
+    pub dropout_gaps: Vec<(u64, u64)>,
+    /// 估计的广播间隔（窗口内样本不足时为 None）
+    pub advertising_interval: Option<Duration>,
+}
+
+impl WindowStats {
+    /// RSSI 标准差 (dBm)
+    pub fn std_dev(&self) -> f64 {
+        self.variance.sqrt()
+    }
+
+    /// 最长中断时长（毫秒）
+    pub fn max_gap_ms(&self) -> u64 {
+        self.dropout_gaps
+            .iter()
+            .map(|(start, end)| end - start)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// 信号变化趋势
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignalTrend {
+    /// RSSI 上升，目标正在靠近信标
+    Approaching,
+    /// RSSI 下降，目标正在远离信标
+    Receding,
+    /// 无明显变化
+    Steady,
+}
+
+/// 趋势变化事件
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrendEvent {
+    /// 信标 ID
+    pub beacon_id: String,
+    /// 新的趋势
+    pub trend: SignalTrend,
+    /// RSSI 斜率 (dB/秒)
+    pub slope_db_per_s: f64,
+}
+
+/// 窗口内 RSSI 的聚合方式
+///
+/// 快衰落使单个样本在真实值附近大幅下跌，均值和最新值都会被深衰落样本拉低；
+/// 对缓慢移动的目标，直方图众数或上分位数要稳定得多
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RssiAggregation {
+    /// 均值（默认）
+    #[default]
+    Mean,
+    /// 最新样本
+    Latest,
+    /// 直方图众数（按 1 dB 分箱，并列时取较强者）
+    Mode,
+    /// 上分位数（0.0 ~ 1.0，如 0.75）
+    UpperPercentile(f64),
+}
+
+/// 信号统计器 - 按信标维护最近的 RSSI 样本
+#[derive(Clone, Debug)]
+pub struct SignalStats {
+    /// beacon_id -> (时间戳毫秒, RSSI) 样本队列，按时间升序
+    samples: HashMap<String, VecDeque<(u64, i16)>>,
+    /// 样本保留时长（毫秒）
+    retention_ms: u64,
+    /// 判定为中断的最小间隔（毫秒）
+    dropout_threshold_ms: u64,
+    /// 时间来源
+    clock: Arc<dyn Clock>,
+}
+
+impl SignalStats {
+    /// 创建统计器
+    ///
+    /// # 参数
+    /// - `retention`: 样本保留时长，应不小于最大查询窗口
+    pub fn new(retention: Duration) -> Self {
+        SignalStats {
+            samples: HashMap::new(),
+            retention_ms: retention.as_millis() as u64,
+            dropout_threshold_ms: 1000,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// 使用指定的时钟（测试时可传入 `MockClock`）
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 设置中断判定阈值（默认 1 秒）
+    pub fn with_dropout_threshold(mut self, threshold: Duration) -> Self {
+        self.dropout_threshold_ms = threshold.as_millis() as u64;
+        self
+    }
+
+    /// 记录一条观测；没有时间戳的观测按当前时间记录，非 RSSI 观测被忽略
+    pub fn record(&mut self, observation: impl Into<Observation>) {
+        let observation = observation.into();
+        let Some(measurement) = observation.to_signal_measurement() else {
+            return;
+        };
+        let timestamp_ms = measurement.timestamp_ms.unwrap_or_else(|| self.now_ms());
+        self.record_at(&measurement.beacon_id, measurement.rssi, timestamp_ms);
+    }
+
+    /// 以指定时间戳记录 RSSI 样本
+    pub fn record_at(&mut self, beacon_id: &str, rssi: impl Into<Rssi>, timestamp_ms: u64) {
+        let rssi = rssi.into().dbm();
+        let queue = self.samples.entry(beacon_id.to_string()).or_default();
+
+        // 乱序到达的样本插入到正确位置，保持时间升序
+        let pos = queue.partition_point(|(t, _)| *t <= timestamp_ms);
+        queue.insert(pos, (timestamp_ms, rssi));
+
+        // 清理超过保留时长的样本
+        let newest = queue.back().map(|(t, _)| *t).unwrap_or(timestamp_ms);
+        let cutoff = newest.saturating_sub(self.retention_ms);
+        while queue.front().is_some_and(|(t, _)| *t < cutoff) {
+            queue.pop_front();
+        }
+    }
+
+    /// 获取信标在最近 `duration` 时间内的统计摘要
+    ///
+    /// # 返回
+    /// - 统计摘要，或 None 如果窗口内没有样本
+    pub fn for_window(&self, beacon_id: &str, duration: Duration) -> Option<WindowStats> {
+        let queue = self.samples.get(beacon_id)?;
+        let now = self.now_ms();
+        let window_ms = duration.as_millis() as u64;
+        let start = now.saturating_sub(window_ms);
+
+        let window: Vec<(u64, i16)> = queue
+            .iter()
+            .filter(|(t, _)| *t >= start && *t <= now)
+            .copied()
+            .collect();
+
+        if window.is_empty() {
+            return None;
+        }
+
+        let count = window.len() as f64;
+        let mean = window.iter().map(|(_, r)| *r as f64).sum::<f64>() / count;
+        let variance = window
+            .iter()
+            .map(|(_, r)| (*r as f64 - mean).powi(2))
+            .sum::<f64>()
+            / count;
+
+        let packet_rate = if window_ms > 0 {
+            count / (window_ms as f64 / 1000.0)
+        } else {
+            0.0
+        };
+
+        // 窗口起点、各样本、当前时刻之间的间隔
+        let mut dropout_gaps = Vec::new();
+        let mut previous = start;
+        for (t, _) in window.iter().chain(std::iter::once(&(now, 0))) {
+            if t - previous > self.dropout_threshold_ms {
+                dropout_gaps.push((previous, *t));
+            }
+            previous = *t;
+        }
+
+        let timestamps: Vec<u64> = window.iter().map(|(t, _)| *t).collect();
+        Some(WindowStats {
+            beacon_id: beacon_id.to_string(),
+            sample_count: window.len(),
+            mean,
+            variance,
+            packet_rate,
+            dropout_gaps,
+            advertising_interval: Self::estimate_interval(&timestamps),
+        })
+    }
+
+    /// 估计信标的广播间隔（使用全部保留样本）
+    ///
+    /// # 返回
+    /// - 广播间隔，或 None 如果样本少于 3 个
+    pub fn advertising_interval(&self, beacon_id: &str) -> Option<Duration> {
+        let queue = self.samples.get(beacon_id)?;
+        let timestamps: Vec<u64> = queue.iter().map(|(t, _)| *t).collect();
+        Self::estimate_interval(&timestamps)
+    }
+
+    /// 由到达时间估计广播间隔
+    ///
+    /// 取相邻样本间隔的中位数：丢包只会产生整数倍的长间隔，
+    /// 丢包率低于一半时中位数仍落在真实间隔上。同一时刻的重复样本（间隔 0）被忽略
+    fn estimate_interval(timestamps: &[u64]) -> Option<Duration> {
+        let mut deltas: Vec<u64> = timestamps
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .filter(|d| *d > 0)
+            .collect();
+        if deltas.len() < 2 {
+            return None;
+        }
+        deltas.sort_unstable();
+        Some(Duration::from_millis(deltas[deltas.len() / 2]))
+    }
+
+    /// 信标的过期窗口
+    ///
+    /// 为估计广播间隔的 `multiplier` 倍（即允许连续丢失约 `multiplier` 个包），
+    /// 无法估计时使用 `fallback`
+    pub fn staleness_window(&self, beacon_id: &str, multiplier: f64, fallback: Duration) -> Duration {
+        self.advertising_interval(beacon_id)
+            .map(|interval| interval.mul_f64(multiplier.max(1.0)))
+            .unwrap_or(fallback)
+    }
+
+    /// 各信标未过期的最新 RSSI
+    ///
+    /// 每个信标按自身的过期窗口（参见 [`SignalStats::staleness_window`]）判断，
+    /// 广播频繁的信标比广播稀疏的信标更快过期
+    pub fn fresh_readings(&self, multiplier: f64, fallback: Duration) -> SignalReadings {
+        let now = self.now_ms();
+        let mut readings = SignalReadings::new();
+        for (beacon_id, queue) in &self.samples {
+            let Some(&(t, rssi)) = queue.back() else {
+                continue;
+            };
+            let window_ms = self.staleness_window(beacon_id, multiplier, fallback).as_millis() as u64;
+            if now.saturating_sub(t) <= window_ms {
+                readings.add(beacon_id.clone(), rssi);
+            }
+        }
+        readings
+    }
+
+    /// 最近 `duration` 时间内按样本新旧加权的 RSSI 均值
+    ///
+    /// 样本权重为 0.5^(样本年龄 / half_life)，新样本权重更高，
+    /// 比普通均值更快跟上运动带来的变化；`half_life` 为 0 时只取最新样本
+    ///
+    /// # 返回
+    /// - 加权均值 (dBm)，或 None 如果窗口内没有样本
+    pub fn weighted_rssi(&self, beacon_id: &str, duration: Duration, half_life: Duration) -> Option<f64> {
+        let queue = self.samples.get(beacon_id)?;
+        let now = self.now_ms();
+        let start = now.saturating_sub(duration.as_millis() as u64);
+        let window: Vec<&(u64, i16)> = queue.iter().filter(|(t, _)| *t >= start && *t <= now).collect();
+        let newest = window.last()?;
+
+        let half_life_ms = half_life.as_millis() as f64;
+        if half_life_ms <= 0.0 {
+            return Some(newest.1 as f64);
+        }
+
+        let (sum, weight_sum) = window.iter().fold((0.0, 0.0), |(sum, weight_sum), (t, r)| {
+            let weight = 0.5_f64.powf((now - t) as f64 / half_life_ms);
+            (sum + weight * *r as f64, weight_sum + weight)
+        });
+        Some(sum / weight_sum)
+    }
+
+    /// 所有信标在最近 `duration` 时间内的衰减加权 RSSI，参见 [`SignalStats::weighted_rssi`]
+    pub fn weighted_readings(&self, duration: Duration, half_life: Duration) -> SignalReadings {
+        let mut readings = SignalReadings::new();
+        for beacon_id in self.samples.keys() {
+            if let Some(rssi) = self.weighted_rssi(beacon_id, duration, half_life) {
+                readings.add(beacon_id.clone(), rssi.round() as i16);
+            }
+        }
+        readings
+    }
+
+    /// 最近 `duration` 时间内按指定方式聚合的 RSSI
+    ///
+    /// # 返回
+    /// - 聚合值 (dBm)，或 None 如果窗口内没有样本
+    pub fn aggregate_rssi(&self, beacon_id: &str, duration: Duration, aggregation: RssiAggregation) -> Option<f64> {
+        let queue = self.samples.get(beacon_id)?;
+        let now = self.now_ms();
+        let start = now.saturating_sub(duration.as_millis() as u64);
+        let mut window: Vec<i16> = queue.iter().filter(|(t, _)| *t >= start && *t <= now).map(|(_, r)| *r).collect();
+        let newest = *window.last()?;
+
+        match aggregation {
+            RssiAggregation::Mean => Some(window.iter().map(|r| *r as f64).sum::<f64>() / window.len() as f64),
+            RssiAggregation::Latest => Some(newest as f64),
+            RssiAggregation::Mode => {
+                let mut counts: HashMap<i16, usize> = HashMap::new();
+                for rssi in &window {
+                    *counts.entry(*rssi).or_default() += 1;
+                }
+                counts.into_iter().max_by_key(|(rssi, count)| (*count, *rssi)).map(|(rssi, _)| rssi as f64)
+            }
+            RssiAggregation::UpperPercentile(p) => {
+                window.sort_unstable();
+                let rank = (p.clamp(0.0, 1.0) * (window.len() - 1) as f64).round() as usize;
+                Some(window[rank] as f64)
+            }
+        }
+    }
+
+    /// 所有信标在最近 `duration` 时间内的原始 RSSI 样本（按时间顺序），
+    /// 可交给 [`DistanceEstimator::estimate`](crate::algorithms::DistanceEstimator::estimate) 或
+    /// [`LocationAlgorithm::trilateration_estimated`](crate::algorithms::LocationAlgorithm::trilateration_estimated)
+    pub fn rssi_windows(&self, duration: Duration) -> HashMap<String, Vec<i16>> {
+        let now = self.now_ms();
+        let start = now.saturating_sub(duration.as_millis() as u64);
+        self.samples
+            .iter()
+            .map(|(id, queue)| (id.clone(), queue.iter().filter(|(t, _)| *t >= start && *t <= now).map(|(_, r)| *r).collect::<Vec<_>>()))
+            .filter(|(_, window)| !window.is_empty())
+            .collect()
+    }
+
+    /// 所有信标在最近 `duration` 时间内按指定方式聚合的 RSSI，参见 [`SignalStats::aggregate_rssi`]
+    pub fn aggregated_readings(&self, duration: Duration, aggregation: RssiAggregation) -> SignalReadings {
+        let mut readings = SignalReadings::new();
+        for beacon_id in self.samples.keys() {
+            if let Some(rssi) = self.aggregate_rssi(beacon_id, duration, aggregation) {
+                readings.add(beacon_id.clone(), rssi.round() as i16);
+            }
+        }
+        readings
+    }
+
+    /// 最近 `duration` 时间内 RSSI 随时间的线性回归斜率 (dB/秒)
+    ///
+    /// # 返回
+    /// - 斜率，或 None 如果样本少于 3 个或时间跨度为 0
+    pub fn slope(&self, beacon_id: &str, duration: Duration) -> Option<f64> {
+        let queue = self.samples.get(beacon_id)?;
+        let now = self.now_ms();
+        let start = now.saturating_sub(duration.as_millis() as u64);
+        let window: Vec<(f64, f64)> = queue
+            .iter()
+            .filter(|(t, _)| *t >= start && *t <= now)
+            .map(|(t, r)| ((*t - start) as f64 / 1000.0, *r as f64))
+            .collect();
+
+        if window.len() < 3 {
+            return None;
+        }
+
+        let count = window.len() as f64;
+        let mean_t = window.iter().map(|(t, _)| t).sum::<f64>() / count;
+        let mean_r = window.iter().map(|(_, r)| r).sum::<f64>() / count;
+        let stt = window.iter().map(|(t, _)| (t - mean_t).powi(2)).sum::<f64>();
+        if stt < 1e-12 {
+            return None;
+        }
+        let str_cov = window
+            .iter()
+            .map(|(t, r)| (t - mean_t) * (r - mean_r))
+            .sum::<f64>();
+        Some(str_cov / stt)
+    }
+
+    /// 判断信标的信号趋势
+    ///
+    /// # 参数
+    /// - `duration`: 回归窗口
+    /// - `threshold_db_per_s`: 斜率绝对值超过该阈值才判定为靠近/远离
+    pub fn trend(&self, beacon_id: &str, duration: Duration, threshold_db_per_s: f64) -> Option<SignalTrend> {
+        let slope = self.slope(beacon_id, duration)?;
+        Some(if slope > threshold_db_per_s {
+            SignalTrend::Approaching
+        } else if slope < -threshold_db_per_s {
+            SignalTrend::Receding
+        } else {
+            SignalTrend::Steady
+        })
+    }
+
+    /// 获取所有有样本记录的信标 ID
+    pub fn beacon_ids(&self) -> Vec<&String> {
+        self.samples.keys().collect()
+    }
+
+    /// 清空所有样本
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    fn now_ms(&self) -> u64 {
+        self.clock.now_ms()
+    }
+}
+
+impl Default for SignalStats {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(60))
+    }
+}
+
+/// 趋势检测器 - 在信标趋势发生变化时产生事件
+#[derive(Clone, Debug)]
+pub struct TrendDetector {
+    /// 回归窗口
+    window: Duration,
+    /// 斜率阈值 (dB/秒)
+    threshold_db_per_s: f64,
+    /// 每个信标最近一次的趋势
+    last: HashMap<String, SignalTrend>,
+}
+
+impl TrendDetector {
+    /// 创建趋势检测器
+    pub fn new(window: Duration, threshold_db_per_s: f64) -> Self {
+        TrendDetector {
+            window,
+            threshold_db_per_s,
+            last: HashMap::new(),
+        }
+    }
+
+    /// 根据最新统计更新趋势，返回发生变化的信标事件
+    pub fn update(&mut self, stats: &SignalStats) -> Vec<TrendEvent> {
+        let mut events = Vec::new();
+        for beacon_id in stats.beacon_ids() {
+            let Some(slope) = stats.slope(beacon_id, self.window) else {
+                continue;
+            };
+            let trend = stats
+                .trend(beacon_id, self.window, self.threshold_db_per_s)
+                .unwrap_or(SignalTrend::Steady);
+            if self.last.get(beacon_id) != Some(&trend) {
+                self.last.insert(beacon_id.clone(), trend);
+                events.push(TrendEvent {
+                    beacon_id: beacon_id.clone(),
+                    trend,
+                    slope_db_per_s: slope,
+                });
+            }
+        }
+        events
+    }
+
+    /// 获取信标当前趋势
+    pub fn current(&self, beacon_id: &str) -> Option<SignalTrend> {
+        self.last.get(beacon_id).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::MockClock;
+
+    #[test]
+    fn test_window_mean_and_variance() {
+        let now = 100_000;
+        let mut stats = SignalStats::default().with_clock(Arc::new(MockClock::new(now)));
+        stats.record_at("B1", -50, now - 300);
+        stats.record_at("B1", -60, now - 200);
+        stats.record_at("B1", -70, now - 100);
+
+        let window = stats.for_window("B1", Duration::from_secs(1)).unwrap();
+        assert_eq!(window.sample_count, 3);
+        assert!((window.mean - (-60.0)).abs() < 1e-9);
+        assert!((window.variance - 200.0 / 3.0).abs() < 1e-9);
+        assert!((window.packet_rate - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_window_dropout_gaps() {
+        let clock = MockClock::new(100_000);
+        let mut stats = SignalStats::default().with_clock(Arc::new(clock.clone()));
+        stats.record_at("B1", -55, 90_500);
+        stats.record_at("B1", -56, 97_000);
+
+        let window = stats.for_window("B1", Duration::from_secs(10)).unwrap();
+        assert_eq!(window.dropout_gaps, vec![(90_500, 97_000), (97_000, 100_000)]);
+
+        clock.advance(Duration::from_secs(5));
+        let window = stats.for_window("B1", Duration::from_secs(10)).unwrap();
+        assert_eq!(window.sample_count, 1);
+        assert!(stats.for_window("B2", Duration::from_secs(10)).is_none());
+    }
+
+    #[test]
+    fn test_trend_detection_events() {
+        let clock = MockClock::new(100_000);
+        let mut stats = SignalStats::default().with_clock(Arc::new(clock.clone()));
+        for i in 0..5 {
+            stats.record_at("B1", -80 + i * 4, 96_000 + i as u64 * 1_000);
+        }
+
+        let mut detector = TrendDetector::new(Duration::from_secs(5), 1.0);
+        let events = detector.update(&stats);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].trend, SignalTrend::Approaching);
+        assert!((events[0].slope_db_per_s - 4.0).abs() < 1e-9);
+
+        // 趋势未变化时不重复产生事件
+        assert!(detector.update(&stats).is_empty());
+    }
+
+    #[test]
+    fn test_advertising_interval_and_fresh_readings() {
+        let clock = MockClock::new(100_000);
+        let mut stats = SignalStats::default().with_clock(Arc::new(clock.clone()));
+        // B1 每 100 毫秒广播一次，偶有丢包；B2 每 1 秒广播一次
+        for t in (90_000..=99_900u64).step_by(100).filter(|t| t % 700 != 0) {
+            stats.record_at("B1", -60, t);
+        }
+        for t in (90_000..=99_000u64).step_by(1_000) {
+            stats.record_at("B2", -70, t);
+        }
+
+        assert_eq!(stats.advertising_interval("B1"), Some(Duration::from_millis(100)));
+        assert_eq!(stats.advertising_interval("B2"), Some(Duration::from_secs(1)));
+        let window = stats.for_window("B2", Duration::from_secs(5)).unwrap();
+        assert_eq!(window.advertising_interval, Some(Duration::from_secs(1)));
+
+        // 1.5 秒后：B1 已过期（5 × 100 毫秒），B2 仍有效（5 × 1 秒）
+        clock.advance(Duration::from_millis(1_500));
+        let readings = stats.fresh_readings(5.0, Duration::from_secs(3));
+        assert!(!readings.contains("B1"));
+        assert_eq!(readings.get("B2"), Some(-70));
+    }
+
+    #[test]
+    fn test_weighted_rssi_favors_recent_samples() {
+        let now = 100_000;
+        let mut stats = SignalStats::default().with_clock(Arc::new(MockClock::new(now)));
+        stats.record_at("B1", -80, now - 2_000);
+        stats.record_at("B1", -60, now);
+
+        let window = Duration::from_secs(5);
+        // 半衰期 1 秒：旧样本权重 0.25
+        let weighted = stats.weighted_rssi("B1", window, Duration::from_secs(1)).unwrap();
+        assert!((weighted - (-64.0)).abs() < 1e-9);
+        assert_eq!(stats.weighted_rssi("B1", window, Duration::ZERO), Some(-60.0));
+        assert_eq!(stats.weighted_readings(window, Duration::from_secs(1)).get("B1"), Some(-64));
+    }
+
+    #[test]
+    fn test_histogram_aggregation_resists_fades() {
+        let now = 100_000;
+        let mut stats = SignalStats::default().with_clock(Arc::new(MockClock::new(now)));
+        // 稳定在 -60 附近，夹杂深衰落样本，最新一个样本恰好是衰落
+        for (i, rssi) in [-60, -61, -60, -75, -59, -60, -82, -61, -60, -78].iter().enumerate() {
+            stats.record_at("B1", *rssi, now - 1_000 + i as u64 * 100);
+        }
+
+        let window = Duration::from_secs(2);
+        assert_eq!(stats.aggregate_rssi("B1", window, RssiAggregation::Latest), Some(-78.0));
+        assert!(stats.aggregate_rssi("B1", window, RssiAggregation::Mean).unwrap() < -64.0);
+        assert_eq!(stats.aggregate_rssi("B1", window, RssiAggregation::Mode), Some(-60.0));
+        assert_eq!(stats.aggregate_rssi("B1", window, RssiAggregation::UpperPercentile(0.75)), Some(-60.0));
+        assert_eq!(stats.aggregate_rssi("B1", window, RssiAggregation::UpperPercentile(1.0)), Some(-59.0));
+        assert_eq!(stats.aggregated_readings(window, RssiAggregation::Mode).get("B1"), Some(-60));
+        assert!(stats.aggregate_rssi("B2", window, RssiAggregation::Mode).is_none());
+    }
+}
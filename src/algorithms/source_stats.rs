@@ -0,0 +1,149 @@
+/// 按观测来源的统计
+///
+/// 本地扫描与远程网关混合部署时，按来源（适配器、网关、回放、模拟器）分别统计
+/// 观测数量、被忽略比例、速率和最近收到时间，用于判断哪一路数据源出了问题
+
+use crate::algorithms::{Observation, ObservationKind, ObservationSource};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// 单个来源的统计
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceCounters {
+    /// 来源
+    pub source: ObservationSource,
+    /// 被接收的观测数
+    pub accepted: u64,
+    /// 被忽略的观测数（类型不符或目标未知）
+    pub ignored: u64,
+    /// 首次收到的时间（毫秒）
+    pub first_seen_ms: u64,
+    /// 最近收到的时间（毫秒）
+    pub last_seen_ms: u64,
+    /// 被接收的 RSSI 观测之和与数量
+    rssi_sum: f64,
+    rssi_count: u64,
+}
+
+impl SourceCounters {
+    fn new(source: ObservationSource, now_ms: u64) -> Self {
+        SourceCounters {
+            source,
+            accepted: 0,
+            ignored: 0,
+            first_seen_ms: now_ms,
+            last_seen_ms: now_ms,
+            rssi_sum: 0.0,
+            rssi_count: 0,
+        }
+    }
+
+    /// 观测总数
+    pub fn total(&self) -> u64 {
+        self.accepted + self.ignored
+    }
+
+    /// 被忽略的比例
+    pub fn ignored_ratio(&self) -> f64 {
+        if self.total() == 0 {
+            return 0.0;
+        }
+        self.ignored as f64 / self.total() as f64
+    }
+
+    /// 被接收的 RSSI 观测均值 (dBm)
+    pub fn mean_rssi(&self) -> Option<f64> {
+        (self.rssi_count > 0).then(|| self.rssi_sum / self.rssi_count as f64)
+    }
+
+    /// 平均观测速率（条/秒），收到时间跨度不足 1 毫秒时为 None
+    pub fn rate_per_s(&self) -> Option<f64> {
+        let span_ms = self.last_seen_ms.saturating_sub(self.first_seen_ms);
+        (span_ms > 0).then(|| self.total() as f64 * 1000.0 / span_ms as f64)
+    }
+
+    /// 距最近一次收到经过的时间
+    pub fn silence(&self, now_ms: u64) -> Duration {
+        Duration::from_millis(now_ms.saturating_sub(self.last_seen_ms))
+    }
+}
+
+/// 按来源的统计集合
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SourceStats {
+    /// 来源名称（`ObservationSource` 的显示形式）-> 统计
+    sources: BTreeMap<String, SourceCounters>,
+}
+
+impl SourceStats {
+    /// 创建空统计
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一条观测
+    ///
+    /// # 参数
+    /// - `observation`: 观测
+    /// - `accepted`: 是否被接收
+    /// - `now_ms`: 收到时间（毫秒）
+    pub fn record(&mut self, observation: &Observation, accepted: bool, now_ms: u64) {
+        let counters = self
+            .sources
+            .entry(observation.source.to_string())
+            .or_insert_with(|| SourceCounters::new(observation.source.clone(), now_ms));
+        counters.last_seen_ms = counters.last_seen_ms.max(now_ms);
+        if accepted {
+            counters.accepted += 1;
+            if observation.kind == ObservationKind::Rssi {
+                counters.rssi_sum += observation.value;
+                counters.rssi_count += 1;
+            }
+        } else {
+            counters.ignored += 1;
+        }
+    }
+
+    /// 某来源的统计
+    pub fn get(&self, source: &ObservationSource) -> Option<&SourceCounters> {
+        self.sources.get(&source.to_string())
+    }
+
+    /// 所有来源的统计，按来源名称排序
+    pub fn all(&self) -> Vec<&SourceCounters> {
+        self.sources.values().collect()
+    }
+
+    /// 超过 `max_silence` 未收到观测的来源
+    pub fn silent_sources(&self, now_ms: u64, max_silence: Duration) -> Vec<&SourceCounters> {
+        self.sources.values().filter(|c| c.silence(now_ms) > max_silence).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_per_source_counters() {
+        let local = ObservationSource::Scanner { adapter: "hci0".to_string() };
+        let remote = ObservationSource::Gateway { gateway: "gw-2".to_string() };
+        let mut stats = SourceStats::new();
+
+        stats.record(&Observation::rssi(local.clone(), "B1", -60, None), true, 1_000);
+        stats.record(&Observation::rssi(local.clone(), "B1", -70, None), true, 2_000);
+        stats.record(&Observation::rssi(local.clone(), "X9", -50, None), false, 3_000);
+        stats.record(&Observation::rssi(remote.clone(), "B2", -80, None), true, 1_500);
+
+        let counters = stats.get(&local).unwrap();
+        assert_eq!(counters.total(), 3);
+        assert_eq!(counters.mean_rssi(), Some(-65.0));
+        assert!((counters.ignored_ratio() - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(counters.rate_per_s(), Some(1.5));
+        assert!(stats.get(&remote).unwrap().rate_per_s().is_none());
+
+        let silent = stats.silent_sources(10_000, Duration::from_secs(8));
+        assert_eq!(silent.len(), 1);
+        assert_eq!(silent[0].source, remote);
+    }
+}
@@ -0,0 +1,231 @@
+/// 运行时检测信标对调或标注错误
+///
+/// 两个信标被实际互换安装时，定位只会悄悄变差而不会报错。这里对每对同时收到的信标，
+/// 比较观测距离与配置位置的残差、以及假设两者位置互换后的残差；
+/// 若互换后的残差持续明显更小，则怀疑这两个信标被对调，发出告警而不是继续默默降低精度
+
+use crate::algorithms::{BeaconSet, BlunavEvent, EventBus, LocationResult, RSSIModel, SignalReadings};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// 对调嫌疑
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapSuspicion {
+    /// 信标 A（ID 较小者）
+    pub beacon_a: String,
+    /// 信标 B
+    pub beacon_b: String,
+    /// 参与比较的样本数
+    pub samples: usize,
+    /// 互换后残差更小的样本比例
+    pub swapped_ratio: f64,
+    /// 按配置位置的平均残差（对数距离比的平方）
+    pub residual: f64,
+    /// 假设互换后的平均残差
+    pub swapped_residual: f64,
+}
+
+/// 一对信标的累计统计
+#[derive(Clone, Debug, Default)]
+struct PairEvidence {
+    samples: usize,
+    swapped_better: usize,
+    residual_sum: f64,
+    swapped_sum: f64,
+}
+
+/// 信标对调检测器
+#[derive(Clone, Debug)]
+pub struct SwapDetector {
+    beacons: BeaconSet,
+    model: RSSIModel,
+    /// 给出结论前的最少样本数
+    min_samples: usize,
+    /// 互换后残差须低于原残差的比例
+    max_residual_ratio: f64,
+    /// 互换后残差更小的样本须达到的比例
+    min_swapped_ratio: f64,
+    evidence: BTreeMap<(String, String), PairEvidence>,
+    /// 已告警的信标对
+    reported: BTreeSet<(String, String)>,
+    events: Option<EventBus>,
+}
+
+impl SwapDetector {
+    /// 创建检测器（默认至少 20 个样本、互换后残差低于一半、80% 的样本支持互换）
+    ///
+    /// # 参数
+    /// - `beacons`: 配置的信标位置
+    /// - `model`: 测距模型，单位须与信标坐标一致
+    pub fn new(beacons: BeaconSet, model: RSSIModel) -> Self {
+        SwapDetector {
+            beacons,
+            model,
+            min_samples: 20,
+            max_residual_ratio: 0.5,
+            min_swapped_ratio: 0.8,
+            evidence: BTreeMap::new(),
+            reported: BTreeSet::new(),
+            events: None,
+        }
+    }
+
+    /// 设置给出结论前的最少样本数
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples.max(1);
+        self
+    }
+
+    /// 设置判定阈值
+    ///
+    /// # 参数
+    /// - `max_residual_ratio`: 互换后平均残差须低于原平均残差的比例
+    /// - `min_swapped_ratio`: 互换后残差更小的样本须达到的比例
+    pub fn with_thresholds(mut self, max_residual_ratio: f64, min_swapped_ratio: f64) -> Self {
+        self.max_residual_ratio = max_residual_ratio;
+        self.min_swapped_ratio = min_swapped_ratio;
+        self
+    }
+
+    /// 设置事件总线，新的嫌疑以 `BeaconSwapSuspected` 事件发布
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// 加入一个位置（定位结果或已知位置）及当时的信号读数
+    ///
+    /// # 返回
+    /// - 本次新出现的对调嫌疑，每对信标只报告一次
+    pub fn observe(&mut self, position: &LocationResult, signals: &SignalReadings) -> Vec<SwapSuspicion> {
+        let mut heard: Vec<(&str, f64, f64)> = signals
+            .all()
+            .iter()
+            .filter_map(|(id, rssi)| {
+                let beacon = self.beacons.get(id)?;
+                let geometric = ((position.x - beacon.x).powi(2)
+                    + (position.y - beacon.y).powi(2)
+                    + (position.z - beacon.z).powi(2))
+                .sqrt();
+                let observed = self.model.rssi_to_distance(*rssi);
+                (geometric > 0.0 && observed > 0.0).then_some((id.as_str(), observed, geometric))
+            })
+            .collect();
+        heard.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut touched = Vec::new();
+        for (i, (id_a, observed_a, geometric_a)) in heard.iter().enumerate() {
+            for (id_b, observed_b, geometric_b) in &heard[i + 1..] {
+                let residual = log_residual(*observed_a, *geometric_a) + log_residual(*observed_b, *geometric_b);
+                let swapped = log_residual(*observed_a, *geometric_b) + log_residual(*observed_b, *geometric_a);
+                let key = (id_a.to_string(), id_b.to_string());
+                let evidence = self.evidence.entry(key.clone()).or_default();
+                evidence.samples += 1;
+                evidence.residual_sum += residual;
+                evidence.swapped_sum += swapped;
+                if swapped < residual {
+                    evidence.swapped_better += 1;
+                }
+                touched.push(key);
+            }
+        }
+
+        let mut raised = Vec::new();
+        for key in touched {
+            if self.reported.contains(&key) {
+                continue;
+            }
+            if let Some(suspicion) = self.judge(&key) {
+                self.reported.insert(key);
+                if let Some(events) = &self.events {
+                    events.emit(BlunavEvent::BeaconSwapSuspected {
+                        beacon_a: suspicion.beacon_a.clone(),
+                        beacon_b: suspicion.beacon_b.clone(),
+                    });
+                }
+                raised.push(suspicion);
+            }
+        }
+        raised
+    }
+
+    /// 当前所有满足判定条件的信标对
+    pub fn suspicions(&self) -> Vec<SwapSuspicion> {
+        self.evidence.keys().filter_map(|key| self.judge(key)).collect()
+    }
+
+    /// 清除某对信标的统计（如已现场核实或更正配置后）
+    pub fn clear_pair(&mut self, beacon_a: &str, beacon_b: &str) {
+        let key = if beacon_a <= beacon_b {
+            (beacon_a.to_string(), beacon_b.to_string())
+        } else {
+            (beacon_b.to_string(), beacon_a.to_string())
+        };
+        self.evidence.remove(&key);
+        self.reported.remove(&key);
+    }
+
+    fn judge(&self, key: &(String, String)) -> Option<SwapSuspicion> {
+        let evidence = self.evidence.get(key)?;
+        if evidence.samples < self.min_samples {
+            return None;
+        }
+        let residual = evidence.residual_sum / evidence.samples as f64;
+        let swapped_residual = evidence.swapped_sum / evidence.samples as f64;
+        let swapped_ratio = evidence.swapped_better as f64 / evidence.samples as f64;
+        (swapped_residual < residual * self.max_residual_ratio && swapped_ratio >= self.min_swapped_ratio).then(|| {
+            SwapSuspicion {
+                beacon_a: key.0.clone(),
+                beacon_b: key.1.clone(),
+                samples: evidence.samples,
+                swapped_ratio,
+                residual,
+                swapped_residual,
+            }
+        })
+    }
+}
+
+/// 对数距离比的平方，对远近距离的误差同等看待
+fn log_residual(observed: f64, geometric: f64) -> f64 {
+    (observed / geometric).ln().powi(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::Beacon;
+
+    #[test]
+    fn test_detects_swapped_pair() {
+        let beacons = BeaconSet::from_vec(vec![
+            Beacon::new("B1".to_string(), "b1".to_string(), 0.0, 0.0, 0.0),
+            Beacon::new("B2".to_string(), "b2".to_string(), 1000.0, 0.0, 0.0),
+            Beacon::new("B3".to_string(), "b3".to_string(), 0.0, 1000.0, 0.0),
+        ]);
+        let model = RSSIModel::default();
+        let bus = EventBus::new(16);
+        let mut receiver = bus.subscribe();
+        let mut detector = SwapDetector::new(beacons, model.clone()).with_min_samples(10).with_events(bus);
+
+        // B1 与 B2 实际装反：在 B1 配置位置附近收到的是 B2 的强信号
+        let rssi = |d: f64| model.distance_to_rssi(d).round() as i16;
+        let mut raised = Vec::new();
+        for step in 0..12 {
+            let x = 100.0 + step as f64 * 20.0;
+            let position = LocationResult::new(x, 100.0, 0.0, 0.9, 10.0, "truth".to_string(), 3);
+            let d1 = (x.powi(2) + 100.0_f64.powi(2)).sqrt();
+            let d2 = ((1000.0 - x).powi(2) + 100.0_f64.powi(2)).sqrt();
+            let d3 = (x.powi(2) + 900.0_f64.powi(2)).sqrt();
+            let signals = SignalReadings::from_pairs(vec![("B1", rssi(d2)), ("B2", rssi(d1)), ("B3", rssi(d3))]);
+            raised.extend(detector.observe(&position, &signals));
+        }
+
+        assert_eq!(raised.len(), 1);
+        assert_eq!((raised[0].beacon_a.as_str(), raised[0].beacon_b.as_str()), ("B1", "B2"));
+        assert_eq!(detector.suspicions().len(), 1);
+        assert_eq!(receiver.try_recv().unwrap().kind(), "beacon_swap_suspected");
+
+        detector.clear_pair("B2", "B1");
+        assert!(detector.suspicions().is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+/// 定位不确定度的可视化数据
+///
+/// 将位置协方差转换为误差椭圆，并生成近似椭圆的折线（平面坐标），
+/// 可直接放入推送给看板的数据中，前端无需重复计算
+
+use crate::algorithms::LocationResult;
+use serde::Serialize;
+use std::f64::consts::TAU;
+
+/// 误差椭圆
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct ErrorEllipse {
+    /// 长半轴（坐标单位）
+    pub semi_major: f64,
+    /// 短半轴（坐标单位）
+    pub semi_minor: f64,
+    /// 长轴与 x 轴的夹角（弧度）
+    pub orientation: f64,
+}
+
+impl ErrorEllipse {
+    /// 圆形误差区域
+    pub fn circle(radius: f64) -> Self {
+        ErrorEllipse {
+            semi_major: radius.abs(),
+            semi_minor: radius.abs(),
+            orientation: 0.0,
+        }
+    }
+
+    /// 由二维位置协方差创建
+    ///
+    /// # 参数
+    /// - `cxx`, `cxy`, `cyy`: 协方差矩阵元素
+    /// - `k_sigma`: 半轴为标准差的倍数（1 为 1σ，约 39% 概率；2.45 约 95%）
+    pub fn from_covariance(cxx: f64, cxy: f64, cyy: f64, k_sigma: f64) -> Self {
+        // 2x2 对称矩阵的特征值
+        let mean = (cxx + cyy) / 2.0;
+        let diff = ((cxx - cyy) / 2.0).hypot(cxy);
+        let major = (mean + diff).max(0.0);
+        let minor = (mean - diff).max(0.0);
+        ErrorEllipse {
+            semi_major: k_sigma * major.sqrt(),
+            semi_minor: k_sigma * minor.sqrt(),
+            orientation: 0.5 * (2.0 * cxy).atan2(cxx - cyy),
+        }
+    }
+
+    /// 由信标几何估计协方差：Σ = σ² · (HᵀH)⁻¹，H 的每行为信标指向位置的单位向量
+    ///
+    /// 信标集中在一侧时椭圆沿远离信标的方向拉长（几何精度因子变差）
+    ///
+    /// # 参数
+    /// - `position`: 估计位置 (x, y)
+    /// - `beacons`: 参与定位的信标 (x, y)
+    /// - `range_std`: 测距标准差（坐标单位）
+    /// - `k_sigma`: 半轴为标准差的倍数
+    ///
+    /// # 返回
+    /// - 误差椭圆，或 None 如果信标少于 2 个或共线
+    pub fn from_geometry(position: (f64, f64), beacons: &[(f64, f64)], range_std: f64, k_sigma: f64) -> Option<Self> {
+        let mut hth = [[0.0; 2]; 2];
+        let mut rows = 0;
+        for (bx, by) in beacons {
+            let (dx, dy) = (position.0 - bx, position.1 - by);
+            let norm = dx.hypot(dy);
+            if norm < 1e-9 {
+                continue;
+            }
+            let (ux, uy) = (dx / norm, dy / norm);
+            hth[0][0] += ux * ux;
+            hth[0][1] += ux * uy;
+            hth[1][1] += uy * uy;
+            rows += 1;
+        }
+        let det = hth[0][0] * hth[1][1] - hth[0][1] * hth[0][1];
+        if rows < 2 || det.abs() < 1e-9 {
+            return None;
+        }
+        let variance = range_std * range_std;
+        Some(Self::from_covariance(
+            variance * hth[1][1] / det,
+            -variance * hth[0][1] / det,
+            variance * hth[0][0] / det,
+            k_sigma,
+        ))
+    }
+
+    /// 以 (cx, cy) 为中心近似椭圆的闭合折线，首尾点相同，共 `n_points + 1` 个点
+    pub fn polygon(&self, cx: f64, cy: f64, n_points: usize) -> Vec<(f64, f64)> {
+        let n = n_points.max(3);
+        let (sin_o, cos_o) = self.orientation.sin_cos();
+        (0..=n)
+            .map(|i| {
+                let t = TAU * (i % n) as f64 / n as f64;
+                let (a, b) = (self.semi_major * t.cos(), self.semi_minor * t.sin());
+                (cx + a * cos_o - b * sin_o, cy + a * sin_o + b * cos_o)
+            })
+            .collect()
+    }
+
+    /// 椭圆面积
+    pub fn area(&self) -> f64 {
+        std::f64::consts::PI * self.semi_major * self.semi_minor
+    }
+}
+
+impl LocationResult {
+    /// 误差椭圆：结果只带有标量误差，按半径为 `error` 的圆处理
+    pub fn error_ellipse(&self) -> ErrorEllipse {
+        ErrorEllipse::circle(self.error)
+    }
+
+    /// 近似误差椭圆的闭合折线（平面坐标），参见 [`ErrorEllipse::polygon`]
+    pub fn error_ellipse_polygon(&self, n_points: usize) -> Vec<(f64, f64)> {
+        self.error_ellipse().polygon(self.x, self.y, n_points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ellipse_from_covariance_and_polygon() {
+        let ellipse = ErrorEllipse::from_covariance(400.0, 0.0, 100.0, 1.0);
+        assert!((ellipse.semi_major - 20.0).abs() < 1e-9);
+        assert!((ellipse.semi_minor - 10.0).abs() < 1e-9);
+        assert!(ellipse.orientation.abs() < 1e-9);
+
+        let polygon = ellipse.polygon(100.0, 50.0, 4);
+        assert_eq!(polygon.len(), 5);
+        assert!((polygon[0].0 - 120.0).abs() < 1e-9 && (polygon[0].1 - 50.0).abs() < 1e-9);
+        assert!((polygon[1].1 - 60.0).abs() < 1e-9);
+        assert_eq!(polygon[0], polygon[4]);
+
+        let result = LocationResult::new(0.0, 0.0, 0.0, 0.8, 30.0, "m".to_string(), 3);
+        let circle = result.error_ellipse_polygon(8);
+        assert!(circle.iter().all(|(x, y)| (x.hypot(*y) - 30.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_geometry_elongates_ellipse() {
+        // 信标都在 x 轴方向：x 方向约束好，y 方向约束差
+        let beacons = [(1000.0, 10.0), (1000.0, -10.0), (900.0, 0.0)];
+        let ellipse = ErrorEllipse::from_geometry((0.0, 0.0), &beacons, 10.0, 1.0).unwrap();
+        assert!(ellipse.semi_major > 5.0 * ellipse.semi_minor);
+        assert!((ellipse.orientation.abs() - std::f64::consts::FRAC_PI_2).abs() < 1e-6);
+
+        let surrounding = [(100.0, 0.0), (-100.0, 0.0), (0.0, 100.0), (0.0, -100.0)];
+        let ellipse = ErrorEllipse::from_geometry((0.0, 0.0), &surrounding, 10.0, 1.0).unwrap();
+        assert!((ellipse.semi_major - ellipse.semi_minor).abs() < 1e-9);
+        assert!(ErrorEllipse::from_geometry((0.0, 0.0), &[(1.0, 0.0)], 10.0, 1.0).is_none());
+    }
+}
@@ -0,0 +1,172 @@
+/// 坐标单位推断与一致性检查
+///
+/// 信标坐标用米而测距模型用厘米（或反之）是定位结果离谱最常见的原因，且不会报任何错误。
+/// 这里按信标间距的量级推断坐标单位，并检查模型的测距范围是否合理
+
+use crate::algorithms::{BeaconSet, DistanceUnit, FloorPlanAnnotation, RSSIModel};
+use std::fmt;
+
+/// 用于检查模型测距范围的参考 RSSI (dBm)，典型室内场景对应数米
+const REFERENCE_RSSI: i16 = -75;
+
+/// 单位检查发现的问题
+#[derive(Clone, Debug, PartialEq)]
+pub enum UnitWarning {
+    /// 按信标间距推断的坐标单位与模型单位不一致
+    CoordinateUnitMismatch {
+        /// 推断的坐标单位
+        inferred: DistanceUnit,
+        /// 模型单位
+        model: DistanceUnit,
+        /// 相邻信标间距的中位数（坐标单位）
+        median_spacing: f64,
+    },
+    /// 模型在参考 RSSI 下的测距结果超出室内常见范围
+    ImplausibleModelRange {
+        /// 参考 RSSI (dBm)
+        rssi: i16,
+        /// 换算为米的距离
+        distance_m: f64,
+    },
+}
+
+impl UnitWarning {
+    /// 是否应视为错误（坐标与模型单位不一致）
+    pub fn is_error(&self) -> bool {
+        matches!(self, UnitWarning::CoordinateUnitMismatch { .. })
+    }
+}
+
+impl fmt::Display for UnitWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnitWarning::CoordinateUnitMismatch { inferred, model, median_spacing } => write!(
+                f,
+                "信标间距中位数 {:.2} 看起来以 {:?} 为单位，但测距模型使用 {:?}",
+                median_spacing, inferred, model
+            ),
+            UnitWarning::ImplausibleModelRange { rssi, distance_m } => write!(
+                f,
+                "测距模型在 {} dBm 时给出 {:.2} 米，超出室内常见范围，请检查模型参数或单位",
+                rssi, distance_m
+            ),
+        }
+    }
+}
+
+/// 相邻信标间距（每个信标到最近信标的水平距离）的中位数
+pub fn median_beacon_spacing(beacons: &BeaconSet) -> Option<f64> {
+    let all = beacons.all();
+    if all.len() < 2 {
+        return None;
+    }
+    let mut nearest: Vec<f64> = all
+        .iter()
+        .map(|a| {
+            all.iter()
+                .filter(|b| b.id != a.id)
+                .map(|b| ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt())
+                .fold(f64::INFINITY, f64::min)
+        })
+        .filter(|d| d.is_finite() && *d > 0.0)
+        .collect();
+    if nearest.is_empty() {
+        return None;
+    }
+    nearest.sort_by(|a, b| a.total_cmp(b));
+    Some(nearest[nearest.len() / 2])
+}
+
+/// 按信标间距推断坐标单位
+///
+/// 室内信标间距通常为 1 ~ 30 米：中位数小于 50 按米、50 ~ 5000 按厘米、更大按毫米
+pub fn infer_coordinate_unit(beacons: &BeaconSet) -> Option<DistanceUnit> {
+    let spacing = median_beacon_spacing(beacons)?;
+    Some(if spacing < 50.0 {
+        DistanceUnit::Meter
+    } else if spacing <= 5000.0 {
+        DistanceUnit::Centimeter
+    } else {
+        DistanceUnit::Millimeter
+    })
+}
+
+/// 检查信标坐标与测距模型的单位是否一致
+pub fn check_unit_consistency(beacons: &BeaconSet, model: &RSSIModel) -> Vec<UnitWarning> {
+    let mut warnings = Vec::new();
+    if let (Some(inferred), Some(median_spacing)) = (infer_coordinate_unit(beacons), median_beacon_spacing(beacons))
+        && inferred != model.unit
+    {
+        warnings.push(UnitWarning::CoordinateUnitMismatch {
+            inferred,
+            model: model.unit,
+            median_spacing,
+        });
+    }
+
+    let distance_m = model.convert_to_unit(model.rssi_to_distance(REFERENCE_RSSI), DistanceUnit::Meter);
+    if !(0.3..=100.0).contains(&distance_m) {
+        warnings.push(UnitWarning::ImplausibleModelRange {
+            rssi: REFERENCE_RSSI,
+            distance_m,
+        });
+    }
+    warnings
+}
+
+impl FloorPlanAnnotation {
+    /// 转换为信标集合并检查与测距模型的单位一致性
+    ///
+    /// # 返回
+    /// - 信标集合及警告列表；坐标与模型单位明显不一致时返回错误
+    pub fn to_beacon_set_checked(&self, model: &RSSIModel) -> Result<(BeaconSet, Vec<UnitWarning>), String> {
+        let beacons = self.to_beacon_set();
+        let warnings = check_unit_consistency(&beacons, model);
+        if let Some(error) = warnings.iter().find(|w| w.is_error()) {
+            return Err(format!("{}（可调整标注文件的 scale 或模型单位）", error));
+        }
+        Ok((beacons, warnings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::Beacon;
+
+    fn grid(spacing: f64) -> BeaconSet {
+        BeaconSet::from_vec(vec![
+            Beacon::new("B1".to_string(), "b1".to_string(), 0.0, 0.0, 0.0),
+            Beacon::new("B2".to_string(), "b2".to_string(), spacing, 0.0, 0.0),
+            Beacon::new("B3".to_string(), "b3".to_string(), 0.0, spacing, 0.0),
+        ])
+    }
+
+    #[test]
+    fn test_infer_unit_and_mismatch() {
+        assert_eq!(infer_coordinate_unit(&grid(6.0)), Some(DistanceUnit::Meter));
+        assert_eq!(infer_coordinate_unit(&grid(600.0)), Some(DistanceUnit::Centimeter));
+        assert_eq!(infer_coordinate_unit(&grid(6000.0)), Some(DistanceUnit::Millimeter));
+
+        let model = RSSIModel::default();
+        assert!(check_unit_consistency(&grid(600.0), &model).is_empty());
+        let warnings = check_unit_consistency(&grid(6.0), &model);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].is_error());
+
+        let json = r#"{"scale": 0.05, "beacons": [
+            {"id": "B1", "px": 0, "py": 0}, {"id": "B2", "px": 100, "py": 0}, {"id": "B3", "px": 0, "py": 100}
+        ]}"#;
+        let annotation = FloorPlanAnnotation::from_json(json).unwrap();
+        assert!(annotation.to_beacon_set_checked(&model).is_err());
+    }
+
+    #[test]
+    fn test_implausible_model_range() {
+        // 截距单位写错导致 -75 dBm 对应数公里
+        let model = RSSIModel::log_distance(-10.0, -20.0, DistanceUnit::Meter);
+        let warnings = check_unit_consistency(&grid(6.0), &model);
+        assert!(matches!(warnings[..], [UnitWarning::ImplausibleModelRange { .. }]));
+        assert!(!warnings[0].is_error());
+    }
+}
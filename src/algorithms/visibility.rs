@@ -0,0 +1,198 @@
+/// 信标可见性矩阵
+///
+/// 按网格单元或区域统计各信标被收到的比例和平均 RSSI，
+/// 用于诊断覆盖空洞以及为信标选择策略提供依据。
+/// 数据来自录制回放或实时运行：每次记录一个已知/估计位置和当时的信号读数
+
+use crate::algorithms::{LocationResult, SignalReadings, ZoneMap};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// 统计区域
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum VisibilityArea {
+    /// 网格单元 (列, 行)
+    Cell(i64, i64),
+    /// 命名区域
+    Zone(String),
+}
+
+impl fmt::Display for VisibilityArea {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VisibilityArea::Cell(column, row) => write!(f, "cell({},{})", column, row),
+            VisibilityArea::Zone(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// 单个区域内单个信标的可见性
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BeaconVisibility {
+    /// 收到该信标的记录数
+    pub heard_count: usize,
+    /// 收到比例（收到次数 / 区域内记录数）
+    pub heard_ratio: f64,
+    /// 平均 RSSI (dBm)
+    pub mean_rssi: f64,
+}
+
+/// 区域划分方式
+#[derive(Clone, Debug)]
+enum Partition {
+    Grid(f64),
+    Zones(ZoneMap),
+}
+
+/// 信标可见性矩阵
+#[derive(Clone, Debug)]
+pub struct VisibilityMatrix {
+    partition: Partition,
+    /// 区域 -> 记录数
+    samples: BTreeMap<VisibilityArea, usize>,
+    /// (区域, 信标) -> (收到次数, RSSI 之和)
+    heard: BTreeMap<(VisibilityArea, String), (usize, f64)>,
+}
+
+impl VisibilityMatrix {
+    /// 按边长为 `cell_size` 的网格统计
+    pub fn grid(cell_size: f64) -> Self {
+        Self::with_partition(Partition::Grid(cell_size.abs().max(f64::EPSILON)))
+    }
+
+    /// 按区域统计，不在任何区域内的记录被忽略
+    pub fn zones(zones: ZoneMap) -> Self {
+        Self::with_partition(Partition::Zones(zones))
+    }
+
+    fn with_partition(partition: Partition) -> Self {
+        VisibilityMatrix {
+            partition,
+            samples: BTreeMap::new(),
+            heard: BTreeMap::new(),
+        }
+    }
+
+    /// 位置所属的统计区域
+    pub fn area_of(&self, position: &LocationResult) -> Option<VisibilityArea> {
+        match &self.partition {
+            Partition::Grid(size) => Some(VisibilityArea::Cell(
+                (position.x / size).floor() as i64,
+                (position.y / size).floor() as i64,
+            )),
+            Partition::Zones(zones) => zones.zone_of(position).map(|z| VisibilityArea::Zone(z.name.clone())),
+        }
+    }
+
+    /// 记录一个位置及当时的信号读数
+    ///
+    /// # 返回
+    /// - 记录所属的区域，或 None 如果位置不在任何区域内
+    pub fn record(&mut self, position: &LocationResult, signals: &SignalReadings) -> Option<VisibilityArea> {
+        let area = self.area_of(position)?;
+        *self.samples.entry(area.clone()).or_insert(0) += 1;
+        for (beacon_id, rssi) in signals.all() {
+            let entry = self.heard.entry((area.clone(), beacon_id.clone())).or_insert((0, 0.0));
+            entry.0 += 1;
+            entry.1 += *rssi as f64;
+        }
+        Some(area)
+    }
+
+    /// 所有有记录的区域
+    pub fn areas(&self) -> Vec<&VisibilityArea> {
+        self.samples.keys().collect()
+    }
+
+    /// 所有被收到过的信标
+    pub fn beacons(&self) -> BTreeSet<&str> {
+        self.heard.keys().map(|(_, id)| id.as_str()).collect()
+    }
+
+    /// 区域内某信标的可见性
+    pub fn get(&self, area: &VisibilityArea, beacon_id: &str) -> Option<BeaconVisibility> {
+        let total = *self.samples.get(area)?;
+        let (count, sum) = *self.heard.get(&(area.clone(), beacon_id.to_string()))?;
+        Some(BeaconVisibility {
+            heard_count: count,
+            heard_ratio: count as f64 / total as f64,
+            mean_rssi: sum / count as f64,
+        })
+    }
+
+    /// 区域内收到比例不低于 `min_ratio` 的信标，按平均 RSSI 从强到弱排序
+    pub fn reliable_beacons(&self, area: &VisibilityArea, min_ratio: f64) -> Vec<(String, BeaconVisibility)> {
+        let mut beacons: Vec<(String, BeaconVisibility)> = self
+            .heard
+            .keys()
+            .filter(|(a, _)| a == area)
+            .filter_map(|(_, id)| self.get(area, id).map(|v| (id.clone(), v)))
+            .filter(|(_, v)| v.heard_ratio >= min_ratio)
+            .collect();
+        beacons.sort_by(|a, b| b.1.mean_rssi.total_cmp(&a.1.mean_rssi));
+        beacons
+    }
+
+    /// 覆盖空洞：可靠信标（收到比例不低于 `min_ratio`）少于 `min_beacons` 个的区域
+    pub fn coverage_holes(&self, min_beacons: usize, min_ratio: f64) -> Vec<&VisibilityArea> {
+        self.samples
+            .keys()
+            .filter(|area| self.reliable_beacons(area, min_ratio).len() < min_beacons)
+            .collect()
+    }
+
+    /// 导出为 CSV：area,beacon_id,samples,heard,ratio,mean_rssi
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("area,beacon_id,samples,heard,ratio,mean_rssi\n");
+        for (area, beacon_id) in self.heard.keys() {
+            if let Some(v) = self.get(area, beacon_id) {
+                csv.push_str(&format!(
+                    "{},{},{},{},{:.3},{:.1}\n",
+                    area, beacon_id, self.samples[area], v.heard_count, v.heard_ratio, v.mean_rssi
+                ));
+            }
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::Zone;
+
+    fn at(x: f64, y: f64) -> LocationResult {
+        LocationResult::new(x, y, 0.0, 1.0, 0.0, "survey".to_string(), 0)
+    }
+
+    #[test]
+    fn test_grid_visibility_and_holes() {
+        let mut matrix = VisibilityMatrix::grid(500.0);
+        matrix.record(&at(100.0, 100.0), &SignalReadings::from_pairs(vec![("B1", -60), ("B2", -70), ("B3", -75)]));
+        matrix.record(&at(200.0, 100.0), &SignalReadings::from_pairs(vec![("B1", -62), ("B2", -72)]));
+        let far = matrix.record(&at(900.0, 100.0), &SignalReadings::from_pairs(vec![("B3", -85)])).unwrap();
+
+        let near = VisibilityArea::Cell(0, 0);
+        let b1 = matrix.get(&near, "B1").unwrap();
+        assert_eq!(b1.heard_count, 2);
+        assert_eq!(b1.mean_rssi, -61.0);
+        assert_eq!(matrix.get(&near, "B3").unwrap().heard_ratio, 0.5);
+
+        let reliable = matrix.reliable_beacons(&near, 0.8);
+        assert_eq!(reliable.iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>(), vec!["B1", "B2"]);
+        assert_eq!(matrix.coverage_holes(2, 0.8), vec![&far]);
+        assert_eq!(matrix.to_csv().lines().count(), 5);
+    }
+
+    #[test]
+    fn test_zone_partition_ignores_outside() {
+        let zones = ZoneMap::from_vec(vec![Zone::rectangle("hall", 0.0, 0.0, 100.0, 100.0)]);
+        let mut matrix = VisibilityMatrix::zones(zones);
+        assert!(matrix.record(&at(500.0, 50.0), &SignalReadings::from_pairs(vec![("B1", -60)])).is_none());
+        assert_eq!(
+            matrix.record(&at(50.0, 50.0), &SignalReadings::from_pairs(vec![("B1", -60)])),
+            Some(VisibilityArea::Zone("hall".to_string()))
+        );
+        assert_eq!(matrix.areas().len(), 1);
+    }
+}
@@ -0,0 +1,222 @@
+/// 对外推送数据的版本化格式
+///
+/// 位置、事件和状态三类负载统一带 `schema_version` 字段（语义化版本），
+/// 由 MQTT / WebSocket / HTTP 等推送通道原样序列化为 JSON。
+/// 结构体同时实现 `Serialize` 与 `Deserialize`，其他语言的消费方可据此生成代码。
+///
+/// 兼容规则：
+/// - 新增可选字段只增加次版本号，旧消费方应忽略未知字段
+/// - 删除或修改字段含义会增加主版本号
+///
+/// 格式（schema_version = "1.1.0"）：
+/// - 位置：`{schema_version, type: "position", device_id, x, y, z, confidence, error, method, beacon_count, timestamp, metadata?}`
+/// - 事件：`{schema_version, type: "event", kind, message, position?, timestamp}`
+/// - 状态：`{schema_version, type: "status", crate_version, observations_fed, observations_ignored, solves, fixes, observations_dropped, beacon_count, timestamp}`
+
+use crate::algorithms::{BlunavEvent, EngineStats, LocationResult, Metadata};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 当前推送格式版本
+pub const WIRE_SCHEMA_VERSION: &str = "1.1.0";
+
+/// 推送负载
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WirePayload {
+    /// 位置
+    Position(PositionPayload),
+    /// 事件
+    Event(EventPayload),
+    /// 状态
+    Status(StatusPayload),
+}
+
+/// 位置负载
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PositionPayload {
+    /// 格式版本
+    pub schema_version: String,
+    /// 设备 ID
+    pub device_id: String,
+    /// X 坐标
+    pub x: f64,
+    /// Y 坐标
+    pub y: f64,
+    /// Z 坐标
+    pub z: f64,
+    /// 置信度 (0.0 ~ 1.0)
+    pub confidence: f64,
+    /// 估计误差（坐标单位）
+    pub error: f64,
+    /// 算法名称
+    pub method: String,
+    /// 参与定位的信标数
+    pub beacon_count: usize,
+    /// 时间戳
+    pub timestamp: DateTime<Utc>,
+    /// 附加业务数据（1.1.0 新增，为空时省略）
+    #[serde(default, skip_serializing_if = "Metadata::is_empty")]
+    pub metadata: Metadata,
+}
+
+impl PositionPayload {
+    /// 由定位结果创建
+    pub fn new(device_id: impl Into<String>, result: &LocationResult) -> Self {
+        PositionPayload {
+            schema_version: WIRE_SCHEMA_VERSION.to_string(),
+            device_id: device_id.into(),
+            x: result.x,
+            y: result.y,
+            z: result.z,
+            confidence: result.confidence,
+            error: result.error,
+            method: result.method.clone(),
+            beacon_count: result.beacon_count,
+            timestamp: result.timestamp,
+            metadata: result.metadata.clone(),
+        }
+    }
+}
+
+/// 事件负载
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EventPayload {
+    /// 格式版本
+    pub schema_version: String,
+    /// 事件类型，参见 `BlunavEvent::kind`
+    pub kind: String,
+    /// 事件说明
+    pub message: String,
+    /// 事件附带的位置（定位类事件）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<PositionPayload>,
+    /// 时间戳
+    pub timestamp: DateTime<Utc>,
+}
+
+impl EventPayload {
+    /// 由事件创建
+    ///
+    /// # 参数
+    /// - `device_id`: 事件所属设备，用于定位类事件的位置负载
+    /// - `event`: 事件
+    /// - `timestamp`: 事件时间
+    pub fn new(device_id: &str, event: &BlunavEvent, timestamp: DateTime<Utc>) -> Self {
+        let position = match event {
+            BlunavEvent::FixComputed(result)
+            | BlunavEvent::RawFixComputed(result)
+            | BlunavEvent::PositionSettled(result)
+            | BlunavEvent::FixRejected { result, .. } => Some(PositionPayload::new(device_id, result)),
+            _ => None,
+        };
+        EventPayload {
+            schema_version: WIRE_SCHEMA_VERSION.to_string(),
+            kind: event.kind().to_string(),
+            message: event.to_string(),
+            position,
+            timestamp,
+        }
+    }
+}
+
+/// 状态负载
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StatusPayload {
+    /// 格式版本
+    pub schema_version: String,
+    /// 库版本
+    pub crate_version: String,
+    /// 接收的观测数
+    pub observations_fed: u64,
+    /// 忽略的观测数
+    pub observations_ignored: u64,
+    /// 求解次数
+    pub solves: u64,
+    /// 输出的定位结果数
+    pub fixes: u64,
+    /// 因积压丢弃的观测数
+    pub observations_dropped: u64,
+    /// 配置的信标数
+    pub beacon_count: usize,
+    /// 时间戳
+    pub timestamp: DateTime<Utc>,
+}
+
+impl StatusPayload {
+    /// 由引擎统计创建
+    pub fn new(stats: &EngineStats, beacon_count: usize, timestamp: DateTime<Utc>) -> Self {
+        StatusPayload {
+            schema_version: WIRE_SCHEMA_VERSION.to_string(),
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            observations_fed: stats.observations_fed,
+            observations_ignored: stats.observations_ignored,
+            solves: stats.solves,
+            fixes: stats.fixes,
+            observations_dropped: stats.observations_dropped,
+            beacon_count,
+            timestamp,
+        }
+    }
+}
+
+impl WirePayload {
+    /// 负载的格式版本
+    pub fn schema_version(&self) -> &str {
+        match self {
+            WirePayload::Position(p) => &p.schema_version,
+            WirePayload::Event(p) => &p.schema_version,
+            WirePayload::Status(p) => &p.schema_version,
+        }
+    }
+
+    /// 序列化为 JSON
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| format!("负载序列化失败: {}", e))
+    }
+
+    /// 从 JSON 解析，主版本号与当前版本不同时返回错误
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let payload: WirePayload = serde_json::from_str(json).map_err(|e| format!("负载解析失败: {}", e))?;
+        if !is_compatible_schema(payload.schema_version()) {
+            return Err(format!(
+                "不兼容的负载格式版本 {}（当前 {}）",
+                payload.schema_version(),
+                WIRE_SCHEMA_VERSION
+            ));
+        }
+        Ok(payload)
+    }
+}
+
+/// 格式版本是否与当前版本兼容（主版本号相同）
+pub fn is_compatible_schema(schema_version: &str) -> bool {
+    let major = |v: &str| v.split('.').next().and_then(|m| m.parse::<u32>().ok());
+    major(schema_version).is_some() && major(schema_version) == major(WIRE_SCHEMA_VERSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_round_trip_and_version_check() {
+        let result = LocationResult::new(120.0, 80.0, 0.0, 0.9, 25.0, "least_squares".to_string(), 4)
+            .with_metadata("order_id", "SO-1001");
+        let position = WirePayload::Position(PositionPayload::new("tag-1", &result));
+        let json = position.to_json().unwrap();
+        assert!(json.contains("\"type\":\"position\""));
+        assert!(json.contains("\"schema_version\":\"1.1.0\""));
+        assert!(json.contains("\"metadata\":{\"order_id\":\"SO-1001\"}"));
+        assert_eq!(WirePayload::from_json(&json).unwrap(), position);
+
+        let event = EventPayload::new("tag-1", &BlunavEvent::FixComputed(result.clone()), result.timestamp);
+        assert_eq!(event.kind, "fix_computed");
+        assert_eq!(event.position.as_ref().unwrap().device_id, "tag-1");
+        let zone = EventPayload::new("tag-1", &BlunavEvent::ZoneEntered { zone: "dock".to_string(), device_id: "tag-1".to_string() }, result.timestamp);
+        assert!(!WirePayload::Event(zone).to_json().unwrap().contains("position"));
+
+        assert!(is_compatible_schema("1.4.2"));
+        assert!(WirePayload::from_json(&json.replace("1.1.0", "2.0.0")).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+/// 区域定义
+///
+/// 区域为平面多边形（可选高度范围），用于判断定位结果所在的房间/功能区
+
+use crate::algorithms::LocationResult;
+
+/// 单个区域
+#[derive(Clone, Debug, PartialEq)]
+pub struct Zone {
+    /// 区域名称
+    pub name: String,
+    /// 多边形顶点 (x, y)，按顺序排列，首尾自动闭合
+    pub polygon: Vec<(f64, f64)>,
+    /// 高度范围 (最小, 最大)，None 表示不限高度
+    pub z_range: Option<(f64, f64)>,
+}
+
+impl Zone {
+    /// 创建多边形区域
+    pub fn new(name: impl Into<String>, polygon: Vec<(f64, f64)>) -> Self {
+        Zone {
+            name: name.into(),
+            polygon,
+            z_range: None,
+        }
+    }
+
+    /// 创建矩形区域
+    pub fn rectangle(name: impl Into<String>, min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Self {
+        Self::new(name, vec![(min_x, min_y), (max_x, min_y), (max_x, max_y), (min_x, max_y)])
+    }
+
+    /// 限定高度范围（如某一楼层）
+    pub fn with_z_range(mut self, min: f64, max: f64) -> Self {
+        self.z_range = Some((min.min(max), min.max(max)));
+        self
+    }
+
+    /// 平面点是否在区域内（射线法）
+    pub fn contains_xy(&self, x: f64, y: f64) -> bool {
+        let n = self.polygon.len();
+        if n < 3 {
+            return false;
+        }
+        let mut inside = false;
+        let mut j = n - 1;
+        for i in 0..n {
+            let (xi, yi) = self.polygon[i];
+            let (xj, yj) = self.polygon[j];
+            if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+                inside = !inside;
+            }
+            j = i;
+        }
+        inside
+    }
+
+    /// 定位结果是否在区域内（包括高度范围）
+    pub fn contains(&self, result: &LocationResult) -> bool {
+        let in_height = self
+            .z_range
+            .is_none_or(|(min, max)| result.z >= min && result.z <= max);
+        in_height && self.contains_xy(result.x, result.y)
+    }
+
+    /// 区域面积（鞋带公式）
+    pub fn area(&self) -> f64 {
+        let n = self.polygon.len();
+        if n < 3 {
+            return 0.0;
+        }
+        let twice = (0..n)
+            .map(|i| {
+                let (x1, y1) = self.polygon[i];
+                let (x2, y2) = self.polygon[(i + 1) % n];
+                x1 * y2 - x2 * y1
+            })
+            .sum::<f64>();
+        twice.abs() / 2.0
+    }
+}
+
+/// 区域集合
+#[derive(Clone, Debug, Default)]
+pub struct ZoneMap {
+    zones: Vec<Zone>,
+}
+
+impl ZoneMap {
+    /// 创建空集合
+    pub fn new() -> Self {
+        ZoneMap { zones: Vec::new() }
+    }
+
+    /// 从区域列表创建
+    pub fn from_vec(zones: Vec<Zone>) -> Self {
+        ZoneMap { zones }
+    }
+
+    /// 添加区域
+    pub fn add_zone(&mut self, zone: Zone) {
+        self.zones.push(zone);
+    }
+
+    /// 按名称获取区域
+    pub fn get(&self, name: &str) -> Option<&Zone> {
+        self.zones.iter().find(|z| z.name == name)
+    }
+
+    /// 所有区域
+    pub fn all(&self) -> &[Zone] {
+        &self.zones
+    }
+
+    /// 区域数量
+    pub fn len(&self) -> usize {
+        self.zones.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.zones.is_empty()
+    }
+
+    /// 定位结果所在的第一个区域（区域重叠时按添加顺序优先）
+    pub fn zone_of(&self, result: &LocationResult) -> Option<&Zone> {
+        self.zones.iter().find(|z| z.contains(result))
+    }
+
+    /// 包含定位结果的所有区域
+    pub fn zones_containing(&self, result: &LocationResult) -> Vec<&Zone> {
+        self.zones.iter().filter(|z| z.contains(result)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(x: f64, y: f64, z: f64) -> LocationResult {
+        LocationResult::new(x, y, z, 0.8, 10.0, "m".to_string(), 3)
+    }
+
+    #[test]
+    fn test_zone_contains_and_area() {
+        // L 形区域
+        let zone = Zone::new("lobby", vec![(0.0, 0.0), (200.0, 0.0), (200.0, 100.0), (100.0, 100.0), (100.0, 200.0), (0.0, 200.0)])
+            .with_z_range(0.0, 300.0);
+        assert!(zone.contains(&fix(50.0, 150.0, 100.0)));
+        assert!(!zone.contains(&fix(150.0, 150.0, 100.0)));
+        assert!(!zone.contains(&fix(50.0, 150.0, 400.0)));
+        assert_eq!(zone.area(), 30_000.0);
+    }
+
+    #[test]
+    fn test_zone_map_lookup() {
+        let map = ZoneMap::from_vec(vec![
+            Zone::rectangle("A", 0.0, 0.0, 100.0, 100.0),
+            Zone::rectangle("B", 50.0, 0.0, 150.0, 100.0),
+        ]);
+        assert_eq!(map.zone_of(&fix(75.0, 50.0, 0.0)).unwrap().name, "A");
+        assert_eq!(map.zones_containing(&fix(75.0, 50.0, 0.0)).len(), 2);
+        assert!(map.zone_of(&fix(500.0, 50.0, 0.0)).is_none());
+    }
+}
@@ -224,6 +224,15 @@ pub fn trilateration_least_squares(
     })
 }
 
+/// 位置预测结果
+#[derive(Clone, Debug)]
+pub struct PositionPrediction {
+    pub x: f64,
+    pub y: f64,
+    pub std_x: f64,  // x 方向标准差
+    pub std_y: f64,  // y 方向标准差
+}
+
 /// 卡尔曼滤波器 - 用于平滑时间序列
 pub struct KalmanFilter {
     pub x: f64,
@@ -252,8 +261,8 @@ impl KalmanFilter {
         // 预测
         self.x += self.vx * dt;
         self.y += self.vy * dt;
-        self.p_xx += self.p_vv * dt * dt + 10.0;
-        self.p_yy += self.p_vv * dt * dt + 10.0;
+        self.p_xx = self.predicted_variance(self.p_xx, dt);
+        self.p_yy = self.predicted_variance(self.p_yy, dt);
 
         // 更新
         let kx = self.p_xx / (self.p_xx + 50.0);
@@ -275,6 +284,24 @@ impl KalmanFilter {
     pub fn position(&self) -> (f64, f64) {
         (self.x, self.y)
     }
+
+    /// 预测 `duration` 之后的位置
+    ///
+    /// 按当前速度外推，协方差随时间增长（与 update 的预测步一致，含过程噪声；不修改滤波器状态）
+    pub fn predict(&self, duration: std::time::Duration) -> PositionPrediction {
+        let dt = duration.as_secs_f64();
+        PositionPrediction {
+            x: self.x + self.vx * dt,
+            y: self.y + self.vy * dt,
+            std_x: self.predicted_variance(self.p_xx, dt).sqrt(),
+            std_y: self.predicted_variance(self.p_yy, dt).sqrt(),
+        }
+    }
+
+    /// 预测步的位置方差：速度不确定性随 dt² 增长，再加上固定的过程噪声
+    fn predicted_variance(&self, p: f64, dt: f64) -> f64 {
+        p + self.p_vv * dt * dt + 10.0
+    }
 }
 
 /// ============================================================================
@@ -319,4 +346,18 @@ mod tests {
         let d_at_ref = model.rssi_to_distance(-49);
         println!("RSSI -49 dBm 对应距离: {:.2} cm", d_at_ref);
     }
+
+    #[test]
+    fn test_kalman_predict() {
+        let mut filter = KalmanFilter::new(0.0, 0.0);
+        filter.vx = 50.0;
+        filter.vy = -20.0;
+
+        let now = filter.predict(std::time::Duration::ZERO);
+        let later = filter.predict(std::time::Duration::from_secs(2));
+        assert_eq!((later.x, later.y), (100.0, -40.0));
+        assert!(later.std_x > now.std_x);
+        assert_eq!(later.std_x, (100.0f64 + 4.0 + 10.0).sqrt());
+        assert_eq!(filter.position(), (0.0, 0.0));
+    }
 }